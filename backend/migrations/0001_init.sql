@@ -0,0 +1,142 @@
+-- Core schema for the MayWin nurse scheduling API.
+
+CREATE TABLE organizations (
+    organization_id BIGSERIAL PRIMARY KEY,
+    name            TEXT NOT NULL,
+    status          TEXT NOT NULL DEFAULT 'active',
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE organization_site (
+    site_id         BIGSERIAL PRIMARY KEY,
+    organization_id BIGINT NOT NULL REFERENCES organizations(organization_id),
+    name            TEXT NOT NULL,
+    time_zone       TEXT NOT NULL DEFAULT 'UTC',
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE units (
+    unit_id         BIGSERIAL PRIMARY KEY,
+    organization_id BIGINT NOT NULL REFERENCES organizations(organization_id),
+    site_id         BIGINT REFERENCES organization_site(site_id),
+    name            TEXT NOT NULL,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE staffs (
+    staff_id         BIGSERIAL PRIMARY KEY,
+    unit_id          BIGINT NOT NULL REFERENCES units(unit_id),
+    code             TEXT NOT NULL,
+    full_name        TEXT NOT NULL,
+    role             TEXT,
+    skills           TEXT[] NOT NULL DEFAULT '{}',
+    max_weekly_hours INT,
+    is_enabled       BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (unit_id, code)
+);
+
+CREATE TABLE shift_patterns (
+    shift_id   BIGSERIAL PRIMARY KEY,
+    unit_id    BIGINT NOT NULL REFERENCES units(unit_id),
+    name       TEXT NOT NULL,
+    start_time TIME NOT NULL,
+    end_time   TIME NOT NULL,
+    is_night   BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE coverage_requirement (
+    coverage_id    BIGSERIAL PRIMARY KEY,
+    unit_id        BIGINT NOT NULL REFERENCES units(unit_id),
+    day            DATE NOT NULL,
+    shift_id       BIGINT NOT NULL REFERENCES shift_patterns(shift_id),
+    required_count INT NOT NULL,
+    required_skill TEXT,
+    UNIQUE (unit_id, day, shift_id)
+);
+
+CREATE TABLE availability (
+    availability_id BIGSERIAL PRIMARY KEY,
+    staff_id        BIGINT NOT NULL REFERENCES staffs(staff_id),
+    day             DATE NOT NULL,
+    shift_id        BIGINT NOT NULL REFERENCES shift_patterns(shift_id),
+    -- 1 = can work, 0 = cannot work
+    value           INT NOT NULL DEFAULT 1,
+    UNIQUE (staff_id, day, shift_id)
+);
+
+CREATE TABLE preferences (
+    preference_id BIGSERIAL PRIMARY KEY,
+    staff_id      BIGINT NOT NULL REFERENCES staffs(staff_id),
+    day           DATE NOT NULL,
+    shift_id      BIGINT NOT NULL REFERENCES shift_patterns(shift_id),
+    -- penalty >= 0; higher means the staff wants to avoid this cell more
+    penalty       INT NOT NULL DEFAULT 0,
+    UNIQUE (staff_id, day, shift_id)
+);
+
+CREATE TABLE scenarios (
+    scenario_id BIGSERIAL PRIMARY KEY,
+    unit_id     BIGINT NOT NULL REFERENCES units(unit_id),
+    payload     JSONB NOT NULL,
+    input_hash  TEXT NOT NULL,
+    status      TEXT NOT NULL DEFAULT 'ready',
+    -- where the scenario came from: web / chatbot / csv
+    source      TEXT NOT NULL DEFAULT 'web',
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (unit_id, input_hash)
+);
+
+CREATE TABLE policy_sets (
+    policy_id  BIGSERIAL PRIMARY KEY,
+    unit_id    BIGINT NOT NULL REFERENCES units(unit_id),
+    name       TEXT NOT NULL,
+    weights    JSONB NOT NULL DEFAULT '{}',
+    hard_rules JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE solver_runs (
+    run_id        BIGSERIAL PRIMARY KEY,
+    scenario_id   BIGINT NOT NULL REFERENCES scenarios(scenario_id),
+    policy_id     BIGINT REFERENCES policy_sets(policy_id),
+    status        TEXT NOT NULL DEFAULT 'queued',
+    solver_status TEXT,
+    objective     DOUBLE PRECISION,
+    workers       INT,
+    started_at    TIMESTAMPTZ,
+    finished_at   TIMESTAMPTZ,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE assignments (
+    assignment_id BIGSERIAL PRIMARY KEY,
+    run_id        BIGINT NOT NULL REFERENCES solver_runs(run_id) ON DELETE CASCADE,
+    staff_id      BIGINT NOT NULL REFERENCES staffs(staff_id),
+    day           DATE NOT NULL,
+    shift_id      BIGINT NOT NULL REFERENCES shift_patterns(shift_id),
+    -- MODEL / POSTFILL / MANUAL
+    source        TEXT NOT NULL DEFAULT 'MODEL',
+    UNIQUE (run_id, staff_id, day, shift_id)
+);
+
+CREATE TABLE kpi (
+    kpi_id            BIGSERIAL PRIMARY KEY,
+    run_id            BIGINT NOT NULL UNIQUE REFERENCES solver_runs(run_id) ON DELETE CASCADE,
+    total_assignments INT NOT NULL DEFAULT 0,
+    understaffed_cells INT NOT NULL DEFAULT 0,
+    overtime_hours    DOUBLE PRECISION NOT NULL DEFAULT 0,
+    avg_satisfaction  DOUBLE PRECISION,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE users (
+    user_id         BIGSERIAL PRIMARY KEY,
+    organization_id BIGINT REFERENCES organizations(organization_id),
+    full_name       TEXT NOT NULL UNIQUE,
+    role            TEXT NOT NULL DEFAULT 'staff',
+    password_hash   TEXT NOT NULL,
+    is_active       BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT now()
+);
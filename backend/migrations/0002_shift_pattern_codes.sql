@@ -0,0 +1,6 @@
+-- Stable per-unit shift codes so solver output survives shift renames.
+ALTER TABLE shift_patterns ADD COLUMN code TEXT;
+-- Backfill existing rows with generated codes.
+UPDATE shift_patterns SET code = 'SH' || shift_id;
+ALTER TABLE shift_patterns ALTER COLUMN code SET NOT NULL;
+CREATE UNIQUE INDEX shift_patterns_unit_code_key ON shift_patterns (unit_id, code);
@@ -0,0 +1,2 @@
+-- User-controlled ordering for shift patterns in the planning grid.
+ALTER TABLE shift_patterns ADD COLUMN display_order INT NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- Machine-readable failure classification for solver runs.
+-- failure_reason is one of: solver_error, mapping_failed, infeasible,
+-- timeout, cancelled. NULL for runs that did not fail (or legacy rows).
+ALTER TABLE solver_runs ADD COLUMN failure_reason TEXT;
+ALTER TABLE solver_runs ADD COLUMN failure_detail TEXT;
@@ -0,0 +1,14 @@
+-- Transactional outbox: webhook events are written in the same transaction
+-- as the state change they describe and delivered asynchronously by a
+-- background dispatcher (at-least-once).
+CREATE TABLE outbox (
+    outbox_id  BIGSERIAL PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    payload    JSONB NOT NULL,
+    attempts   INT NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    sent_at    TIMESTAMPTZ
+);
+
+CREATE INDEX outbox_unsent_idx ON outbox (outbox_id) WHERE sent_at IS NULL;
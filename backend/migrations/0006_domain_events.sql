@@ -0,0 +1,11 @@
+-- Append-only domain event log for analytics and activity feeds.
+CREATE TABLE domain_events (
+    event_id   BIGSERIAL PRIMARY KEY,
+    event_type TEXT NOT NULL,
+    unit_id    BIGINT,
+    entity_id  BIGINT,
+    payload    JSONB NOT NULL DEFAULT '{}',
+    at         TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX domain_events_unit_idx ON domain_events (unit_id, event_id);
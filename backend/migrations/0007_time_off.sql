@@ -0,0 +1,14 @@
+-- Approved time-off ranges per staff member; the solver and manual edits
+-- must never schedule someone inside an approved range.
+CREATE TABLE time_off (
+    time_off_id BIGSERIAL PRIMARY KEY,
+    staff_id    BIGINT NOT NULL REFERENCES staffs(staff_id) ON DELETE CASCADE,
+    start_day   DATE NOT NULL,
+    end_day     DATE NOT NULL,
+    status      TEXT NOT NULL DEFAULT 'approved',
+    reason      TEXT,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (start_day <= end_day)
+);
+
+CREATE INDEX time_off_staff_idx ON time_off (staff_id, start_day, end_day);
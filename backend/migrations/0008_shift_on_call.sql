@@ -0,0 +1,3 @@
+-- On-call shifts are standby, not worked time: they don't count toward
+-- worked-hours or overtime sums and may coexist with a working shift.
+ALTER TABLE shift_patterns ADD COLUMN is_on_call BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,10 @@
+-- Free-form annotations on solver runs ("approved by Dr. X", ...).
+CREATE TABLE run_notes (
+    note_id       BIGSERIAL PRIMARY KEY,
+    solver_run_id BIGINT NOT NULL REFERENCES solver_runs(run_id) ON DELETE CASCADE,
+    user_id       BIGINT REFERENCES users(user_id),
+    note          TEXT NOT NULL,
+    at            TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX run_notes_run_idx ON run_notes (solver_run_id, note_id);
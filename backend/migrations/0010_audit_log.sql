@@ -0,0 +1,14 @@
+-- Audit trail of who did what to which entity, scoped by organization.
+CREATE TABLE audit_log (
+    audit_id        BIGSERIAL PRIMARY KEY,
+    user_id         BIGINT REFERENCES users(user_id),
+    organization_id BIGINT REFERENCES organizations(organization_id),
+    action          TEXT NOT NULL,
+    entity_type     TEXT NOT NULL,
+    entity_id       BIGINT,
+    detail          JSONB NOT NULL DEFAULT '{}',
+    at              TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX audit_log_org_idx ON audit_log (organization_id, audit_id);
+CREATE INDEX audit_log_action_idx ON audit_log (action, at);
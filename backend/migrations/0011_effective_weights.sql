@@ -0,0 +1,2 @@
+-- The merged weights actually forwarded to the solver, for reproducibility.
+ALTER TABLE solver_runs ADD COLUMN effective_weights JSONB;
@@ -0,0 +1,43 @@
+-- Change tracking for incremental sync: updated_at on the unit-scoped
+-- planning tables, and tombstones so clients can drop deleted rows.
+ALTER TABLE staffs ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE shift_patterns ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE coverage_requirement ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE FUNCTION touch_updated_at() RETURNS trigger AS $$
+BEGIN
+    NEW.updated_at = now();
+    RETURN NEW;
+END $$ LANGUAGE plpgsql;
+
+CREATE TRIGGER staffs_touch BEFORE UPDATE ON staffs
+    FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+CREATE TRIGGER shift_patterns_touch BEFORE UPDATE ON shift_patterns
+    FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+CREATE TRIGGER coverage_requirement_touch BEFORE UPDATE ON coverage_requirement
+    FOR EACH ROW EXECUTE FUNCTION touch_updated_at();
+
+CREATE TABLE tombstones (
+    tombstone_id BIGSERIAL PRIMARY KEY,
+    unit_id      BIGINT NOT NULL,
+    entity_type  TEXT NOT NULL,
+    entity_id    BIGINT NOT NULL,
+    deleted_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX tombstones_unit_time_idx ON tombstones (unit_id, deleted_at);
+
+-- TG_ARGV: entity type label, primary key column name.
+CREATE FUNCTION record_tombstone() RETURNS trigger AS $$
+DECLARE old_row jsonb := to_jsonb(OLD);
+BEGIN
+    INSERT INTO tombstones (unit_id, entity_type, entity_id)
+    VALUES ((old_row->>'unit_id')::bigint, TG_ARGV[0], (old_row->>TG_ARGV[1])::bigint);
+    RETURN OLD;
+END $$ LANGUAGE plpgsql;
+
+CREATE TRIGGER staffs_tombstone AFTER DELETE ON staffs
+    FOR EACH ROW EXECUTE FUNCTION record_tombstone('staff', 'staff_id');
+CREATE TRIGGER shift_patterns_tombstone AFTER DELETE ON shift_patterns
+    FOR EACH ROW EXECUTE FUNCTION record_tombstone('shift_pattern', 'shift_id');
+CREATE TRIGGER coverage_requirement_tombstone AFTER DELETE ON coverage_requirement
+    FOR EACH ROW EXECUTE FUNCTION record_tombstone('coverage', 'coverage_id');
@@ -0,0 +1,7 @@
+-- At most one active policy per unit; the switch happens transactionally
+-- and this partial unique index backstops concurrent activations.
+ALTER TABLE policy_sets ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE UNIQUE INDEX policy_sets_one_active_per_unit
+    ON policy_sets (unit_id)
+    WHERE is_active;
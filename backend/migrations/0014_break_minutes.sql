@@ -0,0 +1,2 @@
+-- Unpaid break per shift, subtracted from worked-hours computations.
+ALTER TABLE shift_patterns ADD COLUMN break_minutes INTEGER NOT NULL DEFAULT 0;
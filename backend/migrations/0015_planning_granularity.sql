@@ -0,0 +1,5 @@
+-- How strictly the unit's shift/coverage setup is validated:
+--   none  - no structural checks (default)
+--   hour  - shift boundaries must fall on whole hours
+--   shift - shift definitions must not overlap each other
+ALTER TABLE units ADD COLUMN planning_granularity TEXT NOT NULL DEFAULT 'none';
@@ -0,0 +1,12 @@
+-- The solver maps shifts by name, so duplicate names within a unit make
+-- that mapping ambiguous. Disambiguate any existing duplicates, then
+-- enforce uniqueness case-insensitively.
+UPDATE shift_patterns sp
+SET name = sp.name || ' #' || sp.shift_id
+WHERE EXISTS (
+    SELECT 1 FROM shift_patterns other
+    WHERE other.unit_id = sp.unit_id
+      AND lower(other.name) = lower(sp.name)
+      AND other.shift_id < sp.shift_id
+);
+CREATE UNIQUE INDEX shift_patterns_unit_name_key ON shift_patterns (unit_id, lower(name));
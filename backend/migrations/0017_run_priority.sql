@@ -0,0 +1,2 @@
+-- Urgency of a queued run: higher values get a solver permit first.
+ALTER TABLE solver_runs ADD COLUMN priority INT NOT NULL DEFAULT 0;
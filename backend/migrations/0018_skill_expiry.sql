@@ -0,0 +1,9 @@
+-- Certifications lapse: a row here puts an expiry date on one of a staff
+-- member's skills. Skills without a row never expire, so the text[] on
+-- staffs keeps working unchanged.
+CREATE TABLE staff_skill_expiry (
+    staff_id   BIGINT NOT NULL REFERENCES staffs(staff_id) ON DELETE CASCADE,
+    skill      TEXT NOT NULL,
+    expires_on DATE NOT NULL,
+    PRIMARY KEY (staff_id, skill)
+);
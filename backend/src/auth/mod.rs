@@ -0,0 +1,172 @@
+//! Bearer-token authentication for the API.
+//!
+//! Every request under `/api/v1` (except `/api/v1/login`) must carry an
+//! HS256 JWT signed with `JWT_SECRET`. The middleware decodes it, enforces
+//! `exp`, and stores a [`CurrentUser`] in request extensions for handlers to
+//! pull with an extractor. When `JWT_SECRET` is unset the middleware passes
+//! everything through in dev/test `APP_ENV`, keeping local development and
+//! tests token-free; in any other environment a missing secret fails closed
+//! with 401 rather than leaving the API open.
+
+use axum::extract::{FromRequestParts, Request};
+use axum::http::{header, request::Parts, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+/// The authenticated caller, decoded from the bearer token.
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrentUser {
+    pub user_id: i64,
+    pub organization_id: Option<i64>,
+    pub role: String,
+}
+
+/// The token's claim set. `sub` carries the user id as a string per JWT
+/// convention; `org` and `role` mirror the users table.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: String,
+    pub org: Option<i64>,
+    pub role: String,
+    /// Expiry as a unix timestamp; enforced on every request.
+    pub exp: i64,
+}
+
+fn jwt_secret() -> Option<String> {
+    std::env::var("JWT_SECRET").ok().filter(|s| !s.is_empty())
+}
+
+/// Whether `APP_ENV` names a development-grade environment. Mirrors the
+/// solver URL fallback in `db.rs`: conveniences that are fine locally must
+/// not become the silent default in production.
+fn dev_environment() -> bool {
+    let app_env = std::env::var("APP_ENV").unwrap_or_else(|_| "dev".to_string());
+    matches!(app_env.as_str(), "dev" | "development" | "test")
+}
+
+/// Sign a token for a user. Used by the login endpoint and by tests.
+pub fn issue_token(
+    user_id: i64,
+    organization_id: Option<i64>,
+    role: &str,
+    ttl_secs: i64,
+) -> Result<String, (StatusCode, String)> {
+    let secret = jwt_secret().ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "JWT_SECRET is not configured".to_string(),
+    ))?;
+    let claims = Claims {
+        sub: user_id.to_string(),
+        org: organization_id,
+        role: role.to_string(),
+        exp: chrono::Utc::now().timestamp() + ttl_secs,
+    };
+    jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .map_err(super::routes::internal_error)
+}
+
+/// 401 with a JSON body, so API clients get a parseable error shape.
+fn unauthorized(message: &str) -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(serde_json::json!({ "error": message })),
+    )
+        .into_response()
+}
+
+/// Decode and validate a bearer token into the caller it represents.
+fn decode_user(token: &str, secret: &str) -> Result<CurrentUser, String> {
+    let validation = jsonwebtoken::Validation::new(jsonwebtoken::Algorithm::HS256);
+    let data = jsonwebtoken::decode::<Claims>(
+        token,
+        &jsonwebtoken::DecodingKey::from_secret(secret.as_bytes()),
+        &validation,
+    )
+    .map_err(|err| match err.kind() {
+        jsonwebtoken::errors::ErrorKind::ExpiredSignature => "token expired".to_string(),
+        _ => "invalid token".to_string(),
+    })?;
+    let user_id = data
+        .claims
+        .sub
+        .parse()
+        .map_err(|_| "invalid token".to_string())?;
+    Ok(CurrentUser {
+        user_id,
+        organization_id: data.claims.org,
+        role: data.claims.role,
+    })
+}
+
+/// Tower middleware guarding the API. Layered onto the `/api/v1` router,
+/// so `/health` is never affected; the login route is exempted by path.
+pub async fn require_auth(mut request: Request, next: Next) -> Response {
+    let Some(secret) = jwt_secret() else {
+        // Token-free operation is a development convenience only; a missing
+        // secret anywhere else rejects every request instead of admitting
+        // every request.
+        if dev_environment() {
+            return next.run(request).await;
+        }
+        return unauthorized("JWT_SECRET is not configured");
+    };
+    if request.uri().path().ends_with("/login") {
+        return next.run(request).await;
+    }
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let Some(token) = token else {
+        return unauthorized("missing bearer token");
+    };
+    match decode_user(token, &secret) {
+        Ok(user) => {
+            request.extensions_mut().insert(user);
+            next.run(request).await
+        }
+        Err(message) => unauthorized(&message),
+    }
+}
+
+/// Authorization check for destructive handlers: when bearer auth is
+/// enabled, the caller must hold one of `allowed` or gets a 403. A no-op
+/// when auth is disabled (the middleware passed the request through and
+/// the `Option<CurrentUser>` extractor yielded `None`).
+pub fn require_role(
+    user: Option<&CurrentUser>,
+    allowed: &[&str],
+) -> Result<(), (StatusCode, String)> {
+    let Some(user) = user else {
+        return Ok(());
+    };
+    if allowed.contains(&user.role.as_str()) {
+        return Ok(());
+    }
+    Err((
+        StatusCode::FORBIDDEN,
+        format!("this operation requires one of roles: {}", allowed.join(", ")),
+    ))
+}
+
+/// Pull the authenticated caller in a handler:
+/// `async fn handler(user: CurrentUser, ...)`. Rejects with 401 when the
+/// middleware did not authenticate the request (i.e. auth is disabled).
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for CurrentUser {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<CurrentUser>().cloned().ok_or((
+            StatusCode::UNAUTHORIZED,
+            "request is not authenticated".to_string(),
+        ))
+    }
+}
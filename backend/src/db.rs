@@ -0,0 +1,260 @@
+//! Database pool setup and shared application state.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+
+/// Live view of a solver run, kept in memory so operations dashboards can
+/// poll without hitting the database.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RunProgress {
+    pub status: String,
+    pub started_at: DateTime<Utc>,
+    pub attempt: i32,
+    pub last_update: DateTime<Utc>,
+}
+
+/// A run waiting for a solver permit, as shown on the queue endpoint.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct QueuedRun {
+    pub run_id: i64,
+    pub priority: i32,
+    pub queued_at: DateTime<Utc>,
+}
+
+struct Waiter {
+    run_id: i64,
+    priority: i32,
+    /// Admission order, for FIFO among equal priorities.
+    seq: u64,
+    queued_at: DateTime<Utc>,
+    wake: tokio::sync::oneshot::Sender<()>,
+}
+
+struct QueueInner {
+    running: usize,
+    next_seq: u64,
+    waiters: Vec<Waiter>,
+}
+
+/// Bounded admission for solver calls. At most `max` runs solve at once;
+/// the rest wait, and a freed permit goes to the highest-priority waiter
+/// (FIFO within a priority) rather than whoever asked first.
+pub struct SolverQueue {
+    max: usize,
+    inner: std::sync::Mutex<QueueInner>,
+}
+
+impl SolverQueue {
+    pub fn new(max: usize) -> Self {
+        Self {
+            max: max.max(1),
+            inner: std::sync::Mutex::new(QueueInner {
+                running: 0,
+                next_seq: 0,
+                waiters: Vec::new(),
+            }),
+        }
+    }
+
+    /// Wait for a permit. Resolves immediately while fewer than `max` runs
+    /// are solving; otherwise parks until a finishing run hands its permit
+    /// over. The permit lasts until the returned guard drops.
+    pub async fn acquire(self: &Arc<Self>, run_id: i64, priority: i32) -> SolverPermit {
+        let waiting = {
+            let mut inner = self.inner.lock().expect("solver queue lock poisoned");
+            if inner.running < self.max {
+                inner.running += 1;
+                None
+            } else {
+                let (wake, woken) = tokio::sync::oneshot::channel();
+                inner.next_seq += 1;
+                let seq = inner.next_seq;
+                inner.waiters.push(Waiter {
+                    run_id,
+                    priority,
+                    seq,
+                    queued_at: Utc::now(),
+                    wake,
+                });
+                Some(woken)
+            }
+        };
+        if let Some(woken) = waiting {
+            // The sender is only dropped with the queue itself, so a wake
+            // always arrives; `release` moved the permit to us already.
+            let _ = woken.await;
+        }
+        SolverPermit {
+            queue: Arc::clone(self),
+        }
+    }
+
+    /// Currently waiting runs, in the order they would be granted permits.
+    pub fn snapshot(&self) -> Vec<QueuedRun> {
+        let inner = self.inner.lock().expect("solver queue lock poisoned");
+        let mut queued: Vec<_> = inner
+            .waiters
+            .iter()
+            .map(|w| (w.priority, w.seq, w.run_id, w.queued_at))
+            .collect();
+        queued.sort_by_key(|&(priority, seq, ..)| (std::cmp::Reverse(priority), seq));
+        queued
+            .into_iter()
+            .map(|(priority, _, run_id, queued_at)| QueuedRun {
+                run_id,
+                priority,
+                queued_at,
+            })
+            .collect()
+    }
+
+    /// Hand the freed permit to the best waiter, or shrink the running
+    /// count when nobody waits. Waiters whose request was aborted are
+    /// skipped (their receiver is gone).
+    fn release(&self) {
+        let mut inner = self.inner.lock().expect("solver queue lock poisoned");
+        loop {
+            let best = inner
+                .waiters
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, w)| (w.priority, std::cmp::Reverse(w.seq)))
+                .map(|(index, _)| index);
+            let Some(index) = best else {
+                inner.running -= 1;
+                return;
+            };
+            let waiter = inner.waiters.swap_remove(index);
+            if waiter.wake.send(()).is_ok() {
+                return;
+            }
+        }
+    }
+}
+
+/// RAII permit from [`SolverQueue::acquire`]; dropping it wakes the next
+/// waiter.
+pub struct SolverPermit {
+    queue: Arc<SolverQueue>,
+}
+
+impl Drop for SolverPermit {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+/// How many runs may solve concurrently, from `SOLVER_MAX_CONCURRENCY`
+/// (default 4).
+fn solver_max_concurrency() -> usize {
+    std::env::var("SOLVER_MAX_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n >= 1)
+        .unwrap_or(4)
+}
+
+/// Shared state handed to every handler.
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: PgPool,
+    /// Shared HTTP client (connection pooling + TLS reuse) for solver calls
+    /// and the ingest callback. Timeout comes from `SOLVER_TIMEOUT_SECS`.
+    pub http: reqwest::Client,
+    /// Solver base URL, parsed and validated once at startup so a typo in
+    /// `FASTAPI_SOLVER_URL` fails the boot instead of every run.
+    pub solver_url: reqwest::Url,
+    /// In-process registry of currently active runs, keyed by run id.
+    pub jobs: Arc<DashMap<i64, RunProgress>>,
+    /// Priority-ordered admission for solver calls; see [`SolverQueue`].
+    pub solver_queue: Arc<SolverQueue>,
+    /// Short-TTL cache for expensive read-mostly aggregates, keyed by
+    /// `<endpoint>:<scope id>:<params>`. Writes invalidate their scope by
+    /// key prefix; `Cache-Control: no-cache` bypasses it per request.
+    pub agg_cache: moka::future::Cache<String, serde_json::Value>,
+}
+
+/// Resolve `FASTAPI_SOLVER_URL`. Unset falls back to localhost for local
+/// development; any `APP_ENV` other than dev/test makes that an error, and
+/// a malformed value always is one.
+fn solver_base_url() -> Result<reqwest::Url, String> {
+    let raw = match std::env::var("FASTAPI_SOLVER_URL") {
+        Ok(value) => value,
+        Err(_) => {
+            let app_env = std::env::var("APP_ENV").unwrap_or_else(|_| "dev".to_string());
+            if matches!(app_env.as_str(), "dev" | "development" | "test") {
+                "http://localhost:8000".to_string()
+            } else {
+                return Err(format!(
+                    "FASTAPI_SOLVER_URL must be set when APP_ENV is '{app_env}'"
+                ));
+            }
+        }
+    };
+    let url = reqwest::Url::parse(&raw)
+        .map_err(|e| format!("FASTAPI_SOLVER_URL '{raw}' is not a valid URL: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "FASTAPI_SOLVER_URL '{raw}' must use http or https"
+        ));
+    }
+    Ok(url)
+}
+
+/// TTL for cached aggregates, from `AGG_CACHE_TTL_SECS` (default 30).
+fn agg_cache_ttl() -> std::time::Duration {
+    let secs = std::env::var("AGG_CACHE_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30);
+    std::time::Duration::from_secs(secs)
+}
+
+impl AppState {
+    pub fn new(pool: PgPool) -> Self {
+        let timeout_secs: u64 = std::env::var("SOLVER_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(timeout_secs))
+            .build()
+            .expect("failed to build HTTP client");
+        let solver_url = solver_base_url().unwrap_or_else(|e| panic!("{e}"));
+        let agg_cache = moka::future::Cache::builder()
+            .max_capacity(10_000)
+            .time_to_live(agg_cache_ttl())
+            .support_invalidation_closures()
+            .build();
+        Self {
+            pool,
+            http,
+            solver_url,
+            jobs: Arc::new(DashMap::new()),
+            solver_queue: Arc::new(SolverQueue::new(solver_max_concurrency())),
+            agg_cache,
+        }
+    }
+
+    /// Drop every cached aggregate whose key starts with `prefix`, called
+    /// from write handlers that change the underlying rows.
+    pub fn invalidate_aggregates(&self, prefix: &str) {
+        let prefix = prefix.to_string();
+        let _ = self
+            .agg_cache
+            .invalidate_entries_if(move |key, _| key.starts_with(&prefix));
+    }
+}
+
+/// Connect to `DATABASE_URL` and run pending migrations.
+pub async fn connect(database_url: &str) -> Result<PgPool, sqlx::Error> {
+    let pool = PgPoolOptions::new()
+        .max_connections(10)
+        .connect(database_url)
+        .await?;
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    Ok(pool)
+}
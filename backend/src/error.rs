@@ -0,0 +1,132 @@
+//! A typed error for handlers, replacing ad-hoc `(StatusCode, String)`
+//! tuples.
+//!
+//! Handlers return `Result<_, AppError>` and use `?` directly on sqlx
+//! calls: `RowNotFound` becomes a 404 and unique violations a 409 instead
+//! of everything collapsing into a 500. The response body is always
+//! `{ "error": { "code", "message" } }` so clients can branch on `code`
+//! without parsing prose. Helpers still returning tuples convert through
+//! [`AppError::Status`], which keeps their status and message but renders
+//! the same envelope, with the code derived from the status.
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+
+#[derive(Debug)]
+pub enum AppError {
+    /// The addressed resource does not exist (404).
+    NotFound,
+    /// The request itself is malformed, e.g. an unparsable body (400).
+    BadRequest(String),
+    /// The caller is authenticated but not allowed to do this (403).
+    Forbidden(String),
+    /// The request collides with existing state, e.g. a duplicate key (409).
+    Conflict(String),
+    /// The request was understood but is semantically invalid (422).
+    Validation(String),
+    /// A 422 with per-field detail, from [`crate::routes::ValidationErrors`].
+    /// Renders that type's `{"error": ..., "fields": ...}` contract.
+    Fields(std::collections::BTreeMap<String, Vec<String>>),
+    /// Any other database failure (500); the detail is logged, not leaked.
+    Db(sqlx::Error),
+    /// A dependency (the solver, an outbound webhook) misbehaved (502).
+    Upstream(String),
+    /// Conversion from pre-`AppError` helpers: status verbatim, envelope
+    /// code derived from it.
+    Status(StatusCode, String),
+}
+
+/// The machine-readable code for a passed-through status, mirroring the
+/// named variants so clients cannot tell which construction a module uses.
+fn status_code_label(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::FORBIDDEN => "forbidden",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::UNPROCESSABLE_ENTITY => "validation",
+        StatusCode::BAD_GATEWAY => "upstream",
+        StatusCode::INTERNAL_SERVER_ERROR => "internal",
+        _ => "error",
+    }
+}
+
+impl AppError {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound => "not_found",
+            Self::BadRequest(_) => "bad_request",
+            Self::Forbidden(_) => "forbidden",
+            Self::Conflict(_) => "conflict",
+            Self::Validation(_) | Self::Fields(_) => "validation",
+            Self::Db(_) => "internal",
+            Self::Upstream(_) => "upstream",
+            Self::Status(status, _) => status_code_label(*status),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let code = self.code();
+        let (status, message) = match self {
+            Self::Status(status, message) => (status, message),
+            Self::Fields(fields) => {
+                return (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    Json(serde_json::json!({
+                        "error": "validation failed",
+                        "fields": fields,
+                    })),
+                )
+                    .into_response()
+            }
+            Self::NotFound => (
+                StatusCode::NOT_FOUND,
+                "resource does not exist".to_string(),
+            ),
+            Self::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            Self::Forbidden(message) => (StatusCode::FORBIDDEN, message),
+            Self::Conflict(message) => (StatusCode::CONFLICT, message),
+            Self::Validation(message) => (StatusCode::UNPROCESSABLE_ENTITY, message),
+            Self::Db(err) => {
+                tracing::error!("database error: {err}");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal error".to_string(),
+                )
+            }
+            Self::Upstream(message) => (StatusCode::BAD_GATEWAY, message),
+        };
+        (
+            status,
+            Json(serde_json::json!({ "error": { "code": code, "message": message } })),
+        )
+            .into_response()
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        if matches!(err, sqlx::Error::RowNotFound) {
+            return Self::NotFound;
+        }
+        if let Some(db_err) = err.as_database_error() {
+            if db_err.is_unique_violation() {
+                return Self::Conflict(format!(
+                    "conflicts with an existing row ({})",
+                    db_err.constraint().unwrap_or("unique constraint")
+                ));
+            }
+        }
+        Self::Db(err)
+    }
+}
+
+impl From<(StatusCode, String)> for AppError {
+    fn from((status, body): (StatusCode, String)) -> Self {
+        Self::Status(status, body)
+    }
+}
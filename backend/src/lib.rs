@@ -0,0 +1,38 @@
+//! MayWin nurse scheduling API.
+//!
+//! Axum + sqlx backend that owns the planning data (organizations, units,
+//! staff, shifts, coverage, availability, preferences) and orchestrates
+//! solves against the FastAPI OR-Tools solver service.
+
+pub mod auth;
+pub mod db;
+pub mod error;
+pub mod outbox;
+pub mod reconciler;
+pub mod routes;
+
+use axum::extract::DefaultBodyLimit;
+use axum::Router;
+use tower_http::cors::CorsLayer;
+use tower_http::decompression::RequestDecompressionLayer;
+
+use db::AppState;
+
+/// Body size cap for uploads. Extractors read the body after the
+/// decompression layer has inflated it, so the limit applies to the
+/// decompressed size.
+const MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+
+/// Build the full application router on top of the given state.
+pub fn app(state: AppState) -> Router {
+    Router::new()
+        .merge(routes::health::router())
+        .nest(
+            "/api/v1",
+            routes::api_router().layer(axum::middleware::from_fn(auth::require_auth)),
+        )
+        .layer(CorsLayer::permissive())
+        .layer(RequestDecompressionLayer::new().gzip(true))
+        .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
+        .with_state(state)
+}
@@ -1 +1,28 @@
-fn main() { println!("MayWin backend running"); }
+use maywin_api::{app, db};
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "maywin_api=info,tower_http=info".into()),
+        )
+        .init();
+
+    let database_url = std::env::var("DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost:5432/maywin".to_string());
+    let pool = db::connect(&database_url)
+        .await
+        .expect("failed to connect to database");
+
+    let state = db::AppState::new(pool);
+    maywin_api::outbox::spawn_dispatcher(state.clone());
+    maywin_api::reconciler::spawn_reconciler(state.clone());
+    let addr = std::env::var("BIND_ADDR").unwrap_or_else(|_| "0.0.0.0:8080".to_string());
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .expect("failed to bind");
+    tracing::info!("MayWin backend listening on {addr}");
+    axum::serve(listener, app(state)).await.expect("server error");
+}
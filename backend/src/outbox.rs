@@ -0,0 +1,96 @@
+//! Transactional outbox for webhook delivery.
+//!
+//! Handlers enqueue events inside the same transaction as the state change,
+//! so an event exists if and only if the change committed. A background
+//! dispatcher polls unsent rows and POSTs them to `WEBHOOK_URL`, marking
+//! them sent on success — at-least-once delivery that survives crashes and
+//! restarts.
+
+use serde_json::{json, Value};
+use sqlx::PgPool;
+
+use crate::db::AppState;
+
+/// Queue an event inside the caller's transaction.
+pub async fn enqueue(
+    tx: &mut sqlx::PgConnection,
+    event_type: &str,
+    payload: &Value,
+) -> sqlx::Result<()> {
+    sqlx::query("INSERT INTO outbox (event_type, payload) VALUES ($1, $2)")
+        .bind(event_type)
+        .bind(payload)
+        .execute(tx)
+        .await?;
+    Ok(())
+}
+
+/// Deliver a batch of unsent events, oldest first. Returns how many were
+/// delivered; failures record the error and stay queued for the next pass.
+pub async fn deliver_pending(pool: &PgPool, http: &reqwest::Client, webhook_url: &str) -> usize {
+    let pending: Vec<(i64, String, Value)> = match sqlx::query_as(
+        "SELECT outbox_id, event_type, payload FROM outbox
+         WHERE sent_at IS NULL ORDER BY outbox_id LIMIT 50",
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(err) => {
+            tracing::warn!("outbox poll failed: {err}");
+            return 0;
+        }
+    };
+
+    let mut delivered = 0;
+    for (outbox_id, event_type, payload) in pending {
+        let body = json!({
+            "outbox_id": outbox_id,
+            "event_type": event_type,
+            "payload": payload,
+        });
+        let result = http.post(webhook_url).json(&body).send().await;
+        let outcome = match result {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response) => Err(format!("webhook returned {}", response.status())),
+            Err(err) => Err(err.to_string()),
+        };
+        let update = match &outcome {
+            Ok(()) => sqlx::query(
+                "UPDATE outbox SET sent_at = now(), attempts = attempts + 1
+                 WHERE outbox_id = $1",
+            ),
+            Err(error) => sqlx::query(
+                "UPDATE outbox SET attempts = attempts + 1, last_error = $2
+                 WHERE outbox_id = $1",
+            )
+            .bind(error.clone()),
+        };
+        if let Err(err) = update.bind(outbox_id).execute(pool).await {
+            tracing::warn!("outbox update for event {outbox_id} failed: {err}");
+            continue;
+        }
+        if outcome.is_ok() {
+            delivered += 1;
+        }
+    }
+    delivered
+}
+
+/// Start the background dispatcher. A no-op when `WEBHOOK_URL` is unset.
+pub fn spawn_dispatcher(state: AppState) {
+    let Ok(webhook_url) = std::env::var("WEBHOOK_URL") else {
+        tracing::info!("WEBHOOK_URL not set; outbox dispatcher disabled");
+        return;
+    };
+    let poll_secs: u64 = std::env::var("OUTBOX_POLL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5);
+    tokio::spawn(async move {
+        loop {
+            deliver_pending(&state.pool, &state.http, &webhook_url).await;
+            tokio::time::sleep(std::time::Duration::from_secs(poll_secs)).await;
+        }
+    });
+}
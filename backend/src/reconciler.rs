@@ -0,0 +1,151 @@
+//! Background reconciler backfilling KPI rows for succeeded runs.
+//!
+//! Historical bugs left some succeeded runs without a `kpi` row, which
+//! breaks dashboards that join on it. The reconciler recomputes those KPIs
+//! from the persisted assignments: totals and understaffing come straight
+//! from SQL against the run's coverage, overtime reuses the same
+//! overnight-aware duration math as the utilization report. Satisfaction
+//! needs the solver's nurse stats, which were never stored, so it stays
+//! NULL for backfilled rows.
+
+use sqlx::PgPool;
+
+use crate::routes::shift_patterns::shift_duration_minutes;
+use crate::routes::solver_runs::default_weekly_hours;
+
+/// Recompute and insert KPIs for every succeeded run missing one. Returns
+/// how many runs were backfilled; individual failures are logged and
+/// skipped so one broken run cannot stall the rest.
+pub async fn backfill_missing_kpis(pool: &PgPool) -> usize {
+    let run_ids: Vec<(i64,)> = match sqlx::query_as(
+        "SELECT r.run_id FROM solver_runs r
+         WHERE r.status = 'succeeded'
+           AND NOT EXISTS (SELECT 1 FROM kpi k WHERE k.run_id = r.run_id)
+         ORDER BY r.run_id",
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(rows) => rows,
+        Err(err) => {
+            tracing::warn!("kpi reconciler poll failed: {err}");
+            return 0;
+        }
+    };
+
+    let mut backfilled = 0;
+    for (run_id,) in run_ids {
+        match recompute_kpi(pool, run_id).await {
+            Ok(()) => backfilled += 1,
+            Err(err) => tracing::warn!("kpi backfill for run {run_id} failed: {err}"),
+        }
+    }
+    if backfilled > 0 {
+        tracing::info!("kpi reconciler backfilled {backfilled} run(s)");
+    }
+    backfilled
+}
+
+/// Recompute one run's KPI row from its persisted assignments and the
+/// unit's coverage.
+async fn recompute_kpi(pool: &PgPool, run_id: i64) -> sqlx::Result<()> {
+    let (total_assignments,): (i64,) =
+        sqlx::query_as("SELECT count(*) FROM assignments WHERE run_id = $1")
+            .bind(run_id)
+            .fetch_one(pool)
+            .await?;
+
+    // Coverage cells of the run's unit within the roster's day span where
+    // fewer staff were assigned than required.
+    let (understaffed_cells,): (i64,) = sqlx::query_as(
+        "SELECT count(*)
+         FROM coverage_requirement c
+         JOIN scenarios sc ON sc.unit_id = c.unit_id
+         JOIN solver_runs r ON r.scenario_id = sc.scenario_id
+         WHERE r.run_id = $1
+           AND c.day BETWEEN (SELECT min(day) FROM assignments WHERE run_id = $1)
+                         AND (SELECT max(day) FROM assignments WHERE run_id = $1)
+           AND c.required_count > (
+               SELECT count(*) FROM assignments a
+               WHERE a.run_id = $1 AND a.day = c.day AND a.shift_id = c.shift_id
+           )",
+    )
+    .bind(run_id)
+    .fetch_one(pool)
+    .await?;
+
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        staff_id: i64,
+        max_weekly_hours: Option<i32>,
+        iso_year: i32,
+        iso_week: i32,
+        start_time: chrono::NaiveTime,
+        end_time: chrono::NaiveTime,
+        is_night: bool,
+        break_minutes: i32,
+    }
+    let rows: Vec<Row> = sqlx::query_as(
+        "SELECT a.staff_id, st.max_weekly_hours,
+                EXTRACT(ISOYEAR FROM a.day)::int AS iso_year,
+                EXTRACT(WEEK FROM a.day)::int AS iso_week,
+                sp.start_time, sp.end_time, sp.is_night, sp.break_minutes
+         FROM assignments a
+         JOIN staffs st ON st.staff_id = a.staff_id
+         JOIN shift_patterns sp ON sp.shift_id = a.shift_id
+         WHERE a.run_id = $1 AND NOT sp.is_on_call",
+    )
+    .bind(run_id)
+    .fetch_all(pool)
+    .await?;
+    let mut week_hours: std::collections::HashMap<(i64, i32, i32), (f64, i32)> =
+        std::collections::HashMap::new();
+    for row in rows {
+        let hours = shift_duration_minutes(
+            row.start_time,
+            row.end_time,
+            row.is_night,
+            row.break_minutes,
+        ) as f64
+            / 60.0;
+        let entry = week_hours
+            .entry((row.staff_id, row.iso_year, row.iso_week))
+            .or_insert((0.0, row.max_weekly_hours.unwrap_or_else(default_weekly_hours)));
+        entry.0 += hours;
+    }
+    let overtime_hours: f64 = week_hours
+        .values()
+        .map(|&(hours, contract)| (hours - f64::from(contract)).max(0.0))
+        .sum();
+
+    sqlx::query(
+        "INSERT INTO kpi (run_id, total_assignments, understaffed_cells, overtime_hours)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (run_id) DO NOTHING",
+    )
+    .bind(run_id)
+    .bind(total_assignments as i32)
+    .bind(understaffed_cells as i32)
+    .bind(overtime_hours)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Start the periodic reconciler. Opt-in: a no-op unless
+/// `KPI_RECONCILE_SECS` is set to the poll interval in seconds.
+pub fn spawn_reconciler(state: crate::db::AppState) {
+    let Some(poll_secs) = std::env::var("KPI_RECONCILE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+    else {
+        tracing::info!("KPI_RECONCILE_SECS not set; kpi reconciler disabled");
+        return;
+    };
+    tokio::spawn(async move {
+        loop {
+            backfill_missing_kpis(&state.pool).await;
+            tokio::time::sleep(std::time::Duration::from_secs(poll_secs)).await;
+        }
+    });
+}
@@ -0,0 +1,175 @@
+//! Audit log: who did what to which entity, for compliance review.
+
+use axum::extract::{Query, State};
+use axum::http::HeaderMap;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct AuditEntry {
+    pub audit_id: i64,
+    pub user_id: Option<i64>,
+    pub organization_id: Option<i64>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: Option<i64>,
+    pub detail: Value,
+    pub at: DateTime<Utc>,
+}
+
+/// Write an audit entry. Pass the handler's transaction so the entry
+/// commits with the change it records.
+#[allow(clippy::too_many_arguments)]
+pub async fn record<'e, E>(
+    executor: E,
+    user_id: Option<i64>,
+    organization_id: Option<i64>,
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<i64>,
+    detail: &Value,
+) -> sqlx::Result<()>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query(
+        "INSERT INTO audit_log (user_id, organization_id, action, entity_type, entity_id, detail)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(user_id)
+    .bind(organization_id)
+    .bind(action)
+    .bind(entity_type)
+    .bind(entity_id)
+    .bind(detail)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditLogQuery {
+    pub entity_type: Option<String>,
+    pub entity_id: Option<i64>,
+    pub user_id: Option<i64>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Keyset cursor from a previous page's `next_cursor`.
+    pub after: Option<i64>,
+    /// Page size (default 100, max 500).
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditLogPage {
+    pub entries: Vec<AuditEntry>,
+    /// Pass as `after` to fetch the next page; absent on the last page.
+    pub next_cursor: Option<i64>,
+}
+
+/// Admin-only compliance view over the whole audit log, cursor-paginated
+/// so it stays usable as history accumulates.
+pub async fn audit_log(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    headers: HeaderMap,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<AuditLogPage>, AppError> {
+    let caller = super::users::current_user(&state, auth.as_ref(), &headers).await?;
+    if caller.role != "admin" {
+        return Err(AppError::Forbidden(
+            "only admins may read the full audit log".to_string(),
+        ));
+    }
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+    let entries = sqlx::query_as::<_, AuditEntry>(
+        "SELECT audit_id, user_id, organization_id, action, entity_type, entity_id, detail, at
+         FROM audit_log
+         WHERE ($1::text IS NULL OR entity_type = $1)
+           AND ($2::bigint IS NULL OR entity_id = $2)
+           AND ($3::bigint IS NULL OR user_id = $3)
+           AND ($4::timestamptz IS NULL OR at >= $4)
+           AND ($5::timestamptz IS NULL OR at <= $5)
+           AND audit_id > $6
+         ORDER BY audit_id
+         LIMIT $7",
+    )
+    .bind(&query.entity_type)
+    .bind(query.entity_id)
+    .bind(query.user_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.after.unwrap_or(0))
+    .bind(limit)
+    .fetch_all(&state.pool)
+    .await?;
+    let next_cursor = (entries.len() as i64 == limit)
+        .then(|| entries.last().map(|e| e.audit_id))
+        .flatten();
+    Ok(Json(AuditLogPage {
+        entries,
+        next_cursor,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListAuditQuery {
+    /// Acting user.
+    pub actor: Option<i64>,
+    pub action: Option<String>,
+    pub entity_type: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// Keyset cursor: only entries with an id greater than this.
+    pub after: Option<i64>,
+    /// Page size (default 100, max 500).
+    pub limit: Option<i64>,
+}
+
+/// Page through the audit log, oldest first. Non-admin callers only see
+/// their own organization's entries.
+pub async fn list_audit(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    headers: HeaderMap,
+    Query(query): Query<ListAuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, AppError> {
+    let caller = super::users::current_user(&state, auth.as_ref(), &headers).await?;
+    // Org-less admins span everything; everyone else is scoped to their org.
+    let org_scope = match (caller.organization_id, caller.role.as_str()) {
+        (None, "admin") => None,
+        (org, _) => Some(org.ok_or_else(|| {
+            AppError::Forbidden("user has no organization; cannot read the audit log".to_string())
+        })?),
+    };
+    let entries = sqlx::query_as::<_, AuditEntry>(
+        "SELECT audit_id, user_id, organization_id, action, entity_type, entity_id, detail, at
+         FROM audit_log
+         WHERE ($1::bigint IS NULL OR organization_id = $1)
+           AND ($2::bigint IS NULL OR user_id = $2)
+           AND ($3::text IS NULL OR action = $3)
+           AND ($4::text IS NULL OR entity_type = $4)
+           AND ($5::timestamptz IS NULL OR at >= $5)
+           AND ($6::timestamptz IS NULL OR at <= $6)
+           AND audit_id > $7
+         ORDER BY audit_id
+         LIMIT $8",
+    )
+    .bind(org_scope)
+    .bind(query.actor)
+    .bind(&query.action)
+    .bind(&query.entity_type)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.after.unwrap_or(0))
+    .bind(query.limit.unwrap_or(100).clamp(1, 500))
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(entries))
+}
@@ -0,0 +1,72 @@
+//! Login: exchange credentials for a bearer token.
+//!
+//! The one route under `/api/v1` that the auth middleware leaves public.
+//! Verification goes through [`super::users::verify_and_upgrade`], so a
+//! successful login also migrates any remaining legacy hash in place.
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::issue_token;
+use crate::db::AppState;
+use crate::error::AppError;
+
+/// Token lifetime: long enough for a planning session, short enough that
+/// a leaked token ages out the same day.
+const TOKEN_TTL_SECS: i64 = 8 * 60 * 60;
+
+#[derive(Debug, Deserialize)]
+pub struct LoginBody {
+    pub full_name: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub token: String,
+    pub user_id: i64,
+    pub organization_id: Option<i64>,
+    pub role: String,
+}
+
+/// One 401 for both unknown users and wrong passwords, so the response
+/// never confirms whether an account exists.
+fn bad_credentials() -> AppError {
+    AppError::Status(
+        StatusCode::UNAUTHORIZED,
+        "invalid credentials".to_string(),
+    )
+}
+
+pub async fn login(
+    State(state): State<AppState>,
+    Json(body): Json<LoginBody>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let user: Option<(i64, Option<i64>, String, bool)> = sqlx::query_as(
+        "SELECT user_id, organization_id, role, is_active FROM users WHERE full_name = $1",
+    )
+    .bind(&body.full_name)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(super::internal_error)?;
+    let Some((user_id, organization_id, role, is_active)) = user else {
+        return Err(bad_credentials());
+    };
+    if !super::users::verify_and_upgrade(&state.pool, user_id, &body.password).await? {
+        return Err(bad_credentials());
+    }
+    // Only after the password checks out: a 403 here confirms the account
+    // exists, which is fine once the caller has proven they own it.
+    if !is_active {
+        return Err(AppError::Forbidden("account is disabled".to_string()));
+    }
+    let token = issue_token(user_id, organization_id, &role, TOKEN_TTL_SECS)?;
+    Ok(Json(LoginResponse {
+        token,
+        user_id,
+        organization_id,
+        role,
+    }))
+}
@@ -0,0 +1,329 @@
+//! Staff availability: whether a staff member can work a (day, shift) cell.
+
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::internal_error;
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Availability {
+    pub availability_id: i64,
+    pub staff_id: i64,
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    pub value: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AvailabilityUpsertItem {
+    pub staff_id: i64,
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    /// 1 = can work, 0 = cannot work.
+    pub value: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkAvailabilityBody {
+    pub items: Vec<AvailabilityUpsertItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkResult {
+    pub upserted: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StaffInputsBody {
+    #[serde(default)]
+    pub availability: Vec<AvailabilityUpsertItem>,
+    #[serde(default)]
+    pub preferences: Vec<super::preferences::PreferenceUpsertItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffInputsResult {
+    pub availability_upserted: usize,
+    pub preferences_upserted: usize,
+}
+
+/// Check a batch of availability cells before any transaction opens, so a
+/// bad batch is rejected whole with every problem named instead of failing
+/// mid-write. `value` must be exactly 0 or 1, and the same
+/// (staff_id, day, shift_id) cell may appear only once — the upsert's
+/// last-write-wins ON CONFLICT would otherwise hide the client bug.
+fn validate_availability_items(
+    items: &[AvailabilityUpsertItem],
+) -> Result<(), AppError> {
+    let mut errors = super::ValidationErrors::new();
+    let mut seen: std::collections::HashMap<(i64, NaiveDate, i64), usize> =
+        std::collections::HashMap::new();
+    for (index, item) in items.iter().enumerate() {
+        if item.value != 0 && item.value != 1 {
+            errors.add(
+                &format!("items[{index}].value"),
+                format!("must be 0 or 1, got {} (staff {})", item.value, item.staff_id),
+            );
+        }
+        match seen.entry((item.staff_id, item.day, item.shift_id)) {
+            std::collections::hash_map::Entry::Occupied(first) => {
+                errors.add(
+                    &format!("items[{index}]"),
+                    format!(
+                        "duplicate cell (staff {}, {}, shift {}), first at items[{}]",
+                        item.staff_id,
+                        item.day,
+                        item.shift_id,
+                        first.get()
+                    ),
+                );
+            }
+            std::collections::hash_map::Entry::Vacant(slot) => {
+                slot.insert(index);
+            }
+        }
+    }
+    errors.check()
+}
+
+/// Atomic save for the grid editor: both availability and preference cells
+/// land in one transaction, so a partial failure rolls everything back.
+pub async fn bulk_upsert_staff_inputs(
+    State(state): State<AppState>,
+    Path(_unit_id): Path<i64>,
+    Json(body): Json<StaffInputsBody>,
+) -> Result<Json<StaffInputsResult>, AppError> {
+    validate_availability_items(&body.availability)?;
+    super::preferences::validate_preference_items(&body.preferences)?;
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    for item in &body.availability {
+        sqlx::query(
+            "INSERT INTO availability (staff_id, day, shift_id, value)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (staff_id, day, shift_id)
+             DO UPDATE SET value = EXCLUDED.value",
+        )
+        .bind(item.staff_id)
+        .bind(item.day)
+        .bind(item.shift_id)
+        .bind(item.value)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+    for item in &body.preferences {
+        sqlx::query(
+            "INSERT INTO preferences (staff_id, day, shift_id, penalty)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (staff_id, day, shift_id)
+             DO UPDATE SET penalty = EXCLUDED.penalty",
+        )
+        .bind(item.staff_id)
+        .bind(item.day)
+        .bind(item.shift_id)
+        .bind(item.penalty)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+    tx.commit().await.map_err(internal_error)?;
+    Ok(Json(StaffInputsResult {
+        availability_upserted: body.availability.len(),
+        preferences_upserted: body.preferences.len(),
+    }))
+}
+
+/// Bounded date range for bulk deletes; both edges are required so a typo
+/// can't wipe a staff member's whole history.
+#[derive(Debug, Deserialize)]
+pub struct DeleteRangeQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteResult {
+    pub deleted: u64,
+}
+
+/// Remove a staff member's availability rows in a date range, e.g. when a
+/// schedule is reset before a new planning cycle.
+pub async fn delete_availability_range(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+    Query(query): Query<DeleteRangeQuery>,
+) -> Result<Json<DeleteResult>, AppError> {
+    if query.from > query.to {
+        return Err(AppError::BadRequest("`from` must not be after `to`".to_string(),
+        ));
+    }
+    let deleted = sqlx::query(
+        "DELETE FROM availability WHERE staff_id = $1 AND day BETWEEN $2 AND $3",
+    )
+    .bind(staff_id)
+    .bind(query.from)
+    .bind(query.to)
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+    Ok(Json(DeleteResult { deleted }))
+}
+
+pub async fn bulk_upsert_availability(
+    State(state): State<AppState>,
+    Json(body): Json<BulkAvailabilityBody>,
+) -> Result<Json<BulkResult>, AppError> {
+    validate_availability_items(&body.items)?;
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    for item in &body.items {
+        sqlx::query(
+            "INSERT INTO availability (staff_id, day, shift_id, value)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (staff_id, day, shift_id)
+             DO UPDATE SET value = EXCLUDED.value",
+        )
+        .bind(item.staff_id)
+        .bind(item.day)
+        .bind(item.shift_id)
+        .bind(item.value)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+    tx.commit().await.map_err(internal_error)?;
+    Ok(Json(BulkResult {
+        upserted: body.items.len(),
+    }))
+}
+
+/// Longest range a single availability listing may span. Longer requests
+/// are clamped, not rejected: the frontend pages by moving `from`.
+pub(crate) const MAX_LIST_SPAN_DAYS: i64 = 92;
+
+#[derive(Debug, Deserialize)]
+pub struct ListAvailabilityQuery {
+    pub staff_id: Option<i64>,
+    /// Filter to one unit's staff (joins through `staffs`).
+    pub unit_id: Option<i64>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+/// Read back stored availability, the counterpart to the bulk upsert.
+pub async fn list_availability(
+    State(state): State<AppState>,
+    Query(query): Query<ListAvailabilityQuery>,
+) -> Result<Json<Vec<Availability>>, AppError> {
+    if query.from > query.to {
+        return Err(AppError::BadRequest("`from` must not be after `to`".to_string(),
+        ));
+    }
+    let to = query
+        .to
+        .min(query.from + chrono::Duration::days(MAX_LIST_SPAN_DAYS - 1));
+    let rows = sqlx::query_as::<_, Availability>(
+        "SELECT a.availability_id, a.staff_id, a.day, a.shift_id, a.value
+         FROM availability a
+         JOIN staffs s ON s.staff_id = a.staff_id
+         WHERE ($1::bigint IS NULL OR a.staff_id = $1)
+           AND ($2::bigint IS NULL OR s.unit_id = $2)
+           AND a.day BETWEEN $3 AND $4
+         ORDER BY a.staff_id, a.day, a.shift_id",
+    )
+    .bind(query.staff_id)
+    .bind(query.unit_id)
+    .bind(query.from)
+    .bind(to)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(rows))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MatrixQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Restrict the grid to one shift; without it a cell is 1 when the
+    /// staff member is available for at least one shift that day.
+    pub shift_id: Option<i64>,
+}
+
+/// Availability pivoted into the staff-by-day grid planners review: one
+/// row per enabled staff member, one column per day in the range, cells
+/// `1`/`0`, blank where nothing was recorded.
+pub async fn availability_matrix_csv(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Query(query): Query<MatrixQuery>,
+) -> Result<Response, AppError> {
+    if query.to < query.from {
+        return Err(AppError::Validation(format!("to ({}) must not precede from ({})", query.to, query.from),
+        ));
+    }
+    let rows: Vec<(i64, String, Option<NaiveDate>, Option<i32>)> = sqlx::query_as(
+        "SELECT s.staff_id, s.code, a.day, a.value
+         FROM staffs s
+         LEFT JOIN availability a ON a.staff_id = s.staff_id
+           AND a.day BETWEEN $2 AND $3
+           AND ($4::bigint IS NULL OR a.shift_id = $4)
+         WHERE s.unit_id = $1 AND s.is_enabled
+         ORDER BY s.code, s.staff_id",
+    )
+    .bind(unit_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.shift_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let days: Vec<NaiveDate> = query
+        .from
+        .iter_days()
+        .take_while(|day| *day <= query.to)
+        .collect();
+    // Staff in first-seen (code) order; a cell keeps the best value seen
+    // so the shift-less grid reads "available for at least one shift".
+    let mut order: Vec<(i64, String)> = Vec::new();
+    let mut cells: std::collections::HashMap<(i64, NaiveDate), i32> =
+        std::collections::HashMap::new();
+    for (staff_id, code, day, value) in rows {
+        if !order.iter().any(|(id, _)| *id == staff_id) {
+            order.push((staff_id, code));
+        }
+        if let (Some(day), Some(value)) = (day, value) {
+            let cell = cells.entry((staff_id, day)).or_insert(value);
+            *cell = (*cell).max(value);
+        }
+    }
+
+    let mut csv = String::from("staff_code");
+    for day in &days {
+        csv.push_str(&format!(",{day}"));
+    }
+    csv.push('\n');
+    for (staff_id, code) in order {
+        csv.push_str(&super::coverage::csv_field(&code));
+        for day in &days {
+            match cells.get(&(staff_id, *day)) {
+                Some(value) => csv.push_str(&format!(",{value}")),
+                None => csv.push(','),
+            }
+        }
+        csv.push('\n');
+    }
+    Ok((
+        [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+        csv,
+    )
+        .into_response())
+}
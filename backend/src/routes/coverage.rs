@@ -0,0 +1,582 @@
+//! Coverage requirements: how many staff each (day, shift) cell needs.
+
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::internal_error;
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct CoverageRequirement {
+    pub coverage_id: i64,
+    pub unit_id: i64,
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    pub required_count: i32,
+    pub required_skill: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CoverageUpsertItem {
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    pub required_count: i32,
+    pub required_skill: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BulkCoverageBody {
+    pub items: Vec<CoverageUpsertItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkResult {
+    pub upserted: usize,
+}
+
+pub async fn bulk_upsert_coverage(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Json(body): Json<BulkCoverageBody>,
+) -> Result<Json<BulkResult>, AppError> {
+    // Units with a planning granularity opt into structural checks: every
+    // referenced shift must be one of the unit's own.
+    let granularity = super::units::planning_granularity(&state.pool, unit_id).await?;
+    if granularity != "none" {
+        let shift_ids: Vec<i64> = body.items.iter().map(|i| i.shift_id).collect();
+        let foreign: Option<(i64,)> = sqlx::query_as(
+            "SELECT s.shift_id FROM unnest($2::bigint[]) AS s(shift_id)
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM shift_patterns sp
+                 WHERE sp.shift_id = s.shift_id AND sp.unit_id = $1
+             )
+             LIMIT 1",
+        )
+        .bind(unit_id)
+        .bind(&shift_ids)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(internal_error)?;
+        if let Some((shift_id,)) = foreign {
+            return Err(AppError::Validation(format!("shift {shift_id} does not belong to unit {unit_id}"),
+            ));
+        }
+    }
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    for item in &body.items {
+        sqlx::query(
+            "INSERT INTO coverage_requirement (unit_id, day, shift_id, required_count, required_skill)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (unit_id, day, shift_id)
+             DO UPDATE SET required_count = EXCLUDED.required_count,
+                           required_skill = EXCLUDED.required_skill",
+        )
+        .bind(unit_id)
+        .bind(item.day)
+        .bind(item.shift_id)
+        .bind(item.required_count)
+        .bind(&item.required_skill)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+    tx.commit().await.map_err(internal_error)?;
+    state.invalidate_aggregates(&format!("coverage_demand:{unit_id}:"));
+    Ok(Json(BulkResult {
+        upserted: body.items.len(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CopyCoverageBody {
+    pub from_start: NaiveDate,
+    pub from_end: NaiveDate,
+    /// First day of each target week; must fall on the same weekday as
+    /// `from_start` so the copies stay day-of-week aligned.
+    pub to_starts: Vec<NaiveDate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CopyCoverageResult {
+    pub copied: usize,
+}
+
+/// Clone a hand-tuned template week's coverage onto future weeks,
+/// overwriting any cells already present there.
+pub async fn copy_coverage(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Json(body): Json<CopyCoverageBody>,
+) -> Result<Json<CopyCoverageResult>, AppError> {
+    let span = (body.from_end - body.from_start).num_days();
+    if span != 6 {
+        return Err(AppError::BadRequest(format!(
+                "source range must cover exactly one week, got {} days",
+                span + 1
+            ),
+        ));
+    }
+    for to_start in &body.to_starts {
+        use chrono::Datelike;
+        if to_start.weekday() != body.from_start.weekday() {
+            return Err(AppError::BadRequest(format!(
+                    "target start {to_start} is a {}, but the template week starts on a {}",
+                    to_start.weekday(),
+                    body.from_start.weekday()
+                ),
+            ));
+        }
+    }
+
+    let template: Vec<(NaiveDate, i64, i32, Option<String>)> = sqlx::query_as(
+        "SELECT day, shift_id, required_count, required_skill
+         FROM coverage_requirement
+         WHERE unit_id = $1 AND day BETWEEN $2 AND $3",
+    )
+    .bind(unit_id)
+    .bind(body.from_start)
+    .bind(body.from_end)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let mut copied = 0;
+    for to_start in &body.to_starts {
+        let offset = *to_start - body.from_start;
+        for (day, shift_id, required_count, required_skill) in &template {
+            sqlx::query(
+                "INSERT INTO coverage_requirement (unit_id, day, shift_id, required_count, required_skill)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (unit_id, day, shift_id)
+                 DO UPDATE SET required_count = EXCLUDED.required_count,
+                               required_skill = EXCLUDED.required_skill",
+            )
+            .bind(unit_id)
+            .bind(*day + offset)
+            .bind(shift_id)
+            .bind(required_count)
+            .bind(required_skill)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+            copied += 1;
+        }
+    }
+    tx.commit().await.map_err(internal_error)?;
+    state.invalidate_aggregates(&format!("coverage_demand:{unit_id}:"));
+    Ok(Json(CopyCoverageResult { copied }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClearCoverageQuery {
+    /// A date or a sentinel (`today`, `week`, `month`) resolved in the
+    /// unit's time zone.
+    pub from: String,
+    pub to: String,
+    /// `zero` (default) keeps the cells with `required_count = 0`;
+    /// `delete` removes the rows entirely.
+    pub mode: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClearCoverageResult {
+    pub affected: u64,
+}
+
+/// Zero out (or delete) every coverage cell in a date range in one
+/// statement, e.g. to cancel planning for a holiday week.
+pub async fn clear_coverage(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Query(query): Query<ClearCoverageQuery>,
+) -> Result<Json<ClearCoverageResult>, AppError> {
+    let time_zone = super::units::unit_time_zone(&state.pool, unit_id).await?;
+    let from = super::resolve_date_bound(&query.from, &time_zone, false)?;
+    let to = super::resolve_date_bound(&query.to, &time_zone, true)?;
+    if from > to {
+        return Err(AppError::BadRequest("`from` must not be after `to`".to_string(),
+        ));
+    }
+    let affected = match query.mode.as_deref().unwrap_or("zero") {
+        "zero" => sqlx::query(
+            "UPDATE coverage_requirement SET required_count = 0
+             WHERE unit_id = $1 AND day BETWEEN $2 AND $3",
+        ),
+        "delete" => sqlx::query(
+            "DELETE FROM coverage_requirement
+             WHERE unit_id = $1 AND day BETWEEN $2 AND $3",
+        ),
+        other => {
+            return Err(AppError::BadRequest(format!("unknown mode '{other}', expected 'zero' or 'delete'"),
+            ))
+        }
+    }
+    .bind(unit_id)
+    .bind(from)
+    .bind(to)
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+    state.invalidate_aggregates(&format!("coverage_demand:{unit_id}:"));
+    Ok(Json(ClearCoverageResult { affected }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidateCoverageQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkillCell {
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    pub required_count: i32,
+    pub available: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkillValidation {
+    pub skill: String,
+    pub total_required: i64,
+    pub total_available: i64,
+    /// Cells where fewer skilled staff are available than required.
+    pub deficit_cells: Vec<SkillCell>,
+    pub in_deficit: bool,
+}
+
+/// Check whether the unit's skill requirements are satisfiable: per skill,
+/// compare each cell's `required_count` against the enabled staff holding
+/// that skill who are not marked unavailable for the cell. A skill whose
+/// certification expires before the cell's day no longer counts.
+pub async fn validate_coverage(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Query(query): Query<ValidateCoverageQuery>,
+) -> Result<Json<Vec<SkillValidation>>, AppError> {
+    let cells: Vec<(NaiveDate, i64, String, i32, i64)> = sqlx::query_as(
+        "SELECT c.day, c.shift_id, c.required_skill, c.required_count,
+                (SELECT count(*) FROM staffs st
+                 WHERE st.unit_id = c.unit_id AND st.is_enabled
+                   AND c.required_skill = ANY(st.skills)
+                   AND NOT EXISTS (SELECT 1 FROM staff_skill_expiry e
+                                   WHERE e.staff_id = st.staff_id
+                                     AND e.skill = c.required_skill
+                                     AND e.expires_on < c.day)
+                   AND COALESCE((SELECT a.value FROM availability a
+                                 WHERE a.staff_id = st.staff_id
+                                   AND a.day = c.day AND a.shift_id = c.shift_id), 1) > 0
+                ) AS available
+         FROM coverage_requirement c
+         WHERE c.unit_id = $1 AND c.required_skill IS NOT NULL
+           AND ($2::date IS NULL OR c.day >= $2)
+           AND ($3::date IS NULL OR c.day <= $3)
+         ORDER BY c.required_skill, c.day, c.shift_id",
+    )
+    .bind(unit_id)
+    .bind(query.from)
+    .bind(query.to)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let mut by_skill: std::collections::BTreeMap<String, SkillValidation> =
+        std::collections::BTreeMap::new();
+    for (day, shift_id, skill, required_count, available) in cells {
+        let entry = by_skill
+            .entry(skill.clone())
+            .or_insert_with(|| SkillValidation {
+                skill,
+                total_required: 0,
+                total_available: 0,
+                deficit_cells: Vec::new(),
+                in_deficit: false,
+            });
+        entry.total_required += i64::from(required_count);
+        entry.total_available += available;
+        if available < i64::from(required_count) {
+            entry.deficit_cells.push(SkillCell {
+                day,
+                shift_id,
+                required_count,
+                available,
+            });
+            entry.in_deficit = true;
+        }
+    }
+    Ok(Json(by_skill.into_values().collect()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DemandQuery {
+    /// A date or a sentinel (`today`, `week`, `month`) resolved in the
+    /// unit's time zone.
+    pub from: String,
+    pub to: String,
+    /// Break the weekly totals down per shift.
+    #[serde(default)]
+    pub by_shift: bool,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct WeeklyDemand {
+    pub iso_year: i32,
+    pub iso_week: i32,
+    /// Monday of the ISO week; the total still only covers days inside the
+    /// requested range, so partial weeks at the edges are partial totals.
+    pub week_start: NaiveDate,
+    pub shift_id: Option<i64>,
+    pub total_required: i64,
+}
+
+/// Total required staff-shifts per ISO week (optionally per shift) for
+/// capacity planning. Responses are served from the short-TTL aggregate
+/// cache; send `Cache-Control: no-cache` to force a fresh computation.
+pub async fn coverage_demand(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Query(query): Query<DemandQuery>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let time_zone = super::units::unit_time_zone(&state.pool, unit_id).await?;
+    let from = super::resolve_date_bound(&query.from, &time_zone, false)?;
+    let to = super::resolve_date_bound(&query.to, &time_zone, true)?;
+    if from > to {
+        return Err(AppError::BadRequest("`from` must not be after `to`".to_string(),
+        ));
+    }
+    let bypass_cache = headers
+        .get(header::CACHE_CONTROL)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("no-cache"));
+    let cache_key = format!("coverage_demand:{unit_id}:{from}:{to}:{}", query.by_shift);
+    if !bypass_cache {
+        if let Some(cached) = state.agg_cache.get(&cache_key).await {
+            return Ok(Json(cached));
+        }
+    }
+    let shift_column = if query.by_shift { "shift_id" } else { "NULL::bigint" };
+    let group_by = if query.by_shift { ", shift_id" } else { "" };
+    let rows = sqlx::query_as::<_, WeeklyDemand>(&format!(
+        "SELECT EXTRACT(ISOYEAR FROM day)::int AS iso_year,
+                EXTRACT(WEEK FROM day)::int AS iso_week,
+                date_trunc('week', day)::date AS week_start,
+                {shift_column} AS shift_id,
+                SUM(required_count)::bigint AS total_required
+         FROM coverage_requirement
+         WHERE unit_id = $1 AND day BETWEEN $2 AND $3
+         GROUP BY 1, 2, 3{group_by}
+         ORDER BY 1, 2{group_by}"
+    ))
+    .bind(unit_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let value = serde_json::to_value(&rows).map_err(internal_error)?;
+    state.agg_cache.insert(cache_key, value.clone()).await;
+    Ok(Json(value))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListCoverageQuery {
+    /// Inclusive day range; either bound may be omitted.
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub shift_id: Option<i64>,
+    /// Page size (default 100, max 500).
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+pub async fn list_coverage(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    headers: HeaderMap,
+    Query(query): Query<ListCoverageQuery>,
+) -> Result<Response, AppError> {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return Err(AppError::Validation(format!("empty range: from {from} is after to {to}"),
+            ));
+        }
+    }
+    // Planners can ask for a spreadsheet via `Accept: text/csv`; the same
+    // filters and pagination apply to both representations.
+    let wants_csv = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains("text/csv"));
+    if wants_csv {
+        return coverage_csv(&state, unit_id, &query).await;
+    }
+    let rows = sqlx::query_as::<_, CoverageRequirement>(
+        "SELECT coverage_id, unit_id, day, shift_id, required_count, required_skill
+         FROM coverage_requirement
+         WHERE unit_id = $1
+           AND ($2::date IS NULL OR day >= $2)
+           AND ($3::date IS NULL OR day <= $3)
+           AND ($4::bigint IS NULL OR shift_id = $4)
+         ORDER BY day, shift_id
+         LIMIT $5 OFFSET $6",
+    )
+    .bind(unit_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.shift_id)
+    .bind(query.limit.unwrap_or(100).clamp(1, 500))
+    .bind(query.offset.unwrap_or(0).max(0))
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(rows).into_response())
+}
+
+/// `.csv` twin of `list_coverage` for clients that can't set Accept headers.
+pub async fn list_coverage_csv(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Query(query): Query<ListCoverageQuery>,
+) -> Result<Response, AppError> {
+    if let (Some(from), Some(to)) = (query.from, query.to) {
+        if from > to {
+            return Err(AppError::Validation(format!("empty range: from {from} is after to {to}"),
+            ));
+        }
+    }
+    coverage_csv(&state, unit_id, &query).await
+}
+
+async fn coverage_csv(
+    state: &AppState,
+    unit_id: i64,
+    query: &ListCoverageQuery,
+) -> Result<Response, AppError> {
+    let rows: Vec<(NaiveDate, String, i32, Option<String>)> = sqlx::query_as(
+        "SELECT c.day, s.name, c.required_count, c.required_skill
+         FROM coverage_requirement c
+         JOIN shift_patterns s ON s.shift_id = c.shift_id
+         WHERE c.unit_id = $1
+           AND ($2::date IS NULL OR c.day >= $2)
+           AND ($3::date IS NULL OR c.day <= $3)
+           AND ($4::bigint IS NULL OR c.shift_id = $4)
+         ORDER BY c.day, c.shift_id
+         LIMIT $5 OFFSET $6",
+    )
+    .bind(unit_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.shift_id)
+    .bind(query.limit.unwrap_or(100).clamp(1, 500))
+    .bind(query.offset.unwrap_or(0).max(0))
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let mut csv = String::from("day,shift_name,required_count,required_skill\n");
+    for (day, shift_name, required_count, required_skill) in rows {
+        csv.push_str(&format!(
+            "{day},{},{required_count},{}\n",
+            csv_field(&shift_name),
+            csv_field(required_skill.as_deref().unwrap_or("")),
+        ));
+    }
+    Ok((
+        [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+        csv,
+    )
+        .into_response())
+}
+
+/// Quote a CSV field when it contains separators or quotes.
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrgCoverageQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub unit_id: Option<i64>,
+    /// Keyset cursor from a previous page's `next_cursor`.
+    pub after: Option<i64>,
+    /// Page size (default 100, max 500).
+    pub limit: Option<i64>,
+}
+
+/// A coverage cell with its unit and shift names resolved for review.
+#[derive(Debug, Serialize, FromRow)]
+pub struct OrgCoverageRow {
+    pub coverage_id: i64,
+    pub unit_id: i64,
+    pub unit_name: String,
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    pub shift_name: String,
+    pub required_count: i32,
+    pub required_skill: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrgCoveragePage {
+    pub entries: Vec<OrgCoverageRow>,
+    /// Pass as `after` to fetch the next page; absent on the last page.
+    pub next_cursor: Option<i64>,
+}
+
+/// Org-wide coverage review: every unit's requirements with names joined
+/// in, cursor-paginated. The per-unit `list_coverage` can't answer "which
+/// units forgot to define coverage for next week" — this can.
+pub async fn org_coverage(
+    State(state): State<AppState>,
+    Path(org_id): Path<i64>,
+    Query(query): Query<OrgCoverageQuery>,
+) -> Result<Json<OrgCoveragePage>, AppError> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 500);
+    let entries = sqlx::query_as::<_, OrgCoverageRow>(
+        "SELECT c.coverage_id, c.unit_id, u.name AS unit_name, c.day,
+                c.shift_id, sp.name AS shift_name, c.required_count, c.required_skill
+         FROM coverage_requirement c
+         JOIN units u ON u.unit_id = c.unit_id
+         JOIN shift_patterns sp ON sp.shift_id = c.shift_id
+         WHERE u.organization_id = $1
+           AND ($2::date IS NULL OR c.day >= $2)
+           AND ($3::date IS NULL OR c.day <= $3)
+           AND ($4::bigint IS NULL OR c.unit_id = $4)
+           AND c.coverage_id > $5
+         ORDER BY c.coverage_id
+         LIMIT $6",
+    )
+    .bind(org_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.unit_id)
+    .bind(query.after.unwrap_or(0))
+    .bind(limit)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let next_cursor = (entries.len() as i64 == limit)
+        .then(|| entries.last().map(|e| e.coverage_id))
+        .flatten();
+    Ok(Json(OrgCoveragePage {
+        entries,
+        next_cursor,
+    }))
+}
@@ -0,0 +1,81 @@
+//! Append-only domain event log ("scenario created", "run succeeded", ...)
+//! for analytics integrations and activity feeds.
+
+use axum::extract::{Query, State};
+use axum::Json;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct DomainEvent {
+    pub event_id: i64,
+    pub event_type: String,
+    pub unit_id: Option<i64>,
+    pub entity_id: Option<i64>,
+    pub payload: Value,
+    pub at: DateTime<Utc>,
+}
+
+/// Record an event. Pass the handler's transaction so the event commits
+/// with the change it describes, or the pool for standalone writes.
+pub async fn emit<'e, E>(
+    executor: E,
+    event_type: &str,
+    unit_id: Option<i64>,
+    entity_id: Option<i64>,
+    payload: &Value,
+) -> sqlx::Result<()>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query(
+        "INSERT INTO domain_events (event_type, unit_id, entity_id, payload)
+         VALUES ($1, $2, $3, $4)",
+    )
+    .bind(event_type)
+    .bind(unit_id)
+    .bind(entity_id)
+    .bind(payload)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListEventsQuery {
+    pub unit_id: Option<i64>,
+    /// Only events on or after this day.
+    pub from: Option<NaiveDate>,
+    /// Page cursor: only events with an id greater than this.
+    pub after: Option<i64>,
+    /// Page size (default 100, max 500).
+    pub limit: Option<i64>,
+}
+
+/// Page through the event log by id, oldest first.
+pub async fn list_events(
+    State(state): State<AppState>,
+    Query(query): Query<ListEventsQuery>,
+) -> Result<Json<Vec<DomainEvent>>, AppError> {
+    let events = sqlx::query_as::<_, DomainEvent>(
+        "SELECT event_id, event_type, unit_id, entity_id, payload, at
+         FROM domain_events
+         WHERE ($1::bigint IS NULL OR unit_id = $1)
+           AND ($2::date IS NULL OR at >= $2)
+           AND event_id > $3
+         ORDER BY event_id
+         LIMIT $4",
+    )
+    .bind(query.unit_id)
+    .bind(query.from)
+    .bind(query.after.unwrap_or(0))
+    .bind(query.limit.unwrap_or(100).clamp(1, 500))
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(events))
+}
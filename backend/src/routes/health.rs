@@ -0,0 +1,49 @@
+//! Liveness endpoint.
+
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+
+use crate::db::AppState;
+
+/// Cap on the probe query so a hung database turns into a fast "degraded"
+/// answer instead of a probe that times out on the kubelet's side.
+const DB_PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/health", get(health))
+}
+
+/// Ping the database so "healthy" means live *and* connected, not just a
+/// process that accepts TCP. Reports the measured round-trip so latency
+/// creep shows up in probe logs before it becomes an outage.
+async fn health(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let started = Instant::now();
+    let ping = tokio::time::timeout(
+        DB_PING_TIMEOUT,
+        sqlx::query("SELECT 1").execute(&state.pool),
+    )
+    .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    match ping {
+        Ok(Ok(_)) => (
+            StatusCode::OK,
+            Json(json!({ "status": "ok", "db_latency_ms": latency_ms })),
+        ),
+        Ok(Err(err)) => {
+            tracing::error!("health probe failed: {err}");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({ "status": "degraded", "db_latency_ms": latency_ms })),
+            )
+        }
+        Err(_) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "status": "degraded", "db_latency_ms": latency_ms })),
+        ),
+    }
+}
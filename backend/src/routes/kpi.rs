@@ -0,0 +1,113 @@
+//! KPI rows computed when a run's result is ingested.
+
+use axum::extract::{Path, Query, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Kpi {
+    pub kpi_id: i64,
+    pub run_id: i64,
+    pub total_assignments: i32,
+    pub understaffed_cells: i32,
+    pub overtime_hours: f64,
+    pub avg_satisfaction: Option<f64>,
+    pub created_at: DateTime<Utc>,
+}
+
+const KPI_COLUMNS: &str = "kpi_id, run_id, total_assignments, understaffed_cells, \
+                           overtime_hours, avg_satisfaction, created_at";
+
+pub async fn get_kpi(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+) -> Result<Json<Kpi>, AppError> {
+    let kpi = sqlx::query_as::<_, Kpi>(&format!(
+        "SELECT {KPI_COLUMNS} FROM kpi WHERE run_id = $1"
+    ))
+    .bind(run_id)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(kpi))
+}
+
+pub async fn list_kpis(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+) -> Result<Json<Vec<Kpi>>, AppError> {
+    let kpis = sqlx::query_as::<_, Kpi>(
+        "SELECT k.kpi_id, k.run_id, k.total_assignments, k.understaffed_cells,
+                k.overtime_hours, k.avg_satisfaction, k.created_at
+         FROM kpi k
+         JOIN solver_runs r ON r.run_id = k.run_id
+         JOIN scenarios s ON s.scenario_id = r.scenario_id
+         WHERE s.unit_id = $1
+         ORDER BY k.run_id DESC",
+    )
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(kpis))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    pub unit_id: i64,
+    /// Filter on the run's start day.
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Bulk KPI history as a spreadsheet: one row per run, joined with the run's
+/// scenario and start time.
+pub async fn export_kpis_csv(
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, AppError> {
+    #[allow(clippy::type_complexity)]
+    let rows: Vec<(i64, i64, Option<DateTime<Utc>>, i32, i32, f64, Option<f64>)> = sqlx::query_as(
+        "SELECT k.run_id, r.scenario_id, r.started_at, k.total_assignments,
+                k.understaffed_cells, k.overtime_hours, k.avg_satisfaction
+         FROM kpi k
+         JOIN solver_runs r ON r.run_id = k.run_id
+         JOIN scenarios s ON s.scenario_id = r.scenario_id
+         WHERE s.unit_id = $1
+           AND ($2::date IS NULL OR r.started_at::date >= $2)
+           AND ($3::date IS NULL OR r.started_at::date <= $3)
+         ORDER BY k.run_id",
+    )
+    .bind(query.unit_id)
+    .bind(query.from)
+    .bind(query.to)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let mut csv = String::from(
+        "run_id,scenario_id,started_at,total_assignments,understaffed_cells,overtime_hours,avg_satisfaction\n",
+    );
+    for (run_id, scenario_id, started_at, total, understaffed, overtime, satisfaction) in rows {
+        csv.push_str(&format!(
+            "{run_id},{scenario_id},{},{total},{understaffed},{overtime},{}\n",
+            started_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            satisfaction.map(|s| s.to_string()).unwrap_or_default(),
+        ));
+    }
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"kpi-export.csv\"",
+            ),
+        ],
+        csv,
+    )
+        .into_response())
+}
@@ -0,0 +1,378 @@
+//! HTTP route modules, one per resource.
+
+pub mod audit;
+pub mod auth;
+pub mod availability;
+pub mod coverage;
+pub mod events;
+pub mod health;
+pub mod kpi;
+pub mod organizations;
+pub mod policy_sets;
+pub mod preferences;
+pub mod scenarios;
+pub mod shift_patterns;
+pub mod solver_runs;
+pub mod staffs;
+pub mod sync;
+pub mod time_off;
+pub mod units;
+pub mod users;
+
+use axum::http::StatusCode;
+use axum::routing::{delete, get, post, put};
+use axum::Router;
+
+use crate::db::AppState;
+
+/// Map any error to a 500 with a short message. Handlers use this for
+/// failures we don't expect during normal operation.
+pub fn internal_error<E: std::fmt::Display>(err: E) -> (StatusCode, String) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("internal error: {err}"),
+    )
+}
+
+/// Accumulates per-field problems while validating a request body, so a
+/// 422 can report everything wrong at once instead of one problem per
+/// round-trip. The body is `{"error": "validation failed", "fields": {...}}`
+/// with a list of messages per field.
+#[derive(Debug, Default)]
+pub struct ValidationErrors {
+    fields: std::collections::BTreeMap<String, Vec<String>>,
+}
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.fields
+            .entry(field.to_string())
+            .or_default()
+            .push(message.into());
+    }
+
+    /// `Err(422)` when any problem was recorded, `Ok(())` otherwise.
+    pub fn check(self) -> Result<(), crate::error::AppError> {
+        if self.fields.is_empty() {
+            return Ok(());
+        }
+        Err(crate::error::AppError::Fields(self.fields))
+    }
+}
+
+/// Parse a date bound that may be a literal date or a sentinel (`today`,
+/// `week`, `month`) resolved against the unit's time zone. Sentinels span a
+/// range, so `end_of_range` picks which edge this bound takes: `from=week`
+/// resolves to Monday, `to=week` to Sunday.
+pub fn resolve_date_bound(
+    value: &str,
+    time_zone: &str,
+    end_of_range: bool,
+) -> Result<chrono::NaiveDate, (StatusCode, String)> {
+    use chrono::Datelike;
+    let today = solver_runs::local_day(chrono::Utc::now(), time_zone);
+    match value {
+        "today" => Ok(today),
+        "week" => {
+            let week = today.week(chrono::Weekday::Mon);
+            Ok(if end_of_range {
+                week.last_day()
+            } else {
+                week.first_day()
+            })
+        }
+        "month" => {
+            let first = today.with_day(1).expect("day 1 exists in every month");
+            Ok(if end_of_range {
+                let next = if first.month() == 12 {
+                    first.with_year(first.year() + 1).unwrap().with_month(1)
+                } else {
+                    first.with_month(first.month() + 1)
+                }
+                .expect("first of next month exists");
+                next.pred_opt().expect("month has a last day")
+            } else {
+                first
+            })
+        }
+        literal => literal.parse().map_err(|_| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("invalid date '{literal}', expected YYYY-MM-DD, 'today', 'week' or 'month'"),
+            )
+        }),
+    }
+}
+
+/// Everything mounted under `/api/v1`.
+pub fn api_router() -> Router<AppState> {
+    Router::new()
+        // auth (the middleware exempts this path)
+        .route("/auth/login", post(auth::login))
+        // organizations & sites
+        .route(
+            "/organizations",
+            post(organizations::create_org).get(organizations::list_orgs),
+        )
+        .route(
+            "/organizations/:org_id",
+            get(organizations::get_org)
+                .patch(organizations::patch_org)
+                .delete(organizations::delete_org),
+        )
+        .route(
+            "/organizations/:org_id/sites",
+            post(organizations::create_site).get(organizations::list_sites),
+        )
+        .route(
+            "/organizations/:org_id/units",
+            post(units::create_unit).get(units::list_units),
+        )
+        .route(
+            "/organizations/:org_id/coverage",
+            get(coverage::org_coverage),
+        )
+        // units
+        .route(
+            "/units/:unit_id",
+            get(units::get_unit)
+                .patch(units::patch_unit)
+                .delete(units::delete_unit),
+        )
+        .route(
+            "/units/:unit_id/staffs",
+            post(staffs::create_staff).get(staffs::list_staffs_by_unit),
+        )
+        .route(
+            "/units/:unit_id/staffs/transfer",
+            post(staffs::transfer_staffs),
+        )
+        .route("/units/:unit_id/roster", get(staffs::unit_roster))
+        .route("/units/:unit_id/config-issues", get(units::config_issues))
+        .route(
+            "/units/:unit_id/shift-patterns",
+            post(shift_patterns::create_shift).get(shift_patterns::list_shifts_by_unit),
+        )
+        .route(
+            "/units/:unit_id/shift-patterns/from-template",
+            post(shift_patterns::create_from_template),
+        )
+        .route(
+            "/units/:unit_id/shift-patterns/order",
+            put(shift_patterns::reorder_shifts),
+        )
+        .route(
+            "/units/:unit_id/coverage",
+            put(coverage::bulk_upsert_coverage).get(coverage::list_coverage),
+        )
+        .route(
+            "/units/:unit_id/coverage/clear",
+            post(coverage::clear_coverage),
+        )
+        .route(
+            "/units/:unit_id/coverage/copy",
+            post(coverage::copy_coverage),
+        )
+        .route(
+            "/units/:unit_id/coverage/demand",
+            get(coverage::coverage_demand),
+        )
+        .route(
+            "/units/:unit_id/coverage/validate",
+            post(coverage::validate_coverage),
+        )
+        .route(
+            "/units/:unit_id/coverage.csv",
+            get(coverage::list_coverage_csv),
+        )
+        .route(
+            "/units/:unit_id/scenarios",
+            post(scenarios::create_scenario).get(scenarios::list_scenarios),
+        )
+        .route(
+            "/units/:unit_id/scenarios/sources",
+            get(scenarios::scenario_sources),
+        )
+        .route(
+            "/units/:unit_id/policy-sets",
+            post(policy_sets::create_policy).get(policy_sets::list_policies),
+        )
+        .route("/units/:unit_id/changes", get(sync::unit_changes))
+        .route("/units/:unit_id/solver-runs", get(solver_runs::list_runs))
+        .route("/units/:unit_id/kpi", get(kpi::list_kpis))
+        // staff
+        .route(
+            "/staffs/:staff_id",
+            get(staffs::get_staff)
+                .patch(staffs::patch_staff)
+                .delete(staffs::delete_staff),
+        )
+        .route(
+            "/staffs/:staff_id/preferences/summary",
+            get(preferences::preferences_summary),
+        )
+        .route("/staffs/:staff_id/profile", get(staffs::staff_profile))
+        .route(
+            "/staffs/:staff_id/skill-expiries",
+            get(staffs::list_skill_expiries).put(staffs::upsert_skill_expiry),
+        )
+        .route(
+            "/staffs/:staff_id/skill-expiries/:skill",
+            delete(staffs::delete_skill_expiry),
+        )
+        .route(
+            "/staffs/:staff_id/time-off",
+            post(time_off::create_time_off).get(time_off::list_time_off),
+        )
+        .route("/time-off/:time_off_id", delete(time_off::delete_time_off))
+        .route(
+            "/units/:unit_id/availability/matrix.csv",
+            get(availability::availability_matrix_csv),
+        )
+        .route(
+            "/staffs/:staff_id/availability",
+            delete(availability::delete_availability_range),
+        )
+        .route(
+            "/staffs/:staff_id/preferences",
+            delete(preferences::delete_preferences_range),
+        )
+        .route(
+            "/staffs/:staff_id/preferences/normalize",
+            post(preferences::normalize_preferences),
+        )
+        // shift patterns
+        .route(
+            "/shift-patterns/:shift_id",
+            get(shift_patterns::get_shift)
+                .patch(shift_patterns::patch_shift)
+                .delete(shift_patterns::delete_shift),
+        )
+        .route(
+            "/shift-patterns/:shift_id/usage",
+            get(shift_patterns::shift_usage),
+        )
+        // availability & preferences (bulk editors)
+        .route(
+            "/units/:unit_id/staff-inputs/bulk",
+            post(availability::bulk_upsert_staff_inputs),
+        )
+        .route("/availability", get(availability::list_availability))
+        .route(
+            "/availability/bulk",
+            post(availability::bulk_upsert_availability),
+        )
+        .route("/preferences", get(preferences::list_preferences))
+        .route(
+            "/preferences/bulk",
+            post(preferences::bulk_upsert_preferences),
+        )
+        // scenarios & runs
+        .route("/scenarios/hash", post(scenarios::hash_scenario))
+        .route("/scenarios", delete(scenarios::bulk_delete_scenarios))
+        .route(
+            "/scenarios/:scenario_id",
+            get(scenarios::get_scenario).delete(scenarios::delete_scenario),
+        )
+        .route(
+            "/scenarios/:scenario_id/complexity",
+            get(scenarios::scenario_complexity),
+        )
+        .route("/scenarios/:scenario_id/run", post(solver_runs::create_run))
+        .route("/solver-runs/active", get(solver_runs::active_runs))
+        .route("/solver-runs/queue", get(solver_runs::queued_runs))
+        .route("/solver-runs/compare", post(solver_runs::compare_runs))
+        .route("/solver-runs/:run_id", get(solver_runs::get_run))
+        .route(
+            "/solver-runs/:run_id/ingest-result",
+            post(solver_runs::ingest_result),
+        )
+        .route(
+            "/solver-runs/:run_id/assignments",
+            get(solver_runs::list_assignments).post(solver_runs::create_assignment),
+        )
+        .route(
+            "/solver-runs/:run_id/assignment-history",
+            get(solver_runs::assignment_history),
+        )
+        .route(
+            "/solver-runs/:run_id/postfill",
+            post(solver_runs::postfill_run),
+        )
+        .route(
+            "/solver-runs/:run_id/unassign-staff",
+            post(solver_runs::unassign_staff),
+        )
+        .route(
+            "/assignments/:assignment_id",
+            get(solver_runs::get_assignment)
+                .patch(solver_runs::reassign_assignment)
+                .delete(solver_runs::delete_assignment),
+        )
+        .route(
+            "/solver-runs/:run_id/source-breakdown",
+            get(solver_runs::source_breakdown),
+        )
+        .route(
+            "/solver-runs/:run_id/utilization",
+            get(solver_runs::run_utilization),
+        )
+        .route(
+            "/solver-runs/:run_id/timeoff-violations",
+            get(time_off::run_timeoff_violations),
+        )
+        .route(
+            "/solver-runs/:run_id/consecutive-day-violations",
+            get(solver_runs::consecutive_day_violations),
+        )
+        .route("/solver-runs/:run_id/summary", get(solver_runs::run_summary))
+        .route(
+            "/solver-runs/:run_id/daily-summary",
+            get(solver_runs::daily_summary),
+        )
+        .route("/solver-runs/:run_id/bundle", get(solver_runs::run_bundle))
+        .route(
+            "/solver-runs/:run_id/notes",
+            post(solver_runs::create_run_note).get(solver_runs::list_run_notes),
+        )
+        .route("/solver-runs/:run_id/kpi", get(kpi::get_kpi))
+        .route("/kpi/export.csv", get(kpi::export_kpis_csv))
+        // policies
+        .route(
+            "/policy-sets/:policy_id",
+            get(policy_sets::get_policy)
+                .patch(policy_sets::patch_policy)
+                .delete(policy_sets::delete_policy),
+        )
+        .route(
+            "/policy-sets/:policy_id/runs",
+            get(policy_sets::list_policy_runs),
+        )
+        .route(
+            "/policy-sets/:policy_id/effective",
+            get(policy_sets::effective_policy),
+        )
+        .route(
+            "/policy-sets/:policy_id/activate",
+            post(policy_sets::activate_policy),
+        )
+        // event & audit logs
+        .route("/events", get(events::list_events))
+        .route("/audit", get(audit::list_audit))
+        .route("/audit-log", get(audit::audit_log))
+        // users
+        .route("/admin/rehash-passwords", post(users::rehash_passwords))
+        .route("/admin/scenarios/rehash", post(scenarios::rehash_scenarios))
+        .route("/me/organizations", get(users::my_organizations))
+        .route("/users", post(users::create_user).get(users::list_users))
+        .route(
+            "/users/:user_id",
+            get(users::get_user)
+                .patch(users::patch_user)
+                .delete(users::delete_user),
+        )
+}
@@ -0,0 +1,192 @@
+//! Organizations and their sites.
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::internal_error;
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Organization {
+    pub organization_id: i64,
+    pub name: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Site {
+    pub site_id: i64,
+    pub organization_id: i64,
+    pub name: String,
+    pub time_zone: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateOrgBody {
+    pub name: String,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchOrgBody {
+    pub name: Option<String>,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSiteBody {
+    pub name: String,
+    pub time_zone: Option<String>,
+}
+
+pub async fn create_org(
+    State(state): State<AppState>,
+    Json(body): Json<CreateOrgBody>,
+) -> Result<(StatusCode, Json<Organization>), AppError> {
+    let org = sqlx::query_as::<_, Organization>(
+        "INSERT INTO organizations (name, status)
+         VALUES ($1, COALESCE($2, 'active'))
+         RETURNING organization_id, name, status, created_at",
+    )
+    .bind(&body.name)
+    .bind(&body.status)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok((StatusCode::CREATED, Json(org)))
+}
+
+pub async fn list_orgs(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Organization>>, AppError> {
+    let orgs = sqlx::query_as::<_, Organization>(
+        "SELECT organization_id, name, status, created_at
+         FROM organizations ORDER BY organization_id",
+    )
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(orgs))
+}
+
+pub async fn get_org(
+    State(state): State<AppState>,
+    Path(org_id): Path<i64>,
+) -> Result<Json<Organization>, AppError> {
+    let org = sqlx::query_as::<_, Organization>(
+        "SELECT organization_id, name, status, created_at
+         FROM organizations WHERE organization_id = $1",
+    )
+    .bind(org_id)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(org))
+}
+
+pub async fn patch_org(
+    State(state): State<AppState>,
+    Path(org_id): Path<i64>,
+    Json(body): Json<PatchOrgBody>,
+) -> Result<Json<Organization>, AppError> {
+    let org = sqlx::query_as::<_, Organization>(
+        "UPDATE organizations
+         SET name = COALESCE($2, name), status = COALESCE($3, status)
+         WHERE organization_id = $1
+         RETURNING organization_id, name, status, created_at",
+    )
+    .bind(org_id)
+    .bind(&body.name)
+    .bind(&body.status)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(org))
+}
+
+pub async fn delete_org(
+    State(state): State<AppState>,
+    user: Option<crate::auth::CurrentUser>,
+    Path(org_id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    crate::auth::require_role(user.as_ref(), &["admin"])?;
+    sqlx::query("DELETE FROM organizations WHERE organization_id = $1")
+        .bind(org_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Gate a write on the owning organization being `active`. Suspended orgs
+/// stay readable but reject mutations with a 403; an org-less admin (the
+/// superadmin in this schema) may still write, e.g. to fix up a suspended
+/// tenant. A missing organization is a 404.
+pub async fn require_active_org(
+    state: &AppState,
+    auth: Option<&crate::auth::CurrentUser>,
+    headers: &HeaderMap,
+    org_id: i64,
+) -> Result<(), (StatusCode, String)> {
+    let org: Option<(String,)> =
+        sqlx::query_as("SELECT status FROM organizations WHERE organization_id = $1")
+            .bind(org_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    let Some((status,)) = org else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("organization {org_id} does not exist"),
+        ));
+    };
+    if status == "active" {
+        return Ok(());
+    }
+    if let Ok(user) = super::users::current_user(state, auth, headers).await {
+        if user.role == "admin" && user.organization_id.is_none() {
+            return Ok(());
+        }
+    }
+    Err((
+        StatusCode::FORBIDDEN,
+        format!("organization {org_id} is {status}; writes are disabled until it is active again"),
+    ))
+}
+
+pub async fn create_site(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    Path(org_id): Path<i64>,
+    headers: HeaderMap,
+    Json(body): Json<CreateSiteBody>,
+) -> Result<(StatusCode, Json<Site>), AppError> {
+    require_active_org(&state, auth.as_ref(), &headers, org_id).await?;
+    let site = sqlx::query_as::<_, Site>(
+        "INSERT INTO organization_site (organization_id, name, time_zone)
+         VALUES ($1, $2, COALESCE($3, 'UTC'))
+         RETURNING site_id, organization_id, name, time_zone, created_at",
+    )
+    .bind(org_id)
+    .bind(&body.name)
+    .bind(&body.time_zone)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok((StatusCode::CREATED, Json(site)))
+}
+
+pub async fn list_sites(
+    State(state): State<AppState>,
+    Path(org_id): Path<i64>,
+) -> Result<Json<Vec<Site>>, AppError> {
+    let sites = sqlx::query_as::<_, Site>(
+        "SELECT site_id, organization_id, name, time_zone, created_at
+         FROM organization_site WHERE organization_id = $1 ORDER BY site_id",
+    )
+    .bind(org_id)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(sites))
+}
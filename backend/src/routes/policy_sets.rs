@@ -0,0 +1,314 @@
+//! Policy sets: solver weights and hard rules per unit.
+
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PolicySet {
+    pub policy_id: i64,
+    pub unit_id: i64,
+    pub name: String,
+    pub weights: Value,
+    pub hard_rules: Value,
+    /// At most one policy per unit is active; see `activate_policy`.
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePolicyBody {
+    pub name: String,
+    pub weights: Option<Value>,
+    pub hard_rules: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchPolicyBody {
+    pub name: Option<String>,
+    pub weights: Option<Value>,
+    pub hard_rules: Option<Value>,
+}
+
+const POLICY_COLUMNS: &str = "policy_id, unit_id, name, weights, hard_rules, is_active, created_at";
+
+pub async fn create_policy(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Json(body): Json<CreatePolicyBody>,
+) -> Result<(StatusCode, Json<PolicySet>), AppError> {
+    let policy = sqlx::query_as::<_, PolicySet>(&format!(
+        "INSERT INTO policy_sets (unit_id, name, weights, hard_rules)
+         VALUES ($1, $2, COALESCE($3, '{{}}'::jsonb), COALESCE($4, '{{}}'::jsonb))
+         RETURNING {POLICY_COLUMNS}"
+    ))
+    .bind(unit_id)
+    .bind(&body.name)
+    .bind(&body.weights)
+    .bind(&body.hard_rules)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok((StatusCode::CREATED, Json(policy)))
+}
+
+pub async fn list_policies(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+) -> Result<Json<Vec<PolicySet>>, AppError> {
+    let policies = sqlx::query_as::<_, PolicySet>(&format!(
+        "SELECT {POLICY_COLUMNS} FROM policy_sets WHERE unit_id = $1 ORDER BY policy_id"
+    ))
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(policies))
+}
+
+pub async fn get_policy(
+    State(state): State<AppState>,
+    Path(policy_id): Path<i64>,
+) -> Result<Json<PolicySet>, AppError> {
+    let policy = sqlx::query_as::<_, PolicySet>(&format!(
+        "SELECT {POLICY_COLUMNS} FROM policy_sets WHERE policy_id = $1"
+    ))
+    .bind(policy_id)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(policy))
+}
+
+/// Patch a policy. Accepts either the regular partial body, or an RFC 6902
+/// JSON Patch (`Content-Type: application/json-patch+json`) applied against
+/// a document of the mutable fields (`name`, `weights`, `hard_rules`).
+pub async fn patch_policy(
+    State(state): State<AppState>,
+    Path(policy_id): Path<i64>,
+    headers: HeaderMap,
+    raw_body: Bytes,
+) -> Result<Json<PolicySet>, AppError> {
+    let is_json_patch = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json-patch+json"));
+    let body = if is_json_patch {
+        json_patch_body(&state, policy_id, &raw_body).await?
+    } else {
+        serde_json::from_slice::<PatchPolicyBody>(&raw_body)
+            .map_err(|e| AppError::BadRequest(format!("invalid body: {e}")))?
+    };
+    apply_patch(&state, policy_id, body).await
+}
+
+/// Apply an RFC 6902 patch to the policy's current mutable fields and turn
+/// the result into a full-object patch body.
+async fn json_patch_body(
+    state: &AppState,
+    policy_id: i64,
+    raw_body: &[u8],
+) -> Result<PatchPolicyBody, AppError> {
+    let patch: json_patch::Patch = serde_json::from_slice(raw_body)
+        .map_err(|e| AppError::BadRequest(format!("invalid JSON Patch: {e}")))?;
+    let current = sqlx::query_as::<_, PolicySet>(&format!(
+        "SELECT {POLICY_COLUMNS} FROM policy_sets WHERE policy_id = $1"
+    ))
+    .bind(policy_id)
+    .fetch_one(&state.pool)
+    .await?;
+
+    let mut doc = serde_json::json!({
+        "name": current.name,
+        "weights": current.weights,
+        "hard_rules": current.hard_rules,
+    });
+    json_patch::patch(&mut doc, &patch)
+        .map_err(|e| AppError::Validation(format!("patch failed: {e}")))?;
+
+    // Validate the patched document still has the right shape.
+    if !doc["weights"].is_object() || !doc["hard_rules"].is_object() {
+        return Err(AppError::Validation(
+            "patched weights/hard_rules must remain objects".to_string(),
+        ));
+    }
+    let name = doc["name"].as_str().ok_or_else(|| {
+        AppError::Validation("patched name must remain a string".to_string())
+    })?;
+    Ok(PatchPolicyBody {
+        name: Some(name.to_string()),
+        weights: Some(doc["weights"].take()),
+        hard_rules: Some(doc["hard_rules"].take()),
+    })
+}
+
+async fn apply_patch(
+    state: &AppState,
+    policy_id: i64,
+    body: PatchPolicyBody,
+) -> Result<Json<PolicySet>, AppError> {
+    let policy = sqlx::query_as::<_, PolicySet>(&format!(
+        "UPDATE policy_sets
+         SET name = COALESCE($2, name),
+             weights = COALESCE($3, weights),
+             hard_rules = COALESCE($4, hard_rules)
+         WHERE policy_id = $1
+         RETURNING {POLICY_COLUMNS}"
+    ))
+    .bind(policy_id)
+    .bind(&body.name)
+    .bind(&body.weights)
+    .bind(&body.hard_rules)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(policy))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PolicyRunsQuery {
+    /// Keyset cursor: only runs with an id greater than this.
+    pub after: Option<i64>,
+    /// Page size (default 100, max 500).
+    pub limit: Option<i64>,
+}
+
+/// A run that referenced the policy, with its KPIs when ingested.
+#[derive(Debug, Serialize, FromRow)]
+pub struct PolicyRun {
+    pub run_id: i64,
+    pub scenario_id: i64,
+    pub status: String,
+    pub solver_status: Option<String>,
+    pub objective: Option<f64>,
+    pub total_assignments: Option<i32>,
+    pub understaffed_cells: Option<i32>,
+    pub overtime_hours: Option<f64>,
+    pub avg_satisfaction: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Runs that used this policy, oldest first, for before/after comparison
+/// while tuning weights.
+pub async fn list_policy_runs(
+    State(state): State<AppState>,
+    Path(policy_id): Path<i64>,
+    Query(query): Query<PolicyRunsQuery>,
+) -> Result<Json<Vec<PolicyRun>>, AppError> {
+    let runs = sqlx::query_as::<_, PolicyRun>(
+        "SELECT r.run_id, r.scenario_id, r.status, r.solver_status, r.objective,
+                k.total_assignments, k.understaffed_cells, k.overtime_hours, k.avg_satisfaction,
+                r.created_at, r.finished_at
+         FROM solver_runs r
+         LEFT JOIN kpi k ON k.run_id = r.run_id
+         WHERE r.policy_id = $1 AND r.run_id > $2
+         ORDER BY r.run_id
+         LIMIT $3",
+    )
+    .bind(policy_id)
+    .bind(query.after.unwrap_or(0))
+    .bind(query.limit.unwrap_or(100).clamp(1, 500))
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(runs))
+}
+
+/// Make this the unit's active policy. The previous active is unset and the
+/// new one set in a single transaction; the partial unique index on
+/// `(unit_id) WHERE is_active` turns a lost race into a 409 instead of two
+/// (or zero) active policies.
+pub async fn activate_policy(
+    State(state): State<AppState>,
+    Path(policy_id): Path<i64>,
+) -> Result<Json<PolicySet>, AppError> {
+    let mut tx = state.pool.begin().await?;
+    let unit: Option<(i64,)> =
+        sqlx::query_as("SELECT unit_id FROM policy_sets WHERE policy_id = $1 FOR UPDATE")
+            .bind(policy_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    let Some((unit_id,)) = unit else {
+        return Err(AppError::NotFound);
+    };
+    sqlx::query(
+        "UPDATE policy_sets SET is_active = FALSE
+         WHERE unit_id = $1 AND is_active AND policy_id <> $2",
+    )
+    .bind(unit_id)
+    .bind(policy_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(activation_conflict)?;
+    let policy = sqlx::query_as::<_, PolicySet>(&format!(
+        "UPDATE policy_sets SET is_active = TRUE WHERE policy_id = $1
+         RETURNING {POLICY_COLUMNS}"
+    ))
+    .bind(policy_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(activation_conflict)?;
+    tx.commit().await.map_err(activation_conflict)?;
+    Ok(Json(policy))
+}
+
+/// Map the partial unique index's violation — or a deadlock between two
+/// switches — to a 409 (a concurrent activation won); everything else
+/// stays a 500.
+fn activation_conflict(err: sqlx::Error) -> AppError {
+    if let Some(db_err) = err.as_database_error() {
+        // 40P01 = deadlock_detected.
+        if db_err.is_unique_violation() || db_err.code().as_deref() == Some("40P01") {
+            return AppError::Conflict(
+                "another activation for this unit won the race; retry".to_string(),
+            );
+        }
+    }
+    AppError::Db(err)
+}
+
+#[derive(Debug, Serialize)]
+pub struct EffectivePolicy {
+    pub policy_id: i64,
+    /// The policy's weights overlaid on the system defaults — exactly what
+    /// `create_run` forwards when no scenario weights intervene.
+    pub weights: Value,
+    pub hard_rules: Value,
+}
+
+/// Show what a solve with this policy would actually apply, defaults
+/// included, so the merge is transparent before anyone hits run.
+pub async fn effective_policy(
+    State(state): State<AppState>,
+    Path(policy_id): Path<i64>,
+) -> Result<Json<EffectivePolicy>, AppError> {
+    let policy: Option<(Value, Value)> =
+        sqlx::query_as("SELECT weights, hard_rules FROM policy_sets WHERE policy_id = $1")
+            .bind(policy_id)
+            .fetch_optional(&state.pool)
+            .await?;
+    let Some((weights, hard_rules)) = policy else {
+        return Err(AppError::NotFound);
+    };
+    let merged = super::solver_runs::merge_weights(super::solver_runs::default_weights(), &weights);
+    Ok(Json(EffectivePolicy {
+        policy_id,
+        weights: merged,
+        hard_rules,
+    }))
+}
+
+pub async fn delete_policy(
+    State(state): State<AppState>,
+    Path(policy_id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    sqlx::query("DELETE FROM policy_sets WHERE policy_id = $1")
+        .bind(policy_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
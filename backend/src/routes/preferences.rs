@@ -0,0 +1,328 @@
+//! Staff preferences: penalties for (day, shift) cells staff want to avoid.
+
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::internal_error;
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Preference {
+    pub preference_id: i64,
+    pub staff_id: i64,
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    pub penalty: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PreferenceUpsertItem {
+    pub staff_id: i64,
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    /// Penalty >= 0; higher means the staff wants this cell less.
+    pub penalty: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkPreferencesBody {
+    pub items: Vec<PreferenceUpsertItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkResult {
+    pub upserted: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NormalizeBody {
+    /// Target ceiling; defaults to `MAX_PREFERENCE_PENALTY` when omitted.
+    pub max: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NormalizeResult {
+    pub updated: u64,
+    /// The largest penalty before rescaling (0 when nothing changed).
+    pub from_max: i32,
+    pub to_max: i32,
+}
+
+/// Ceiling on preference penalties, from `MAX_PREFERENCE_PENALTY`
+/// (default 1000). Bounded even when unconfigured: one fat-fingered
+/// 1_000_000 would otherwise dominate the solver objective.
+fn max_penalty() -> i32 {
+    std::env::var("MAX_PREFERENCE_PENALTY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(1000)
+}
+
+/// Check a batch of preference cells before the transaction opens:
+/// penalties must lie in `0..=max_penalty()`, and every violation is
+/// reported by index in one 422 so the whole batch can be fixed at once.
+pub(crate) fn validate_preference_items(
+    items: &[PreferenceUpsertItem],
+) -> Result<(), AppError> {
+    let max = max_penalty();
+    let mut errors = super::ValidationErrors::new();
+    for (index, item) in items.iter().enumerate() {
+        if item.penalty < 0 {
+            errors.add(
+                &format!("items[{index}].penalty"),
+                format!(
+                    "must not be negative, got {} (staff {} on {})",
+                    item.penalty, item.staff_id, item.day
+                ),
+            );
+        } else if item.penalty > max {
+            errors.add(
+                &format!("items[{index}].penalty"),
+                format!(
+                    "penalty {} for staff {} on {} exceeds the maximum of {max}",
+                    item.penalty, item.staff_id, item.day
+                ),
+            );
+        }
+    }
+    errors.check()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListPreferencesQuery {
+    pub staff_id: Option<i64>,
+    /// Filter to one unit's staff (joins through `staffs`).
+    pub unit_id: Option<i64>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Only penalties at or above this, to fetch just the strong aversions.
+    pub min_penalty: Option<i32>,
+}
+
+/// Read back stored preferences, the counterpart to the bulk upsert.
+pub async fn list_preferences(
+    State(state): State<AppState>,
+    Query(query): Query<ListPreferencesQuery>,
+) -> Result<Json<Vec<Preference>>, AppError> {
+    if query.from > query.to {
+        return Err(AppError::BadRequest("`from` must not be after `to`".to_string(),
+        ));
+    }
+    // Same span clamp as the availability listing: long ranges page by
+    // moving `from` rather than erroring.
+    let to = query
+        .to
+        .min(query.from + chrono::Duration::days(super::availability::MAX_LIST_SPAN_DAYS - 1));
+    // An unknown staff filter is a 404, not an indistinguishable empty list.
+    if let Some(staff_id) = query.staff_id {
+        sqlx::query("SELECT staff_id FROM staffs WHERE staff_id = $1")
+            .bind(staff_id)
+            .fetch_one(&state.pool)
+            .await?;
+    }
+    let rows = sqlx::query_as::<_, Preference>(
+        "SELECT p.preference_id, p.staff_id, p.day, p.shift_id, p.penalty
+         FROM preferences p
+         JOIN staffs s ON s.staff_id = p.staff_id
+         WHERE ($1::bigint IS NULL OR p.staff_id = $1)
+           AND ($2::bigint IS NULL OR s.unit_id = $2)
+           AND p.day BETWEEN $3 AND $4
+           AND ($5::int IS NULL OR p.penalty >= $5)
+         ORDER BY p.day, p.shift_id, p.staff_id",
+    )
+    .bind(query.staff_id)
+    .bind(query.unit_id)
+    .bind(query.from)
+    .bind(to)
+    .bind(query.min_penalty)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(rows))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PreferenceSummaryQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// How many most-avoided shifts to include (default 3).
+    pub top: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PreferenceSummary {
+    pub staff_id: i64,
+    pub total_penalty: i64,
+    pub entry_count: i64,
+    pub avg_penalty: f64,
+    pub top_avoided: Vec<TopAvoidedShift>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct TopAvoidedShift {
+    pub shift_id: i64,
+    pub shift_name: String,
+    pub total_penalty: i64,
+}
+
+/// Summarize a staff member's preference penalties over a window: totals
+/// plus the shifts they most want to avoid, for coaching conversations.
+pub async fn preferences_summary(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+    Query(query): Query<PreferenceSummaryQuery>,
+) -> Result<Json<PreferenceSummary>, AppError> {
+    if query.from > query.to {
+        return Err(AppError::BadRequest("`from` must not be after `to`".to_string(),
+        ));
+    }
+    let (total_penalty, entry_count): (i64, i64) = sqlx::query_as(
+        "SELECT COALESCE(SUM(penalty), 0), COUNT(*)
+         FROM preferences
+         WHERE staff_id = $1 AND day BETWEEN $2 AND $3",
+    )
+    .bind(staff_id)
+    .bind(query.from)
+    .bind(query.to)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let top_avoided = sqlx::query_as::<_, TopAvoidedShift>(
+        "SELECT p.shift_id, s.name AS shift_name, SUM(p.penalty) AS total_penalty
+         FROM preferences p
+         JOIN shift_patterns s ON s.shift_id = p.shift_id
+         WHERE p.staff_id = $1 AND p.day BETWEEN $2 AND $3
+         GROUP BY p.shift_id, s.name
+         HAVING SUM(p.penalty) > 0
+         ORDER BY total_penalty DESC
+         LIMIT $4",
+    )
+    .bind(staff_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.top.unwrap_or(3).clamp(1, 20))
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let avg_penalty = if entry_count > 0 {
+        total_penalty as f64 / entry_count as f64
+    } else {
+        0.0
+    };
+    Ok(Json(PreferenceSummary {
+        staff_id,
+        total_penalty,
+        entry_count,
+        avg_penalty,
+        top_avoided,
+    }))
+}
+
+/// Remove a staff member's preference rows in a date range; the bounded
+/// range is required so a typo can't wipe their whole history.
+pub async fn delete_preferences_range(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+    Query(query): Query<super::availability::DeleteRangeQuery>,
+) -> Result<Json<super::availability::DeleteResult>, AppError> {
+    if query.from > query.to {
+        return Err(AppError::BadRequest("`from` must not be after `to`".to_string(),
+        ));
+    }
+    let deleted = sqlx::query(
+        "DELETE FROM preferences WHERE staff_id = $1 AND day BETWEEN $2 AND $3",
+    )
+    .bind(staff_id)
+    .bind(query.from)
+    .bind(query.to)
+    .execute(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+    Ok(Json(super::availability::DeleteResult { deleted }))
+}
+
+pub async fn bulk_upsert_preferences(
+    State(state): State<AppState>,
+    Json(body): Json<BulkPreferencesBody>,
+) -> Result<Json<BulkResult>, AppError> {
+    validate_preference_items(&body.items)?;
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    for item in &body.items {
+        sqlx::query(
+            "INSERT INTO preferences (staff_id, day, shift_id, penalty)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (staff_id, day, shift_id)
+             DO UPDATE SET penalty = EXCLUDED.penalty",
+        )
+        .bind(item.staff_id)
+        .bind(item.day)
+        .bind(item.shift_id)
+        .bind(item.penalty)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+    tx.commit().await.map_err(internal_error)?;
+    Ok(Json(BulkResult {
+        upserted: body.items.len(),
+    }))
+}
+
+/// Rescale a staff member's penalties proportionally into `0..=max`, so a
+/// history entered on an inconsistent scale stops dominating the objective.
+/// Rounds to the nearest integer; a staff with no positive penalties is a
+/// no-op.
+pub async fn normalize_preferences(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+    Json(body): Json<NormalizeBody>,
+) -> Result<Json<NormalizeResult>, AppError> {
+    let to_max = body.max.unwrap_or_else(max_penalty);
+    if to_max < 1 {
+        return Err(AppError::BadRequest("`max` must be at least 1".to_string(),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let (from_max,): (i32,) = sqlx::query_as(
+        "SELECT COALESCE(MAX(penalty), 0) FROM preferences WHERE staff_id = $1",
+    )
+    .bind(staff_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    if from_max <= 0 {
+        return Ok(Json(NormalizeResult {
+            updated: 0,
+            from_max: 0,
+            to_max,
+        }));
+    }
+
+    let updated = sqlx::query(
+        "UPDATE preferences
+         SET penalty = ROUND(penalty::numeric * $2 / $3)::int
+         WHERE staff_id = $1",
+    )
+    .bind(staff_id)
+    .bind(to_max)
+    .bind(from_max)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+    tx.commit().await.map_err(internal_error)?;
+    Ok(Json(NormalizeResult {
+        updated,
+        from_max,
+        to_max,
+    }))
+}
@@ -0,0 +1,572 @@
+//! Scenarios: a frozen solver input payload for a unit, deduplicated by hash.
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+
+use super::internal_error;
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Scenario {
+    pub scenario_id: i64,
+    pub unit_id: i64,
+    pub payload: Value,
+    pub input_hash: String,
+    pub status: String,
+    pub source: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateScenarioBody {
+    pub payload: Value,
+    pub source: Option<String>,
+}
+
+/// Canonical SHA-256 over the payload. `serde_json` serializes object keys
+/// in sorted order, so two payloads differing only in key order hash equal.
+pub fn input_hash(payload: &Value) -> String {
+    let canonical = serde_json::to_string(payload).unwrap_or_default();
+    let digest = Sha256::digest(canonical.as_bytes());
+    hex::encode(digest)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HashScenarioBody {
+    pub payload: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HashScenarioResponse {
+    pub input_hash: String,
+}
+
+/// Compute the canonical `input_hash` for a payload without persisting
+/// anything, so clients can detect "nothing changed since last solve".
+pub async fn hash_scenario(Json(body): Json<HashScenarioBody>) -> Json<HashScenarioResponse> {
+    Json(HashScenarioResponse {
+        input_hash: input_hash(&body.payload),
+    })
+}
+
+/// Span in days between the earliest and latest payload day, inclusive.
+/// None when the payload has no parseable days.
+fn horizon_days(payload: &Value) -> Option<i64> {
+    let days: Vec<chrono::NaiveDate> = payload
+        .get("days")?
+        .as_array()?
+        .iter()
+        .filter_map(|d| d.as_str()?.parse().ok())
+        .collect();
+    let first = days.iter().min()?;
+    let last = days.iter().max()?;
+    Some((*last - *first).num_days() + 1)
+}
+
+fn max_horizon_days() -> i64 {
+    std::env::var("MAX_HORIZON_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(90)
+}
+
+/// Where scenarios come from. Input is case-insensitive; rows store the
+/// canonical lowercase form so `scenario_sources` buckets cleanly.
+const SCENARIO_SOURCES: [&str; 3] = ["web", "chatbot", "csv"];
+
+fn normalize_source(value: &Option<String>) -> Result<Option<String>, (StatusCode, String)> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let lower = value.to_ascii_lowercase();
+    if SCENARIO_SOURCES.contains(&lower.as_str()) {
+        return Ok(Some(lower));
+    }
+    Err((
+        StatusCode::UNPROCESSABLE_ENTITY,
+        format!("unknown source '{value}', expected 'web', 'chatbot' or 'csv'"),
+    ))
+}
+
+const SCENARIO_COLUMNS: &str =
+    "scenario_id, unit_id, payload, input_hash, status, source, created_at";
+
+pub async fn create_scenario(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Json(body): Json<CreateScenarioBody>,
+) -> Result<(StatusCode, Json<Scenario>), AppError> {
+    // A payload describing another unit saved under this one would produce
+    // nonsense runs; reject the mismatch outright.
+    if let Some(embedded) = body.payload.get("unit_id").and_then(Value::as_i64) {
+        if embedded != unit_id {
+            return Err(AppError::Validation(format!("payload describes unit {embedded}, but was posted to unit {unit_id}"),
+            ));
+        }
+    }
+    // Refuse pathological horizons that would blow up the solver.
+    if let Some(span) = horizon_days(&body.payload) {
+        let limit = max_horizon_days();
+        if span > limit {
+            return Err(AppError::BadRequest(format!("scenario horizon spans {span} days, exceeding the limit of {limit}"),
+            ));
+        }
+    }
+    let source = normalize_source(&body.source)?;
+    let hash = input_hash(&body.payload);
+    // If an identical payload already exists for the unit, return it instead
+    // of inserting a duplicate.
+    let existing = sqlx::query_as::<_, Scenario>(&format!(
+        "SELECT {SCENARIO_COLUMNS} FROM scenarios WHERE unit_id = $1 AND input_hash = $2"
+    ))
+    .bind(unit_id)
+    .bind(&hash)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    if let Some(scenario) = existing {
+        return Ok((StatusCode::OK, Json(scenario)));
+    }
+    let scenario = sqlx::query_as::<_, Scenario>(&format!(
+        "INSERT INTO scenarios (unit_id, payload, input_hash, source)
+         VALUES ($1, $2, $3, COALESCE($4, 'web'))
+         RETURNING {SCENARIO_COLUMNS}"
+    ))
+    .bind(unit_id)
+    .bind(&body.payload)
+    .bind(&hash)
+    .bind(&source)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    super::events::emit(
+        &state.pool,
+        "scenario.created",
+        Some(unit_id),
+        Some(scenario.scenario_id),
+        &serde_json::json!({ "input_hash": scenario.input_hash, "source": scenario.source }),
+    )
+    .await
+    .map_err(internal_error)?;
+    Ok((StatusCode::CREATED, Json(scenario)))
+}
+
+pub async fn list_scenarios(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+) -> Result<Json<Vec<Scenario>>, AppError> {
+    let scenarios = sqlx::query_as::<_, Scenario>(&format!(
+        "SELECT {SCENARIO_COLUMNS} FROM scenarios WHERE unit_id = $1 ORDER BY scenario_id"
+    ))
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(scenarios))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SourcesQuery {
+    pub from: Option<chrono::NaiveDate>,
+    pub to: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct SourceStat {
+    pub source: String,
+    pub count: i64,
+}
+
+/// How scenarios arrive (`web`/`chatbot`/`csv`): counts per source over an
+/// optional creation-date window.
+pub async fn scenario_sources(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Query(query): Query<SourcesQuery>,
+) -> Result<Json<Vec<SourceStat>>, AppError> {
+    let stats = sqlx::query_as::<_, SourceStat>(
+        "SELECT source, count(*) AS count
+         FROM scenarios
+         WHERE unit_id = $1
+           AND ($2::date IS NULL OR created_at::date >= $2)
+           AND ($3::date IS NULL OR created_at::date <= $3)
+         GROUP BY source ORDER BY count DESC, source",
+    )
+    .bind(unit_id)
+    .bind(query.from)
+    .bind(query.to)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(stats))
+}
+
+pub async fn get_scenario(
+    State(state): State<AppState>,
+    Path(scenario_id): Path<i64>,
+) -> Result<Json<Scenario>, AppError> {
+    let scenario = sqlx::query_as::<_, Scenario>(&format!(
+        "SELECT {SCENARIO_COLUMNS} FROM scenarios WHERE scenario_id = $1"
+    ))
+    .bind(scenario_id)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(scenario))
+}
+
+/// Rough solve-size estimate derived from the stored payload.
+#[derive(Debug, Serialize)]
+pub struct ScenarioComplexity {
+    pub scenario_id: i64,
+    pub staff_count: usize,
+    pub shift_count: usize,
+    pub day_count: usize,
+    pub coverage_cells: usize,
+    /// variables ~= staff x shifts x days
+    pub estimated_variables: usize,
+    pub difficulty: &'static str,
+}
+
+fn difficulty_label(estimated_variables: usize) -> &'static str {
+    match estimated_variables {
+        0..=1_000 => "trivial",
+        1_001..=10_000 => "easy",
+        10_001..=100_000 => "moderate",
+        100_001..=1_000_000 => "hard",
+        _ => "extreme",
+    }
+}
+
+/// Estimate problem size so clients can pick sensible timeouts/workers
+/// before committing to a solve.
+pub async fn scenario_complexity(
+    State(state): State<AppState>,
+    Path(scenario_id): Path<i64>,
+) -> Result<Json<ScenarioComplexity>, AppError> {
+    let (payload,): (Value,) =
+        sqlx::query_as("SELECT payload FROM scenarios WHERE scenario_id = $1")
+            .bind(scenario_id)
+            .fetch_one(&state.pool)
+            .await
+            .map_err(internal_error)?;
+
+    let len_of = |key: &str| payload.get(key).and_then(Value::as_array).map_or(0, Vec::len);
+    let staff_count = len_of("nurses");
+    let shift_count = len_of("shifts");
+    let day_count = len_of("days");
+    let coverage_cells = payload
+        .get("demand")
+        .and_then(Value::as_object)
+        .map_or(0, |days| {
+            days.values()
+                .map(|shifts| shifts.as_object().map_or(0, |m| m.len()))
+                .sum()
+        });
+    let estimated_variables = staff_count * shift_count * day_count;
+
+    Ok(Json(ScenarioComplexity {
+        scenario_id,
+        staff_count,
+        shift_count,
+        day_count,
+        coverage_cells,
+        estimated_variables,
+        difficulty: difficulty_label(estimated_variables),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteScenarioQuery {
+    #[serde(default)]
+    pub force: bool,
+    /// Report what would be deleted without mutating anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteScenarioResult {
+    pub dry_run: bool,
+    pub deleted_runs: u64,
+    pub deleted_assignments: i64,
+    pub deleted_kpi: i64,
+}
+
+/// Delete a scenario. Refuses (409) when succeeded runs reference it unless
+/// `?force=true`, in which case dependent runs/assignments/kpi go with it
+/// in one transaction and the counts are reported. `?dry_run=true` returns
+/// the same counts without deleting anything.
+pub async fn delete_scenario(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    Path(scenario_id): Path<i64>,
+    Query(query): Query<DeleteScenarioQuery>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<DeleteScenarioResult>, AppError> {
+    let (succeeded_runs,): (i64,) = sqlx::query_as(
+        "SELECT count(*) FROM solver_runs WHERE scenario_id = $1 AND status = 'succeeded'",
+    )
+    .bind(scenario_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    // A preview runs the same dependency queries and stops there, so
+    // operators can see the blast radius before re-issuing with force.
+    if query.dry_run {
+        let (runs, assignments, kpi): (i64, i64, i64) = sqlx::query_as(
+            "SELECT (SELECT count(*) FROM solver_runs WHERE scenario_id = $1),
+                    (SELECT count(*) FROM assignments a
+                     JOIN solver_runs r ON r.run_id = a.run_id
+                     WHERE r.scenario_id = $1),
+                    (SELECT count(*) FROM kpi k
+                     JOIN solver_runs r ON r.run_id = k.run_id
+                     WHERE r.scenario_id = $1)",
+        )
+        .bind(scenario_id)
+        .fetch_one(&state.pool)
+        .await
+        .map_err(internal_error)?;
+        return Ok(Json(DeleteScenarioResult {
+            dry_run: true,
+            deleted_runs: runs as u64,
+            deleted_assignments: assignments,
+            deleted_kpi: kpi,
+        }));
+    }
+    if succeeded_runs > 0 && !query.force {
+        return Err(AppError::Conflict(format!(
+                "scenario {scenario_id} has {succeeded_runs} succeeded run(s); \
+                 pass ?force=true to delete them too"
+            ),
+        ));
+    }
+
+    let actor = match super::users::current_user(&state, auth.as_ref(), &headers).await {
+        Ok(user) => Some(user.user_id),
+        Err(_) => None,
+    };
+    let org_id: Option<(i64,)> = sqlx::query_as(
+        "SELECT u.organization_id FROM scenarios s
+         JOIN units u ON u.unit_id = s.unit_id
+         WHERE s.scenario_id = $1",
+    )
+    .bind(scenario_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let (deleted_assignments,): (i64,) = sqlx::query_as(
+        "SELECT count(*) FROM assignments a
+         JOIN solver_runs r ON r.run_id = a.run_id
+         WHERE r.scenario_id = $1",
+    )
+    .bind(scenario_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    let (deleted_kpi,): (i64,) = sqlx::query_as(
+        "SELECT count(*) FROM kpi k
+         JOIN solver_runs r ON r.run_id = k.run_id
+         WHERE r.scenario_id = $1",
+    )
+    .bind(scenario_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    // assignments and kpi cascade off solver_runs
+    let deleted_runs = sqlx::query("DELETE FROM solver_runs WHERE scenario_id = $1")
+        .bind(scenario_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?
+        .rows_affected();
+    sqlx::query("DELETE FROM scenarios WHERE scenario_id = $1")
+        .bind(scenario_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    super::audit::record(
+        &mut *tx,
+        actor,
+        org_id.map(|(id,)| id),
+        "scenario.deleted",
+        "scenario",
+        Some(scenario_id),
+        &serde_json::json!({ "forced": query.force, "deleted_runs": deleted_runs }),
+    )
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(Json(DeleteScenarioResult {
+        dry_run: false,
+        deleted_runs,
+        deleted_assignments,
+        deleted_kpi,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkDeleteQuery {
+    pub unit_id: Option<i64>,
+    pub status: Option<String>,
+    /// Only scenarios created strictly before this day.
+    pub before: Option<NaiveDate>,
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkDeleteResult {
+    pub deleted_scenarios: u64,
+    pub deleted_runs: u64,
+}
+
+/// Bulk-delete every scenario matching the filters in one transaction.
+/// At least one filter is required — an unfiltered DELETE that happens to
+/// wipe the table is exactly the accident this guard exists for. Refuses
+/// (409) when any matching scenario is referenced by runs unless
+/// `?force=true`, in which case the runs (and their cascaded assignments
+/// and KPIs) go too.
+pub async fn bulk_delete_scenarios(
+    State(state): State<AppState>,
+    Query(query): Query<BulkDeleteQuery>,
+) -> Result<Json<BulkDeleteResult>, AppError> {
+    if query.unit_id.is_none() && query.status.is_none() && query.before.is_none() {
+        return Err(AppError::Validation(
+            "provide at least one of `unit_id`, `status` or `before`".to_string(),
+        ));
+    }
+    let mut tx = state.pool.begin().await?;
+    let ids: Vec<i64> = sqlx::query_scalar(
+        "SELECT scenario_id FROM scenarios
+         WHERE ($1::bigint IS NULL OR unit_id = $1)
+           AND ($2::text IS NULL OR status = $2)
+           AND ($3::date IS NULL OR created_at::date < $3)
+         ORDER BY scenario_id
+         FOR UPDATE",
+    )
+    .bind(query.unit_id)
+    .bind(&query.status)
+    .bind(query.before)
+    .fetch_all(&mut *tx)
+    .await?;
+    let (used,): (i64,) =
+        sqlx::query_as("SELECT count(*) FROM solver_runs WHERE scenario_id = ANY($1)")
+            .bind(&ids)
+            .fetch_one(&mut *tx)
+            .await?;
+    if used > 0 && !query.force {
+        return Err(AppError::Conflict(format!(
+            "{used} run(s) reference the matching scenarios; pass ?force=true to delete them too"
+        )));
+    }
+    let deleted_runs = sqlx::query("DELETE FROM solver_runs WHERE scenario_id = ANY($1)")
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    let deleted_scenarios = sqlx::query("DELETE FROM scenarios WHERE scenario_id = ANY($1)")
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+    Ok(Json(BulkDeleteResult {
+        deleted_scenarios,
+        deleted_runs,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct RehashResult {
+    pub rehashed: usize,
+    pub merged: usize,
+}
+
+/// Recompute `input_hash` for every scenario with the current
+/// canonicalization. Scenarios in the same unit that now hash identically
+/// are merged: the newest row survives, its duplicates' runs are repointed
+/// to it, and the old rows are removed.
+pub async fn rehash_scenarios(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<RehashResult>, AppError> {
+    crate::auth::require_role(auth.as_ref(), &["admin"])?;
+    // Attribute the maintenance action when the caller is identified.
+    let actor = match super::users::current_user(&state, auth.as_ref(), &headers).await {
+        Ok(user) => Some(user.user_id),
+        Err(_) => None,
+    };
+    let scenarios: Vec<(i64, i64, Value, String)> = sqlx::query_as(
+        "SELECT scenario_id, unit_id, payload, input_hash FROM scenarios ORDER BY scenario_id",
+    )
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    // Group by the new canonical hash; the last (newest) id in each group
+    // is the keeper.
+    let mut groups: std::collections::BTreeMap<(i64, String), Vec<(i64, String)>> =
+        std::collections::BTreeMap::new();
+    for (scenario_id, unit_id, payload, old_hash) in scenarios {
+        groups
+            .entry((unit_id, input_hash(&payload)))
+            .or_default()
+            .push((scenario_id, old_hash));
+    }
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let mut rehashed = 0;
+    let mut merged = 0;
+    for ((_, new_hash), members) in groups {
+        let (keeper_id, keeper_old_hash) = members.last().expect("group is non-empty").clone();
+        for (duplicate_id, _) in &members[..members.len() - 1] {
+            sqlx::query("UPDATE solver_runs SET scenario_id = $2 WHERE scenario_id = $1")
+                .bind(duplicate_id)
+                .bind(keeper_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal_error)?;
+            sqlx::query("DELETE FROM scenarios WHERE scenario_id = $1")
+                .bind(duplicate_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal_error)?;
+            merged += 1;
+        }
+        if keeper_old_hash != new_hash {
+            sqlx::query("UPDATE scenarios SET input_hash = $2 WHERE scenario_id = $1")
+                .bind(keeper_id)
+                .bind(&new_hash)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal_error)?;
+            rehashed += 1;
+        }
+    }
+    super::audit::record(
+        &mut *tx,
+        actor,
+        None,
+        "scenarios.rehashed",
+        "scenario",
+        None,
+        &serde_json::json!({ "rehashed": rehashed, "merged": merged }),
+    )
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+    Ok(Json(RehashResult { rehashed, merged }))
+}
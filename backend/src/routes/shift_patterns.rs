@@ -0,0 +1,593 @@
+//! Shift patterns (Morning/Evening/Night definitions) per unit.
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::internal_error;
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ShiftPattern {
+    pub shift_id: i64,
+    pub unit_id: i64,
+    pub name: String,
+    /// Stable identifier the solver can reference even after a rename.
+    pub code: String,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub is_night: bool,
+    /// Standby duty: excluded from worked-hours and overtime sums.
+    pub is_on_call: bool,
+    /// Unpaid break, subtracted from worked-hours computations.
+    pub break_minutes: i32,
+    pub display_order: i32,
+    pub created_at: DateTime<Utc>,
+    /// Worked minutes, derived: the overnight-aware span minus the break.
+    pub duration_minutes: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateShiftBody {
+    pub name: String,
+    /// Defaults to a code generated from the name.
+    pub code: Option<String>,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub is_night: Option<bool>,
+    pub is_on_call: Option<bool>,
+    pub break_minutes: Option<i32>,
+}
+
+/// Generate a stable default code from a shift name ("Day Shift" -> "DAY_SHIFT").
+fn generated_code(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchShiftBody {
+    pub name: Option<String>,
+    pub code: Option<String>,
+    pub start_time: Option<NaiveTime>,
+    pub end_time: Option<NaiveTime>,
+    pub is_night: Option<bool>,
+    pub is_on_call: Option<bool>,
+    pub break_minutes: Option<i32>,
+}
+
+/// Minutes a shift spans, wrapping past midnight when the end time is
+/// before the start (overnight shifts). [`SHIFT_COLUMNS`] mirrors this
+/// in SQL for the derived `duration_minutes` column.
+fn shift_span_minutes(start: NaiveTime, end: NaiveTime, is_night: bool) -> i64 {
+    let diff = (end - start).num_minutes();
+    if diff < 0 || (diff == 0 && is_night) {
+        diff + 24 * 60
+    } else {
+        diff
+    }
+}
+
+/// Worked minutes of a shift: the overnight-aware span minus the unpaid
+/// break.
+pub fn shift_duration_minutes(
+    start: NaiveTime,
+    end: NaiveTime,
+    is_night: bool,
+    break_minutes: i32,
+) -> i64 {
+    (shift_span_minutes(start, end, is_night) - i64::from(break_minutes)).max(0)
+}
+
+/// Whether two shifts' time ranges intersect on the 24-hour clock,
+/// accounting for overnight wrap on either side.
+pub(crate) fn shifts_overlap(
+    a_start: NaiveTime,
+    a_end: NaiveTime,
+    a_night: bool,
+    b_start: NaiveTime,
+    b_end: NaiveTime,
+    b_night: bool,
+) -> bool {
+    use chrono::Timelike;
+    let day = 24 * 60;
+    let a0 = i64::from(a_start.num_seconds_from_midnight() / 60);
+    let b0 = i64::from(b_start.num_seconds_from_midnight() / 60);
+    let a1 = a0 + shift_span_minutes(a_start, a_end, a_night);
+    let b1 = b0 + shift_span_minutes(b_start, b_end, b_night);
+    // Compare on the ring by sliding one interval a full day either way.
+    [-day, 0, day]
+        .iter()
+        .any(|shift| a0 < b1 + shift && b0 + shift < a1)
+}
+
+/// Enforce the unit's planning granularity on a shift definition:
+/// hour-based units require whole-hour boundaries, shift-based units
+/// reject overlap with the unit's other shifts. `exclude_shift_id` skips
+/// the shift being patched when checking overlap.
+async fn enforce_granularity(
+    state: &AppState,
+    unit_id: i64,
+    start: NaiveTime,
+    end: NaiveTime,
+    is_night: bool,
+    exclude_shift_id: Option<i64>,
+) -> Result<(), (StatusCode, String)> {
+    let granularity = super::units::planning_granularity(&state.pool, unit_id).await?;
+    match granularity.as_str() {
+        "hour" => {
+            use chrono::Timelike;
+            if start.minute() != 0 || start.second() != 0 || end.minute() != 0 || end.second() != 0
+            {
+                return Err((
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!(
+                        "shift boundaries must fall on whole hours ({start}-{end}); unit {unit_id} plans hour-based"
+                    ),
+                ));
+            }
+        }
+        "shift" => {
+            let others: Vec<(i64, String, NaiveTime, NaiveTime, bool)> = sqlx::query_as(
+                "SELECT shift_id, name, start_time, end_time, is_night
+                 FROM shift_patterns WHERE unit_id = $1 AND shift_id <> $2",
+            )
+            .bind(unit_id)
+            .bind(exclude_shift_id.unwrap_or(0))
+            .fetch_all(&state.pool)
+            .await
+            .map_err(internal_error)?;
+            for (other_id, name, other_start, other_end, other_night) in others {
+                if shifts_overlap(start, end, is_night, other_start, other_end, other_night) {
+                    return Err((
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        format!(
+                            "shift would overlap shift {other_id} '{name}' ({other_start}-{other_end}); unit {unit_id} plans in non-overlapping shifts"
+                        ),
+                    ));
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// A shift's times must describe a non-empty range. Equal times are
+/// ambiguous (empty or a full day?) and rejected; crossing midnight is
+/// fine but must be flagged `is_night` so duration math is unambiguous —
+/// callers auto-set the flag when the body leaves it unspecified.
+fn validate_times(
+    start: NaiveTime,
+    end: NaiveTime,
+    is_night: Option<bool>,
+    errors: &mut super::ValidationErrors,
+) -> bool {
+    if start == end {
+        errors.add("end_time", "must differ from start_time");
+        return false;
+    }
+    if start > end && is_night == Some(false) {
+        errors.add(
+            "is_night",
+            format!("shift {start}-{end} crosses midnight; is_night must be true"),
+        );
+        return false;
+    }
+    true
+}
+
+/// Breaks must fit inside the shift; a break as long as the shift would
+/// zero out every worked-hours figure silently.
+fn validate_break(
+    start: NaiveTime,
+    end: NaiveTime,
+    is_night: bool,
+    break_minutes: i32,
+) -> Result<(), (StatusCode, String)> {
+    if break_minutes < 0 {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "break_minutes must not be negative".to_string(),
+        ));
+    }
+    let span = shift_span_minutes(start, end, is_night);
+    if i64::from(break_minutes) >= span {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("break_minutes ({break_minutes}) must be shorter than the shift ({span} minutes)"),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) const SHIFT_COLUMNS: &str =
+    "shift_id, unit_id, name, code, start_time, end_time, is_night, \
+     is_on_call, break_minutes, display_order, created_at, \
+     GREATEST(EXTRACT(EPOCH FROM (end_time - start_time))::bigint / 60 \
+              + CASE WHEN end_time < start_time OR (end_time = start_time AND is_night) \
+                     THEN 1440 ELSE 0 END \
+              - break_minutes, 0) AS duration_minutes";
+
+/// Map a duplicate name or code within the unit to a 409; everything else
+/// stays a 500. Names are unique case-insensitively because the solver
+/// maps shifts by name.
+fn duplicate_conflict(err: sqlx::Error) -> (StatusCode, String) {
+    if let Some(db_err) = err.as_database_error() {
+        if db_err.is_unique_violation() {
+            let field = if db_err.constraint() == Some("shift_patterns_unit_code_key") {
+                "code"
+            } else {
+                "name"
+            };
+            return (
+                StatusCode::CONFLICT,
+                format!("a shift with this {field} already exists in the unit"),
+            );
+        }
+    }
+    internal_error(err)
+}
+
+pub async fn create_shift(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Json(body): Json<CreateShiftBody>,
+) -> Result<(StatusCode, Json<ShiftPattern>), AppError> {
+    let mut errors = super::ValidationErrors::new();
+    if body.name.trim().is_empty() {
+        errors.add("name", "must not be empty");
+    }
+    // Crossing midnight implies a night shift when the body doesn't say.
+    let is_night = body
+        .is_night
+        .unwrap_or(body.start_time > body.end_time);
+    if validate_times(body.start_time, body.end_time, body.is_night, &mut errors) {
+        if let Err((_, message)) = validate_break(
+            body.start_time,
+            body.end_time,
+            is_night,
+            body.break_minutes.unwrap_or(0),
+        ) {
+            errors.add("break_minutes", message);
+        }
+    }
+    errors.check()?;
+    enforce_granularity(&state, unit_id, body.start_time, body.end_time, is_night, None).await?;
+    let shift = sqlx::query_as::<_, ShiftPattern>(&format!(
+        "INSERT INTO shift_patterns (unit_id, name, code, start_time, end_time, is_night, is_on_call, break_minutes)
+         VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, FALSE), COALESCE($8, 0))
+         RETURNING {SHIFT_COLUMNS}"
+    ))
+    .bind(unit_id)
+    .bind(&body.name)
+    .bind(body.code.clone().unwrap_or_else(|| generated_code(&body.name)))
+    .bind(body.start_time)
+    .bind(body.end_time)
+    .bind(is_night)
+    .bind(body.is_on_call)
+    .bind(body.break_minutes)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(duplicate_conflict)?;
+    Ok((StatusCode::CREATED, Json(shift)))
+}
+
+pub async fn list_shifts_by_unit(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+) -> Result<Json<Vec<ShiftPattern>>, AppError> {
+    let shifts = sqlx::query_as::<_, ShiftPattern>(&format!(
+        "SELECT {SHIFT_COLUMNS} FROM shift_patterns WHERE unit_id = $1
+         ORDER BY display_order, shift_id"
+    ))
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(shifts))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FromTemplateQuery {
+    pub template: String,
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// Built-in shift templates: (name, start, end, is_night).
+fn template_shifts(template: &str) -> Option<&'static [(&'static str, &'static str, &'static str, bool)]> {
+    match template {
+        "three_shift" => Some(&[
+            ("Morning", "07:00:00", "15:00:00", false),
+            ("Evening", "15:00:00", "23:00:00", false),
+            ("Night", "23:00:00", "07:00:00", true),
+        ]),
+        "two_shift" => Some(&[
+            ("Day", "07:00:00", "19:00:00", false),
+            ("Night", "19:00:00", "07:00:00", true),
+        ]),
+        _ => None,
+    }
+}
+
+/// Materialize a named template of shift patterns for a unit. Refuses when
+/// the unit already has shifts unless `?overwrite=true`.
+pub async fn create_from_template(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Query(query): Query<FromTemplateQuery>,
+) -> Result<(StatusCode, Json<Vec<ShiftPattern>>), AppError> {
+    let Some(shifts) = template_shifts(&query.template) else {
+        return Err(AppError::BadRequest(format!(
+                "unknown template '{}', expected 'three_shift' or 'two_shift'",
+                query.template
+            ),
+        ));
+    };
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let (existing,): (i64,) =
+        sqlx::query_as("SELECT count(*) FROM shift_patterns WHERE unit_id = $1")
+            .bind(unit_id)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+    if existing > 0 {
+        if !query.overwrite {
+            return Err(AppError::Conflict(format!(
+                    "unit {unit_id} already has {existing} shift(s); pass ?overwrite=true to replace"
+                ),
+            ));
+        }
+        sqlx::query("DELETE FROM shift_patterns WHERE unit_id = $1")
+            .bind(unit_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    let mut created = Vec::with_capacity(shifts.len());
+    for (position, (name, start, end, is_night)) in shifts.iter().enumerate() {
+        let shift = sqlx::query_as::<_, ShiftPattern>(&format!(
+            "INSERT INTO shift_patterns (unit_id, name, code, start_time, end_time, is_night, display_order)
+             VALUES ($1, $2, $3, $4::time, $5::time, $6, $7)
+             RETURNING {SHIFT_COLUMNS}"
+        ))
+        .bind(unit_id)
+        .bind(name)
+        .bind(generated_code(name))
+        .bind(start)
+        .bind(end)
+        .bind(is_night)
+        .bind(position as i32)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+        created.push(shift);
+    }
+    tx.commit().await.map_err(internal_error)?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReorderShiftsBody {
+    /// Shift ids in the order they should appear.
+    pub shift_ids: Vec<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReorderShiftsResult {
+    pub reordered: usize,
+}
+
+/// Set `display_order` for a unit's shifts from an ordered id list.
+pub async fn reorder_shifts(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Json(body): Json<ReorderShiftsBody>,
+) -> Result<Json<ReorderShiftsResult>, AppError> {
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    for (position, shift_id) in body.shift_ids.iter().enumerate() {
+        let updated = sqlx::query(
+            "UPDATE shift_patterns SET display_order = $3
+             WHERE shift_id = $1 AND unit_id = $2",
+        )
+        .bind(shift_id)
+        .bind(unit_id)
+        .bind(position as i32)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+        if updated.rows_affected() == 0 {
+            return Err(AppError::Validation(format!("shift {shift_id} does not belong to unit {unit_id}"),
+            ));
+        }
+    }
+    tx.commit().await.map_err(internal_error)?;
+    Ok(Json(ReorderShiftsResult {
+        reordered: body.shift_ids.len(),
+    }))
+}
+
+pub async fn get_shift(
+    State(state): State<AppState>,
+    Path(shift_id): Path<i64>,
+) -> Result<Json<ShiftPattern>, AppError> {
+    let shift = sqlx::query_as::<_, ShiftPattern>(&format!(
+        "SELECT {SHIFT_COLUMNS} FROM shift_patterns WHERE shift_id = $1"
+    ))
+    .bind(shift_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(shift))
+}
+
+pub async fn patch_shift(
+    State(state): State<AppState>,
+    Path(shift_id): Path<i64>,
+    Json(body): Json<PatchShiftBody>,
+) -> Result<Json<ShiftPattern>, AppError> {
+    // Validate the break against the times as they will be after the patch,
+    // so shortening a shift cannot leave a break longer than the shift.
+    let current = sqlx::query_as::<_, ShiftPattern>(&format!(
+        "SELECT {SHIFT_COLUMNS} FROM shift_patterns WHERE shift_id = $1"
+    ))
+    .bind(shift_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let start = body.start_time.unwrap_or(current.start_time);
+    let end = body.end_time.unwrap_or(current.end_time);
+    // As on create: a patch that makes the shift cross midnight turns on
+    // `is_night` unless the body explicitly set the flag.
+    let is_night = body
+        .is_night
+        .unwrap_or(if start > end { true } else { current.is_night });
+    let mut errors = super::ValidationErrors::new();
+    validate_times(start, end, body.is_night, &mut errors);
+    errors.check()?;
+    validate_break(
+        start,
+        end,
+        is_night,
+        body.break_minutes.unwrap_or(current.break_minutes),
+    )?;
+    enforce_granularity(&state, current.unit_id, start, end, is_night, Some(shift_id)).await?;
+    let shift = sqlx::query_as::<_, ShiftPattern>(&format!(
+        "UPDATE shift_patterns
+         SET name = COALESCE($2, name),
+             code = COALESCE($3, code),
+             start_time = COALESCE($4, start_time),
+             end_time = COALESCE($5, end_time),
+             is_night = $6,
+             is_on_call = COALESCE($7, is_on_call),
+             break_minutes = COALESCE($8, break_minutes)
+         WHERE shift_id = $1
+         RETURNING {SHIFT_COLUMNS}"
+    ))
+    .bind(shift_id)
+    .bind(&body.name)
+    .bind(&body.code)
+    .bind(body.start_time)
+    .bind(body.end_time)
+    .bind(is_night)
+    .bind(body.is_on_call)
+    .bind(body.break_minutes)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(duplicate_conflict)?;
+    Ok(Json(shift))
+}
+
+/// How widely a shift pattern is referenced, for the edit/delete decision.
+#[derive(Debug, Serialize, FromRow)]
+pub struct ShiftUsage {
+    pub shift_id: i64,
+    pub coverage_cells: i64,
+    pub availability: i64,
+    pub preferences: i64,
+    pub assignments: i64,
+    /// Earliest and latest day any of those rows reference; absent when
+    /// the shift is unused.
+    pub first_used: Option<NaiveDate>,
+    pub last_used: Option<NaiveDate>,
+}
+
+/// Concrete numbers behind the referenced-shift delete guard: counts per
+/// referencing table plus the span of days involved.
+pub async fn shift_usage(
+    State(state): State<AppState>,
+    Path(shift_id): Path<i64>,
+) -> Result<Json<ShiftUsage>, AppError> {
+    let exists: Option<(i64,)> =
+        sqlx::query_as("SELECT shift_id FROM shift_patterns WHERE shift_id = $1")
+            .bind(shift_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    if exists.is_none() {
+        return Err(AppError::Status(
+            StatusCode::NOT_FOUND,
+            format!("shift pattern {shift_id} does not exist"),
+        ));
+    }
+    let usage = sqlx::query_as::<_, ShiftUsage>(
+        "WITH days AS (
+             SELECT day FROM coverage_requirement WHERE shift_id = $1
+             UNION ALL SELECT day FROM availability WHERE shift_id = $1
+             UNION ALL SELECT day FROM preferences WHERE shift_id = $1
+             UNION ALL SELECT day FROM assignments WHERE shift_id = $1
+         )
+         SELECT $1::bigint AS shift_id,
+                (SELECT count(*) FROM coverage_requirement WHERE shift_id = $1) AS coverage_cells,
+                (SELECT count(*) FROM availability WHERE shift_id = $1) AS availability,
+                (SELECT count(*) FROM preferences WHERE shift_id = $1) AS preferences,
+                (SELECT count(*) FROM assignments WHERE shift_id = $1) AS assignments,
+                (SELECT min(day) FROM days) AS first_used,
+                (SELECT max(day) FROM days) AS last_used",
+    )
+    .bind(shift_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(usage))
+}
+
+/// What deleting a shift pattern would take with it.
+#[derive(Debug, Serialize)]
+pub struct ShiftDeletePreview {
+    pub dry_run: bool,
+    pub coverage_cells: i64,
+    pub availability: i64,
+    pub preferences: i64,
+    pub assignments: i64,
+}
+
+pub async fn delete_shift(
+    State(state): State<AppState>,
+    Path(shift_id): Path<i64>,
+    Query(query): Query<super::units::DryRunQuery>,
+) -> Result<axum::response::Response, AppError> {
+    use axum::response::IntoResponse;
+    if query.dry_run {
+        let (coverage_cells, availability, preferences, assignments): (i64, i64, i64, i64) =
+            sqlx::query_as(
+                "SELECT (SELECT count(*) FROM coverage_requirement WHERE shift_id = $1),
+                        (SELECT count(*) FROM availability WHERE shift_id = $1),
+                        (SELECT count(*) FROM preferences WHERE shift_id = $1),
+                        (SELECT count(*) FROM assignments WHERE shift_id = $1)",
+            )
+            .bind(shift_id)
+            .fetch_one(&state.pool)
+            .await
+            .map_err(internal_error)?;
+        return Ok(Json(ShiftDeletePreview {
+            dry_run: true,
+            coverage_cells,
+            availability,
+            preferences,
+            assignments,
+        })
+        .into_response());
+    }
+    sqlx::query("DELETE FROM shift_patterns WHERE shift_id = $1")
+        .bind(shift_id)
+        .execute(&state.pool)
+        .await
+        .map_err(internal_error)?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
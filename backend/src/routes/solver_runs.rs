@@ -0,0 +1,2243 @@
+//! Solver runs: orchestrating a solve against the FastAPI solver and
+//! ingesting its results.
+
+use std::collections::{HashMap, HashSet};
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+
+use super::internal_error;
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct SolverRun {
+    pub run_id: i64,
+    pub scenario_id: i64,
+    pub policy_id: Option<i64>,
+    pub status: String,
+    pub solver_status: Option<String>,
+    pub objective: Option<f64>,
+    pub workers: Option<i32>,
+    /// Queue urgency: higher-priority runs get a solver permit first.
+    pub priority: i32,
+    /// Machine-readable failure class (`solver_error`, `mapping_failed`,
+    /// `infeasible`, `timeout`, `cancelled`); NULL unless the run failed.
+    pub failure_reason: Option<String>,
+    pub failure_detail: Option<String>,
+    /// The merged weights forwarded to the solver (defaults overlaid with
+    /// the scenario's and policy's weights).
+    pub effective_weights: Option<Value>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    /// Set when the requested `workers` value was adjusted to fit the
+    /// allowed range. Only present on the create response; not persisted.
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// A classified pipeline failure, recorded on the run row.
+pub struct RunFailure {
+    pub status: StatusCode,
+    pub reason: Option<&'static str>,
+    pub detail: String,
+}
+
+impl RunFailure {
+    fn new(status: StatusCode, reason: &'static str, detail: String) -> Self {
+        Self {
+            status,
+            reason: Some(reason),
+            detail,
+        }
+    }
+}
+
+impl From<(StatusCode, String)> for RunFailure {
+    fn from((status, detail): (StatusCode, String)) -> Self {
+        Self {
+            status,
+            reason: None,
+            detail,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Assignment {
+    pub assignment_id: i64,
+    pub run_id: i64,
+    pub staff_id: i64,
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    pub source: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateRunBody {
+    pub policy_id: Option<i64>,
+    pub workers: Option<i32>,
+    /// Queue urgency when solver slots are contended; higher goes first,
+    /// FIFO among equals (default 0).
+    pub priority: Option<i32>,
+    /// Ask the solver whether a feasible solution exists without persisting
+    /// assignments or KPIs; the run row is marked `checked`.
+    #[serde(default)]
+    pub check_only: bool,
+    /// Narrow which staff the payload's nurse list may reference.
+    pub staff_filter: Option<StaffFilter>,
+}
+
+/// Who stays in the solve scope. `include` force-includes staff the
+/// `enabled_only` rule would drop (one-off cover by someone on leave);
+/// `exclude` always wins over both.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StaffFilter {
+    #[serde(default = "default_enabled_only")]
+    pub enabled_only: bool,
+    #[serde(default)]
+    pub include: Vec<i64>,
+    #[serde(default)]
+    pub exclude: Vec<i64>,
+}
+
+fn default_enabled_only() -> bool {
+    true
+}
+
+/// What the FastAPI solver returns from `/solve`.
+#[derive(Debug, Deserialize)]
+pub struct SolveResponse {
+    pub status: String,
+    pub objective_value: Option<f64>,
+    #[serde(default)]
+    pub assignments: Vec<SolverAssignment>,
+    #[serde(default)]
+    pub understaffed: Vec<UnderstaffItem>,
+    #[serde(default)]
+    pub nurse_stats: Vec<NurseStats>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SolverAssignment {
+    pub day: String,
+    pub shift: String,
+    pub nurse: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnderstaffItem {
+    pub day: String,
+    pub shift: String,
+    pub missing: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NurseStats {
+    pub nurse: String,
+    pub assigned_shifts: i32,
+    pub overtime: i32,
+    pub nights: i32,
+}
+
+/// Mapped result rows handed to `ingest_result`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestBody {
+    pub assignments: Vec<IngestAssignment>,
+    pub understaffed_cells: i32,
+    #[serde(default)]
+    pub nurse_stats: Vec<NurseStats>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestAssignment {
+    pub staff_id: i64,
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IngestResult {
+    pub inserted: usize,
+}
+
+/// Allowed `assignments.source` values. Input is case-insensitive; rows
+/// store the canonical uppercase form the reports filter on.
+const ASSIGNMENT_SOURCES: [&str; 3] = ["MODEL", "POSTFILL", "MANUAL"];
+
+fn normalize_assignment_source(
+    value: &Option<String>,
+) -> Result<Option<String>, (StatusCode, String)> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let upper = value.to_ascii_uppercase();
+    if ASSIGNMENT_SOURCES.contains(&upper.as_str()) {
+        return Ok(Some(upper));
+    }
+    Err((
+        StatusCode::UNPROCESSABLE_ENTITY,
+        format!("unknown source '{value}', expected 'MODEL', 'POSTFILL' or 'MANUAL'"),
+    ))
+}
+
+const RUN_COLUMNS: &str = "run_id, scenario_id, policy_id, status, solver_status, objective, \
+                           workers, priority, failure_reason, failure_detail, effective_weights, \
+                           started_at, finished_at, created_at";
+
+/// Baseline solver weights used when neither the scenario payload nor the
+/// policy provides a value for a key. Override the whole set with the
+/// `DEFAULT_SOLVER_WEIGHTS` env var (a JSON object).
+pub fn default_weights() -> serde_json::Map<String, Value> {
+    if let Ok(raw) = std::env::var("DEFAULT_SOLVER_WEIGHTS") {
+        if let Ok(Value::Object(map)) = serde_json::from_str(&raw) {
+            return map;
+        }
+    }
+    serde_json::json!({
+        "understaffing": 100,
+        "overtime": 10,
+        "fairness": 5,
+        "night_balance": 3,
+        "preference": 1,
+    })
+    .as_object()
+    .expect("default weights literal is an object")
+    .clone()
+}
+
+/// Upper bound on solver worker threads. Configure with `SOLVER_MAX_WORKERS`;
+/// defaults to the number of CPUs on this host.
+fn max_solver_workers() -> i32 {
+    std::env::var("SOLVER_MAX_WORKERS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n >= 1)
+        .unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get() as i32)
+                .unwrap_or(1)
+        })
+}
+
+/// Overlay `overlay`'s keys onto `base`, returning the merged object.
+pub fn merge_weights(mut base: serde_json::Map<String, Value>, overlay: &Value) -> Value {
+    if let Value::Object(map) = overlay {
+        for (key, value) in map {
+            base.insert(key.clone(), value.clone());
+        }
+    }
+    Value::Object(base)
+}
+
+/// Kick off a synchronous solve for a scenario: build the solver payload,
+/// call the FastAPI `/solve` endpoint, map names back to ids, and ingest
+/// the assignments through our own `/ingest-result` endpoint.
+pub async fn create_run(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    Path(scenario_id): Path<i64>,
+    headers: HeaderMap,
+    Json(body): Json<CreateRunBody>,
+) -> Result<(StatusCode, Json<SolverRun>), AppError> {
+    let (unit_id, org_id, payload): (i64, i64, Value) = sqlx::query_as(
+        "SELECT sc.unit_id, u.organization_id, sc.payload
+         FROM scenarios sc JOIN units u ON u.unit_id = sc.unit_id
+         WHERE sc.scenario_id = $1",
+    )
+    .bind(scenario_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    super::organizations::require_active_org(&state, auth.as_ref(), &headers, org_id).await?;
+
+    // Weights cascade: defaults, then the scenario payload's own weights,
+    // then the policy's. An empty-weights policy thus still yields a
+    // complete set for the solver.
+    let mut solver_payload = payload.clone();
+    let mut effective = merge_weights(default_weights(), &payload["weights"]);
+    if let Some(policy_id) = body.policy_id {
+        let (weights,): (Value,) =
+            sqlx::query_as("SELECT weights FROM policy_sets WHERE policy_id = $1")
+                .bind(policy_id)
+                .fetch_one(&state.pool)
+                .await
+                .map_err(internal_error)?;
+        effective = merge_weights(
+            effective.as_object().cloned().unwrap_or_default(),
+            &weights,
+        );
+    }
+    solver_payload["weights"] = effective.clone();
+
+    if let Some(filter) = &body.staff_filter {
+        apply_staff_filter(&state, unit_id, filter, &mut solver_payload).await?;
+    }
+
+    // Workers are clamped rather than rejected: the solver host is shared,
+    // so an oversized request gets the ceiling plus a warning, not a 4xx.
+    let max_workers = max_solver_workers();
+    let workers = body.workers.unwrap_or(max_workers).clamp(1, max_workers);
+    let warning = body.workers.filter(|&w| w != workers).map(|requested| {
+        format!("workers clamped from {requested} to {workers} (allowed range 1..={max_workers})")
+    });
+    solver_payload["workers"] = Value::from(workers);
+
+    let priority = body.priority.unwrap_or(0);
+    let run = sqlx::query_as::<_, SolverRun>(&format!(
+        "INSERT INTO solver_runs (scenario_id, policy_id, status, workers, priority, effective_weights)
+         VALUES ($1, $2, 'queued', $3, $4, $5)
+         RETURNING {RUN_COLUMNS}"
+    ))
+    .bind(scenario_id)
+    .bind(body.policy_id)
+    .bind(workers)
+    .bind(priority)
+    .bind(&effective)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    track_job(&state, run.run_id, "queued");
+
+    // Park until a solver slot frees; when one does, the queue grants it to
+    // the highest-priority waiter, not the oldest. The permit spans the
+    // whole solve and releases on any exit path below.
+    let _permit = state.solver_queue.acquire(run.run_id, priority).await;
+    let run = sqlx::query_as::<_, SolverRun>(&format!(
+        "UPDATE solver_runs SET status = 'running', started_at = now()
+         WHERE run_id = $1
+         RETURNING {RUN_COLUMNS}"
+    ))
+    .bind(run.run_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    track_job(&state, run.run_id, "running");
+
+    // A feasibility probe stops after the solver answers: no assignment
+    // mapping, no KPI, no webhook — just the verdict on the run row.
+    if body.check_only {
+        return match call_solver(&state, &solver_payload).await {
+            Ok(solved) => {
+                let mut run = sqlx::query_as::<_, SolverRun>(&format!(
+                    "UPDATE solver_runs
+                     SET status = 'checked', solver_status = $2, objective = $3,
+                         finished_at = now()
+                     WHERE run_id = $1
+                     RETURNING {RUN_COLUMNS}"
+                ))
+                .bind(run.run_id)
+                .bind(&solved.status)
+                .bind(solved.objective_value)
+                .fetch_one(&state.pool)
+                .await
+                .map_err(internal_error)?;
+                run.warning = warning;
+                finish_job(&state, run.run_id, "checked");
+                Ok((StatusCode::CREATED, Json(run)))
+            }
+            Err(failure) => {
+                sqlx::query(
+                    "UPDATE solver_runs
+                     SET status = 'failed', failure_reason = $2, failure_detail = $3,
+                         finished_at = now()
+                     WHERE run_id = $1",
+                )
+                .bind(run.run_id)
+                .bind(failure.reason)
+                .bind(&failure.detail)
+                .execute(&state.pool)
+                .await
+                .map_err(internal_error)?;
+                finish_job(&state, run.run_id, "failed");
+                Err(AppError::Status(failure.status, failure.detail))
+            }
+        };
+    }
+
+    match solve_and_ingest(&state, &run, unit_id, &solver_payload).await {
+        Ok((solver_status, objective)) => {
+            // The status change and its outbox event commit together, so the
+            // webhook fires if and only if the run is recorded as succeeded.
+            let mut tx = state.pool.begin().await.map_err(internal_error)?;
+            let mut run = sqlx::query_as::<_, SolverRun>(&format!(
+                "UPDATE solver_runs
+                 SET status = 'succeeded', solver_status = $2, objective = $3, finished_at = now()
+                 WHERE run_id = $1
+                 RETURNING {RUN_COLUMNS}"
+            ))
+            .bind(run.run_id)
+            .bind(&solver_status)
+            .bind(objective)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+            crate::outbox::enqueue(
+                &mut tx,
+                "run.succeeded",
+                &serde_json::json!({
+                    "run_id": run.run_id,
+                    "scenario_id": scenario_id,
+                    "unit_id": unit_id,
+                    "solver_status": solver_status,
+                    "objective": objective,
+                }),
+            )
+            .await
+            .map_err(internal_error)?;
+            crate::routes::events::emit(
+                &mut *tx,
+                "run.succeeded",
+                Some(unit_id),
+                Some(run.run_id),
+                &serde_json::json!({ "solver_status": solver_status, "objective": objective }),
+            )
+            .await
+            .map_err(internal_error)?;
+            tx.commit().await.map_err(internal_error)?;
+            run.warning = warning;
+            finish_job(&state, run.run_id, "succeeded");
+            Ok((StatusCode::CREATED, Json(run)))
+        }
+        Err(failure) => {
+            let mut tx = state.pool.begin().await.map_err(internal_error)?;
+            sqlx::query(
+                "UPDATE solver_runs
+                 SET status = 'failed', failure_reason = $2, failure_detail = $3,
+                     finished_at = now()
+                 WHERE run_id = $1",
+            )
+            .bind(run.run_id)
+            .bind(failure.reason)
+            .bind(&failure.detail)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+            crate::outbox::enqueue(
+                &mut tx,
+                "run.failed",
+                &serde_json::json!({
+                    "run_id": run.run_id,
+                    "scenario_id": scenario_id,
+                    "unit_id": unit_id,
+                    "failure_reason": failure.reason,
+                }),
+            )
+            .await
+            .map_err(internal_error)?;
+            crate::routes::events::emit(
+                &mut *tx,
+                "run.failed",
+                Some(unit_id),
+                Some(run.run_id),
+                &serde_json::json!({ "failure_reason": failure.reason }),
+            )
+            .await
+            .map_err(internal_error)?;
+            tx.commit().await.map_err(internal_error)?;
+            finish_job(&state, run.run_id, "failed");
+            Err(AppError::Status(failure.status, failure.detail))
+        }
+    }
+}
+
+/// Register a run as active in the in-memory job registry.
+fn track_job(state: &AppState, run_id: i64, status: &str) {
+    let now = Utc::now();
+    state.jobs.insert(
+        run_id,
+        crate::db::RunProgress {
+            status: status.to_string(),
+            started_at: now,
+            attempt: 1,
+            last_update: now,
+        },
+    );
+}
+
+/// Mark a run terminal in the registry and drop the entry shortly after,
+/// so dashboards still see the final state of just-finished runs.
+fn finish_job(state: &AppState, run_id: i64, status: &str) {
+    if let Some(mut progress) = state.jobs.get_mut(&run_id) {
+        progress.status = status.to_string();
+        progress.last_update = Utc::now();
+    }
+    let jobs = state.jobs.clone();
+    let ttl = std::env::var("JOB_REGISTRY_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(30);
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(ttl)).await;
+        jobs.remove(&run_id);
+    });
+}
+
+/// An entry from the in-memory registry, keyed back by run id.
+#[derive(Debug, Serialize)]
+pub struct ActiveRun {
+    pub run_id: i64,
+    #[serde(flatten)]
+    pub progress: crate::db::RunProgress,
+}
+
+/// Runs currently waiting for a solver permit, in the order the queue
+/// would grant one, with their priorities.
+pub async fn queued_runs(State(state): State<AppState>) -> Json<Vec<crate::db::QueuedRun>> {
+    Json(state.solver_queue.snapshot())
+}
+
+/// Snapshot of currently active (or just-finished) runs, straight from
+/// memory — no database round trip per poll.
+pub async fn active_runs(State(state): State<AppState>) -> Json<Vec<ActiveRun>> {
+    let mut runs: Vec<ActiveRun> = state
+        .jobs
+        .iter()
+        .map(|entry| ActiveRun {
+            run_id: *entry.key(),
+            progress: entry.value().clone(),
+        })
+        .collect();
+    runs.sort_by_key(|r| r.run_id);
+    Json(runs)
+}
+
+/// Call the FastAPI `/solve` endpoint, classifying transport and decode
+/// failures.
+async fn call_solver(state: &AppState, solver_payload: &Value) -> Result<SolveResponse, RunFailure> {
+    let endpoint = state
+        .solver_url
+        .join("solve")
+        .expect("validated base URL accepts a path");
+    let response = state
+        .http
+        .post(endpoint)
+        .json(solver_payload)
+        .send()
+        .await
+        .map_err(|e| {
+            let reason = if e.is_timeout() { "timeout" } else { "solver_error" };
+            RunFailure::new(StatusCode::BAD_GATEWAY, reason, format!("solver unreachable: {e}"))
+        })?;
+    if !response.status().is_success() {
+        return Err(RunFailure::new(
+            StatusCode::BAD_GATEWAY,
+            "solver_error",
+            format!("solver returned {}", response.status()),
+        ));
+    }
+    response.json().await.map_err(|e| {
+        RunFailure::new(
+            StatusCode::BAD_GATEWAY,
+            "solver_error",
+            format!("bad solver response: {e}"),
+        )
+    })
+}
+
+/// The solve pipeline proper; any error marks the run failed with a
+/// classified reason.
+async fn solve_and_ingest(
+    state: &AppState,
+    run: &SolverRun,
+    unit_id: i64,
+    solver_payload: &Value,
+) -> Result<(String, Option<f64>), RunFailure> {
+    let solved = call_solver(state, solver_payload).await?;
+
+    if solved.status == "INFEASIBLE" {
+        return Err(RunFailure::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "infeasible",
+            "solver reported the scenario infeasible".to_string(),
+        ));
+    }
+
+    // Map solver names back onto our rows. Shift lookup prefers the stable
+    // code (solvers are nudged to emit codes) and falls back to the name.
+    let strategy = ambiguous_name_strategy()?;
+    let (staff_by_code, staff_by_name) = staff_maps(state, unit_id).await?;
+    let (shift_by_code, shift_by_name) = shift_maps(state, unit_id).await?;
+
+    let mut assignments = Vec::with_capacity(solved.assignments.len());
+    for a in &solved.assignments {
+        let staff_id = resolve_staff(&a.nurse, strategy, &staff_by_code, &staff_by_name)?;
+        let shift_id = *shift_by_code
+            .get(&a.shift)
+            .or_else(|| shift_by_name.get(&a.shift.to_lowercase()))
+            .ok_or_else(|| {
+                RunFailure::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "mapping_failed",
+                    format!("solver returned unknown shift '{}'", a.shift),
+                )
+            })?;
+        let day: NaiveDate = a.day.parse().map_err(|_| {
+            RunFailure::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "mapping_failed",
+                format!("solver returned unparseable day '{}'", a.day),
+            )
+        })?;
+        assignments.push(IngestAssignment {
+            staff_id,
+            day,
+            shift_id,
+            source: Some("MODEL".to_string()),
+        });
+    }
+
+    let ingest_body = IngestBody {
+        assignments,
+        understaffed_cells: solved.understaffed.iter().map(|u| u.missing).sum(),
+        nurse_stats: solved.nurse_stats,
+    };
+
+    ingest(&state.pool, run.run_id, &ingest_body, false).await?;
+
+    Ok((solved.status, solved.objective_value))
+}
+
+/// How to resolve a solver nurse reference when several staff in the unit
+/// share that `full_name`. Configured with `AMBIGUOUS_NAME_STRATEGY`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AmbiguousNameStrategy {
+    /// Fail the run with a clear message (the default).
+    Error,
+    /// Accept staff codes in the nurse field; ambiguous plain names still fail.
+    PreferCode,
+    /// Pick the single enabled staff among the candidates, if there is one.
+    PreferEnabled,
+}
+
+fn ambiguous_name_strategy() -> Result<AmbiguousNameStrategy, RunFailure> {
+    match std::env::var("AMBIGUOUS_NAME_STRATEGY").as_deref() {
+        Err(_) | Ok("error") => Ok(AmbiguousNameStrategy::Error),
+        Ok("prefer_code") => Ok(AmbiguousNameStrategy::PreferCode),
+        Ok("prefer_enabled") => Ok(AmbiguousNameStrategy::PreferEnabled),
+        Ok(other) => Err(RunFailure::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "mapping_failed",
+            format!(
+                "unknown AMBIGUOUS_NAME_STRATEGY '{other}', \
+                 expected error, prefer_code or prefer_enabled"
+            ),
+        )),
+    }
+}
+
+fn resolve_staff(
+    nurse: &str,
+    strategy: AmbiguousNameStrategy,
+    by_code: &HashMap<String, i64>,
+    by_name: &HashMap<String, Vec<(i64, bool)>>,
+) -> Result<i64, RunFailure> {
+    // A code is unique by construction, so an exact code match is always safe.
+    if strategy == AmbiguousNameStrategy::PreferCode {
+        if let Some(&id) = by_code.get(nurse) {
+            return Ok(id);
+        }
+    }
+    let candidates = by_name.get(nurse).map(Vec::as_slice).unwrap_or(&[]);
+    match candidates {
+        [] => by_code.get(nurse).copied().ok_or_else(|| {
+            RunFailure::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "mapping_failed",
+                format!("solver returned unknown nurse '{nurse}'"),
+            )
+        }),
+        [(id, _)] => Ok(*id),
+        many => {
+            if strategy == AmbiguousNameStrategy::PreferEnabled {
+                let enabled: Vec<i64> = many
+                    .iter()
+                    .filter(|(_, is_enabled)| *is_enabled)
+                    .map(|(id, _)| *id)
+                    .collect();
+                if let [id] = enabled[..] {
+                    return Ok(id);
+                }
+            }
+            let ids: Vec<String> = many.iter().map(|(id, _)| id.to_string()).collect();
+            Err(RunFailure::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "mapping_failed",
+                format!(
+                    "staff name '{nurse}' is ambiguous (staff ids {}); have the solver \
+                     emit staff codes or set AMBIGUOUS_NAME_STRATEGY",
+                    ids.join(", ")
+                ),
+            ))
+        }
+    }
+}
+
+/// Drop nurse entries the filter rules out from the payload's `nurses`
+/// array. Entries are matched to staff by id, code or full name; entries
+/// that reference nobody are left alone (mapping will flag them later).
+async fn apply_staff_filter(
+    state: &AppState,
+    unit_id: i64,
+    filter: &StaffFilter,
+    solver_payload: &mut Value,
+) -> Result<(), (StatusCode, String)> {
+    let rows: Vec<(i64, String, String, bool)> = sqlx::query_as(
+        "SELECT staff_id, code, full_name, is_enabled FROM staffs WHERE unit_id = $1",
+    )
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let unit_ids: std::collections::HashSet<i64> = rows.iter().map(|(id, ..)| *id).collect();
+    for &id in filter.include.iter().chain(&filter.exclude) {
+        if !unit_ids.contains(&id) {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("staff {id} in staff_filter does not belong to unit {unit_id}"),
+            ));
+        }
+    }
+    if let Some(&id) = filter.include.iter().find(|id| filter.exclude.contains(id)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("staff {id} is in both include and exclude"),
+        ));
+    }
+
+    let allowed: std::collections::HashSet<i64> = rows
+        .iter()
+        .filter(|(id, _, _, is_enabled)| {
+            !filter.exclude.contains(id)
+                && (*is_enabled || !filter.enabled_only || filter.include.contains(id))
+        })
+        .map(|(id, ..)| *id)
+        .collect();
+    // Resolve a payload nurse entry to a staff id, if it references one.
+    let resolve = |entry: &Value| -> Option<i64> {
+        match entry {
+            Value::String(s) => rows
+                .iter()
+                .find(|(_, code, name, _)| code == s || name == s)
+                .map(|(id, ..)| *id),
+            Value::Object(o) => {
+                if let Some(id) = o.get("staff_id").and_then(Value::as_i64) {
+                    return unit_ids.contains(&id).then_some(id);
+                }
+                ["code", "name", "nurse", "full_name"]
+                    .iter()
+                    .filter_map(|key| o.get(*key).and_then(Value::as_str))
+                    .find_map(|s| {
+                        rows.iter()
+                            .find(|(_, code, name, _)| code == s || name == s)
+                            .map(|(id, ..)| *id)
+                    })
+            }
+            _ => None,
+        }
+    };
+    if let Some(nurses) = solver_payload
+        .get_mut("nurses")
+        .and_then(Value::as_array_mut)
+    {
+        nurses.retain(|entry| resolve(entry).is_none_or(|id| allowed.contains(&id)));
+    }
+    Ok(())
+}
+
+/// Lookup maps for solver nurse references: exact code, and full name with
+/// every staff sharing it (plus their enabled flag).
+#[allow(clippy::type_complexity)]
+async fn staff_maps(
+    state: &AppState,
+    unit_id: i64,
+) -> Result<(HashMap<String, i64>, HashMap<String, Vec<(i64, bool)>>), (StatusCode, String)> {
+    let rows: Vec<(String, String, i64, bool)> = sqlx::query_as(
+        "SELECT code, full_name, staff_id, is_enabled FROM staffs
+         WHERE unit_id = $1 ORDER BY staff_id",
+    )
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let by_code = rows.iter().map(|(c, _, id, _)| (c.clone(), *id)).collect();
+    let mut by_name: HashMap<String, Vec<(i64, bool)>> = HashMap::new();
+    for (_, name, id, is_enabled) in rows {
+        by_name.entry(name).or_default().push((id, is_enabled));
+    }
+    Ok((by_code, by_name))
+}
+
+/// Lookup maps for solver shift references: exact code, and lowercased name.
+async fn shift_maps(
+    state: &AppState,
+    unit_id: i64,
+) -> Result<(HashMap<String, i64>, HashMap<String, i64>), (StatusCode, String)> {
+    let rows: Vec<(String, String, i64)> =
+        sqlx::query_as("SELECT code, lower(name), shift_id FROM shift_patterns WHERE unit_id = $1")
+            .bind(unit_id)
+            .fetch_all(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    let by_code = rows.iter().map(|(c, _, id)| (c.clone(), *id)).collect();
+    let by_name = rows.into_iter().map(|(_, n, id)| (n, id)).collect();
+    Ok((by_code, by_name))
+}
+
+/// HTTP wrapper around [`ingest`] kept for external callers; `create_run`
+/// calls [`ingest`] directly instead of looping back over HTTP.
+pub async fn ingest_result(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+    Query(query): Query<IngestQuery>,
+    Json(body): Json<IngestBody>,
+) -> Result<Json<IngestResult>, AppError> {
+    let inserted = ingest(&state.pool, run_id, &body, query.strict).await?;
+    Ok(Json(IngestResult { inserted }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IngestQuery {
+    /// Reject (422) and roll back if any assignment lands on an approved
+    /// time-off day.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+/// Upper bound on assignment rows per ingest; generous for real rosters
+/// but finite, so a runaway solver can't flood the database. Override via
+/// the `MAX_INGEST_ASSIGNMENTS` env var.
+fn max_ingest_assignments() -> usize {
+    std::env::var("MAX_INGEST_ASSIGNMENTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(50_000)
+}
+
+/// Persist mapped assignments and compute the run's KPI row.
+pub async fn ingest(
+    pool: &sqlx::PgPool,
+    run_id: i64,
+    body: &IngestBody,
+    strict: bool,
+) -> Result<usize, (StatusCode, String)> {
+    let limit = max_ingest_assignments();
+    if body.assignments.len() > limit {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "{} assignments exceed the ingest limit of {limit}",
+                body.assignments.len()
+            ),
+        ));
+    }
+    // Validate every source before touching the database, so a bad row
+    // late in the batch cannot leave a half-ingested run behind.
+    let mut sources = Vec::with_capacity(body.assignments.len());
+    for a in &body.assignments {
+        sources.push(normalize_assignment_source(&a.source)?);
+    }
+    let mut tx = pool.begin().await.map_err(internal_error)?;
+    for (a, source) in body.assignments.iter().zip(&sources) {
+        sqlx::query(
+            "INSERT INTO assignments (run_id, staff_id, day, shift_id, source)
+             VALUES ($1, $2, $3, $4, COALESCE($5, 'MODEL'))
+             ON CONFLICT (run_id, staff_id, day, shift_id) DO NOTHING",
+        )
+        .bind(run_id)
+        .bind(a.staff_id)
+        .bind(a.day)
+        .bind(a.shift_id)
+        .bind(source)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+
+    if strict {
+        let violations = super::time_off::violations_for_run(&mut *tx, run_id).await?;
+        if !violations.is_empty() {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "{} assignment(s) land on approved time off (first: staff {} on {})",
+                    violations.len(),
+                    violations[0].staff_id,
+                    violations[0].day
+                ),
+            ));
+        }
+    }
+
+    let total_overtime: i32 = body.nurse_stats.iter().map(|s| s.overtime).sum();
+    // Rough satisfaction score in 0..100 (100 = no penalties/overtime),
+    // derived from solver nurse stats. When the solver sent no stats, fall
+    // back to stored preference penalties against the persisted assignments
+    // so the KPI is always populated.
+    let avg_satisfaction: Option<f64> = if body.nurse_stats.is_empty() {
+        satisfaction_from_preferences(&mut tx, run_id).await?
+    } else {
+        let sum: f64 = body
+            .nurse_stats
+            .iter()
+            .map(|s| (100.0 - 10.0 * s.overtime as f64 - 5.0 * s.nights as f64).max(0.0))
+            .sum();
+        Some(sum / body.nurse_stats.len() as f64)
+    };
+
+    sqlx::query(
+        "INSERT INTO kpi (run_id, total_assignments, understaffed_cells, overtime_hours, avg_satisfaction)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (run_id)
+         DO UPDATE SET total_assignments = EXCLUDED.total_assignments,
+                       understaffed_cells = EXCLUDED.understaffed_cells,
+                       overtime_hours = EXCLUDED.overtime_hours,
+                       avg_satisfaction = EXCLUDED.avg_satisfaction",
+    )
+    .bind(run_id)
+    .bind(body.assignments.len() as i32)
+    .bind(body.understaffed_cells)
+    .bind(total_overtime as f64 * 8.0)
+    .bind(avg_satisfaction)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+
+    Ok(body.assignments.len())
+}
+
+/// Satisfaction fallback when the solver returns no `nurse_stats`: each
+/// staff member starts at 100 and loses their summed preference penalty for
+/// the shifts they were actually assigned, floored at 0. Returns `None` for
+/// runs without assignments.
+async fn satisfaction_from_preferences(
+    tx: &mut sqlx::PgConnection,
+    run_id: i64,
+) -> Result<Option<f64>, (StatusCode, String)> {
+    let penalties: Vec<(i64, i64)> = sqlx::query_as(
+        "SELECT a.staff_id, COALESCE(SUM(p.penalty), 0)
+         FROM assignments a
+         LEFT JOIN preferences p
+           ON p.staff_id = a.staff_id AND p.day = a.day AND p.shift_id = a.shift_id
+         WHERE a.run_id = $1
+         GROUP BY a.staff_id",
+    )
+    .bind(run_id)
+    .fetch_all(tx)
+    .await
+    .map_err(internal_error)?;
+    if penalties.is_empty() {
+        return Ok(None);
+    }
+    let sum: f64 = penalties
+        .iter()
+        .map(|(_, penalty)| (100.0 - *penalty as f64).max(0.0))
+        .sum();
+    Ok(Some(sum / penalties.len() as f64))
+}
+
+/// Lean DTO for list/detail headers: run status plus KPI headline and
+/// counts in one call. KPI fields are null for runs without a KPI row.
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct RunSummary {
+    pub run_id: i64,
+    pub scenario_id: i64,
+    pub status: String,
+    pub solver_status: Option<String>,
+    pub objective: Option<f64>,
+    pub failure_reason: Option<String>,
+    pub assignment_count: i64,
+    pub understaffed_cells: Option<i32>,
+    pub overtime_hours: Option<f64>,
+    pub avg_satisfaction: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+pub async fn run_summary(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+) -> Result<Json<RunSummary>, AppError> {
+    let summary = sqlx::query_as::<_, RunSummary>(
+        "SELECT r.run_id, r.scenario_id, r.status, r.solver_status, r.objective,
+                r.failure_reason,
+                (SELECT count(*) FROM assignments a WHERE a.run_id = r.run_id) AS assignment_count,
+                k.understaffed_cells, k.overtime_hours, k.avg_satisfaction,
+                r.created_at, r.finished_at
+         FROM solver_runs r
+         LEFT JOIN kpi k ON k.run_id = r.run_id
+         WHERE r.run_id = $1",
+    )
+    .bind(run_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(summary))
+}
+
+/// One day of the run's horizon: how many assignments landed versus how
+/// many heads the unit's coverage asked for, summed across shifts.
+#[derive(Debug, Serialize)]
+pub struct DailySummaryRow {
+    pub day: NaiveDate,
+    pub assigned: i64,
+    pub required: i64,
+    /// `max(required - assigned, 0)`; 0 when the day is fully covered.
+    pub shortfall: i64,
+}
+
+/// Per-day staffing rollup for a run. Covers every day of the run's
+/// horizon — the scenario payload's `days` span, or failing that the span
+/// of the run's assignments and coverage rows — so gaps show up as rows
+/// with zero assignments rather than disappearing.
+pub async fn daily_summary(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+) -> Result<Json<Vec<DailySummaryRow>>, AppError> {
+    let (unit_id, payload): (i64, Value) = sqlx::query_as(
+        "SELECT sc.unit_id, sc.payload
+         FROM solver_runs r
+         JOIN scenarios sc ON sc.scenario_id = r.scenario_id
+         WHERE r.run_id = $1",
+    )
+    .bind(run_id)
+    .fetch_one(&state.pool)
+    .await?;
+
+    let assigned: HashMap<NaiveDate, i64> = sqlx::query_as(
+        "SELECT day, count(*) FROM assignments WHERE run_id = $1 GROUP BY day",
+    )
+    .bind(run_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .into_iter()
+    .collect();
+    let required: HashMap<NaiveDate, i64> = sqlx::query_as(
+        "SELECT day, SUM(required_count)::bigint
+         FROM coverage_requirement WHERE unit_id = $1 GROUP BY day",
+    )
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .into_iter()
+    .collect();
+
+    let payload_days: Vec<NaiveDate> = payload
+        .get("days")
+        .and_then(Value::as_array)
+        .map(|days| {
+            days.iter()
+                .filter_map(Value::as_str)
+                .filter_map(|d| d.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    let span = if payload_days.is_empty() {
+        let known = || assigned.keys().chain(required.keys()).copied();
+        known().min().zip(known().max())
+    } else {
+        payload_days
+            .iter()
+            .min()
+            .copied()
+            .zip(payload_days.iter().max().copied())
+    };
+    let Some((first, last)) = span else {
+        return Ok(Json(Vec::new()));
+    };
+
+    let rows = first
+        .iter_days()
+        .take_while(|day| *day <= last)
+        .map(|day| {
+            let assigned = assigned.get(&day).copied().unwrap_or(0);
+            let required = required.get(&day).copied().unwrap_or(0);
+            DailySummaryRow {
+                day,
+                assigned,
+                required,
+                shortfall: (required - assigned).max(0),
+            }
+        })
+        .collect();
+    Ok(Json(rows))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompareRunsBody {
+    pub run_ids: Vec<i64>,
+}
+
+/// One column of the comparison matrix: a run with its KPIs and counts.
+#[derive(Debug, Serialize, FromRow)]
+pub struct RunComparison {
+    pub run_id: i64,
+    pub scenario_id: i64,
+    pub policy_id: Option<i64>,
+    pub status: String,
+    pub objective: Option<f64>,
+    pub assignment_count: i64,
+    pub manual_count: i64,
+    pub total_assignments: Option<i32>,
+    pub understaffed_cells: Option<i32>,
+    pub overtime_hours: Option<f64>,
+    pub avg_satisfaction: Option<f64>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompareResult {
+    pub unit_id: i64,
+    /// One entry per requested run, ordered by `finished_at` (unfinished
+    /// runs last) so columns read oldest to newest.
+    pub runs: Vec<RunComparison>,
+}
+
+/// Side-by-side KPI matrix for a batch of runs, for weighing several
+/// candidate rosters in one table. All runs must belong to the same unit:
+/// cross-unit KPIs are not comparable.
+pub async fn compare_runs(
+    State(state): State<AppState>,
+    Json(body): Json<CompareRunsBody>,
+) -> Result<Json<CompareResult>, AppError> {
+    if body.run_ids.len() < 2 {
+        return Err(AppError::BadRequest("run_ids must contain at least two runs".to_string(),
+        ));
+    }
+    let units: Vec<(i64, i64)> = sqlx::query_as(
+        "SELECT r.run_id, sc.unit_id
+         FROM solver_runs r
+         JOIN scenarios sc ON sc.scenario_id = r.scenario_id
+         WHERE r.run_id = ANY($1)",
+    )
+    .bind(&body.run_ids)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    for run_id in &body.run_ids {
+        if !units.iter().any(|(id, _)| id == run_id) {
+            return Err(AppError::Status(
+            StatusCode::NOT_FOUND,
+            format!("solver run {run_id} does not exist"),
+            ));
+        }
+    }
+    let unit_id = units[0].1;
+    if let Some((run_id, other_unit)) = units.iter().find(|(_, unit)| *unit != unit_id) {
+        return Err(AppError::Validation(format!(
+                "run {run_id} belongs to unit {other_unit}, not {unit_id}; compare runs of one unit"
+            ),
+        ));
+    }
+
+    let runs = sqlx::query_as::<_, RunComparison>(
+        "SELECT r.run_id, r.scenario_id, r.policy_id, r.status, r.objective,
+                (SELECT count(*) FROM assignments a WHERE a.run_id = r.run_id) AS assignment_count,
+                (SELECT count(*) FROM assignments a
+                 WHERE a.run_id = r.run_id AND a.source = 'MANUAL') AS manual_count,
+                k.total_assignments, k.understaffed_cells, k.overtime_hours, k.avg_satisfaction,
+                r.finished_at
+         FROM solver_runs r
+         LEFT JOIN kpi k ON k.run_id = r.run_id
+         WHERE r.run_id = ANY($1)
+         ORDER BY r.finished_at NULLS LAST, r.run_id",
+    )
+    .bind(&body.run_ids)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(CompareResult { unit_id, runs }))
+}
+
+/// The run's full context as one self-contained JSON document: run row,
+/// scenario payload, policy snapshot, assignments with resolved names, KPI
+/// and notes. Meant for archiving or handing to an external auditor, so it
+/// carries everything needed to reconstruct the roster without DB access.
+pub async fn run_bundle(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+) -> Result<Json<Value>, AppError> {
+    let run = sqlx::query_as::<_, SolverRun>(&format!(
+        "SELECT {RUN_COLUMNS} FROM solver_runs WHERE run_id = $1"
+    ))
+    .bind(run_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .ok_or((
+        StatusCode::NOT_FOUND,
+        format!("solver run {run_id} does not exist"),
+    ))?;
+
+    let scenario: Value = sqlx::query_scalar(
+        "SELECT to_jsonb(sc) FROM scenarios sc WHERE sc.scenario_id = $1",
+    )
+    .bind(run.scenario_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let policy: Option<Value> = match run.policy_id {
+        Some(policy_id) => sqlx::query_scalar(
+            "SELECT to_jsonb(p) FROM policy_sets p WHERE p.policy_id = $1",
+        )
+        .bind(policy_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(internal_error)?,
+        None => None,
+    };
+    let kpi: Option<Value> = sqlx::query_scalar("SELECT to_jsonb(k) FROM kpi k WHERE k.run_id = $1")
+        .bind(run_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(internal_error)?;
+
+    #[derive(Serialize, FromRow)]
+    struct BundleAssignment {
+        assignment_id: i64,
+        staff_id: i64,
+        staff_code: String,
+        staff_name: String,
+        day: NaiveDate,
+        shift_id: i64,
+        shift_code: String,
+        shift_name: String,
+        source: String,
+    }
+    let assignments: Vec<BundleAssignment> = sqlx::query_as(
+        "SELECT a.assignment_id, a.staff_id, st.code AS staff_code,
+                st.full_name AS staff_name, a.day, a.shift_id,
+                sp.code AS shift_code, sp.name AS shift_name, a.source
+         FROM assignments a
+         JOIN staffs st ON st.staff_id = a.staff_id
+         JOIN shift_patterns sp ON sp.shift_id = a.shift_id
+         WHERE a.run_id = $1
+         ORDER BY a.day, a.shift_id, a.staff_id",
+    )
+    .bind(run_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let notes: Vec<Value> = sqlx::query_scalar(
+        "SELECT to_jsonb(n) FROM run_notes n
+         WHERE n.solver_run_id = $1 ORDER BY n.note_id",
+    )
+    .bind(run_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    Ok(Json(serde_json::json!({
+        "bundle_version": 1,
+        "generated_at": Utc::now(),
+        "run": run,
+        "scenario": scenario,
+        "policy": policy,
+        "kpi": kpi,
+        "assignments": assignments,
+        "notes": notes,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConsecutiveDaysQuery {
+    /// Override the policy's `hard_rules.max_consecutive_days`.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConsecutiveDayViolation {
+    pub staff_id: i64,
+    pub full_name: String,
+    pub start_day: NaiveDate,
+    pub end_day: NaiveDate,
+    pub length: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConsecutiveDaysReport {
+    pub max_consecutive_days: i64,
+    pub violations: Vec<ConsecutiveDayViolation>,
+}
+
+/// Per staff, find stretches of consecutive days with any assignment that
+/// exceed `max_consecutive_days` — from the query, or the run's policy
+/// `hard_rules`.
+pub async fn consecutive_day_violations(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+    Query(query): Query<ConsecutiveDaysQuery>,
+) -> Result<Json<ConsecutiveDaysReport>, AppError> {
+    let limit = match query.limit {
+        Some(limit) => limit,
+        None => {
+            let rule: Option<(Option<i64>,)> = sqlx::query_as(
+                "SELECT (p.hard_rules->>'max_consecutive_days')::bigint
+                 FROM solver_runs r
+                 JOIN policy_sets p ON p.policy_id = r.policy_id
+                 WHERE r.run_id = $1",
+            )
+            .bind(run_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(internal_error)?;
+            rule.and_then(|(limit,)| limit).ok_or((
+                StatusCode::BAD_REQUEST,
+                "no max_consecutive_days in the run's policy; pass ?limit=".to_string(),
+            ))?
+        }
+    };
+    if limit < 1 {
+        return Err(AppError::BadRequest("max_consecutive_days must be at least 1".to_string(),
+        ));
+    }
+
+    let worked: Vec<(i64, String, NaiveDate)> = sqlx::query_as(
+        "SELECT DISTINCT a.staff_id, st.full_name, a.day
+         FROM assignments a
+         JOIN staffs st ON st.staff_id = a.staff_id
+         WHERE a.run_id = $1
+         ORDER BY a.staff_id, a.day",
+    )
+    .bind(run_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let mut violations = Vec::new();
+    let mut streak: Option<(i64, String, NaiveDate, NaiveDate)> = None;
+    let flush = |streak: &Option<(i64, String, NaiveDate, NaiveDate)>,
+                     violations: &mut Vec<ConsecutiveDayViolation>| {
+        if let Some((staff_id, full_name, start, end)) = streak {
+            let length = (*end - *start).num_days() + 1;
+            if length > limit {
+                violations.push(ConsecutiveDayViolation {
+                    staff_id: *staff_id,
+                    full_name: full_name.clone(),
+                    start_day: *start,
+                    end_day: *end,
+                    length,
+                });
+            }
+        }
+    };
+    for (staff_id, full_name, day) in worked {
+        match &mut streak {
+            Some((id, _, _, end)) if *id == staff_id && (day - *end).num_days() == 1 => {
+                *end = day;
+            }
+            _ => {
+                flush(&streak, &mut violations);
+                streak = Some((staff_id, full_name, day, day));
+            }
+        }
+    }
+    flush(&streak, &mut violations);
+    Ok(Json(ConsecutiveDaysReport {
+        max_consecutive_days: limit,
+        violations,
+    }))
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct RunNote {
+    pub note_id: i64,
+    pub solver_run_id: i64,
+    pub user_id: Option<i64>,
+    pub user_name: Option<String>,
+    pub note: String,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateNoteBody {
+    pub note: String,
+}
+
+/// Attach a note to a run, attributed to the authenticated caller. With
+/// bearer auth enabled the token subject is authoritative; without it the
+/// identity header is consulted, and an anonymous note stays unattributed.
+pub async fn create_run_note(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    Path(run_id): Path<i64>,
+    headers: axum::http::HeaderMap,
+    Json(body): Json<CreateNoteBody>,
+) -> Result<(StatusCode, Json<RunNote>), AppError> {
+    if body.note.trim().is_empty() {
+        return Err(AppError::BadRequest("note must not be empty".to_string()));
+    }
+    let user_id = match &auth {
+        Some(auth) => Some(auth.user_id),
+        None => match super::users::current_user(&state, None, &headers).await {
+            Ok(user) => Some(user.user_id),
+            Err((StatusCode::UNAUTHORIZED, _)) => None,
+            Err(err) => return Err(err.into()),
+        },
+    };
+    let note = sqlx::query_as::<_, RunNote>(
+        "WITH inserted AS (
+             INSERT INTO run_notes (solver_run_id, user_id, note)
+             VALUES ($1, $2, $3)
+             RETURNING note_id, solver_run_id, user_id, note, at
+         )
+         SELECT i.note_id, i.solver_run_id, i.user_id, u.full_name AS user_name, i.note, i.at
+         FROM inserted i LEFT JOIN users u ON u.user_id = i.user_id",
+    )
+    .bind(run_id)
+    .bind(user_id)
+    .bind(body.note.trim())
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok((StatusCode::CREATED, Json(note)))
+}
+
+pub async fn list_run_notes(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+) -> Result<Json<Vec<RunNote>>, AppError> {
+    let notes = sqlx::query_as::<_, RunNote>(
+        "SELECT n.note_id, n.solver_run_id, n.user_id, u.full_name AS user_name, n.note, n.at
+         FROM run_notes n
+         LEFT JOIN users u ON u.user_id = n.user_id
+         WHERE n.solver_run_id = $1
+         ORDER BY n.note_id",
+    )
+    .bind(run_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(notes))
+}
+
+/// A run plus the owning unit's time zone, so clients can render local
+/// times without a second lookup. Timestamps themselves stay UTC (`Z`).
+#[derive(Debug, Serialize)]
+pub struct RunResponse {
+    #[serde(flatten)]
+    pub run: SolverRun,
+    pub time_zone: String,
+    /// The run's creation day in the unit's time zone.
+    pub local_day: NaiveDate,
+}
+
+/// Resolve the time zone for a run's unit: its site's zone, or UTC.
+pub async fn run_time_zone(
+    pool: &sqlx::PgPool,
+    run_id: i64,
+) -> Result<String, (StatusCode, String)> {
+    let (time_zone,): (String,) = sqlx::query_as(
+        "SELECT COALESCE(os.time_zone, 'UTC')
+         FROM solver_runs r
+         JOIN scenarios sc ON sc.scenario_id = r.scenario_id
+         JOIN units u ON u.unit_id = sc.unit_id
+         LEFT JOIN organization_site os ON os.site_id = u.site_id
+         WHERE r.run_id = $1",
+    )
+    .bind(run_id)
+    .fetch_one(pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(time_zone)
+}
+
+/// Convert a UTC instant to the given IANA zone's calendar day,
+/// falling back to the UTC day when the zone is unknown.
+pub fn local_day(at: DateTime<Utc>, time_zone: &str) -> NaiveDate {
+    time_zone
+        .parse::<chrono_tz::Tz>()
+        .map(|tz| at.with_timezone(&tz).date_naive())
+        .unwrap_or_else(|_| at.date_naive())
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffUtilization {
+    pub staff_id: i64,
+    pub full_name: String,
+    pub assigned_hours: f64,
+    pub max_weekly_hours: i32,
+    pub weeks: i64,
+    pub utilization_pct: f64,
+    pub over_contract: bool,
+}
+
+pub(crate) fn default_weekly_hours() -> i32 {
+    std::env::var("DEFAULT_WEEKLY_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(40)
+}
+
+/// Per-staff assigned hours vs contracted weekly hours for a run, flagging
+/// staff scheduled over contract.
+pub async fn run_utilization(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+) -> Result<Json<Vec<StaffUtilization>>, AppError> {
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        staff_id: i64,
+        full_name: String,
+        max_weekly_hours: Option<i32>,
+        day: NaiveDate,
+        start_time: chrono::NaiveTime,
+        end_time: chrono::NaiveTime,
+        is_night: bool,
+        break_minutes: i32,
+    }
+    let rows: Vec<Row> = sqlx::query_as(
+        "SELECT a.staff_id, st.full_name, st.max_weekly_hours, a.day,
+                sp.start_time, sp.end_time, sp.is_night, sp.break_minutes
+         FROM assignments a
+         JOIN staffs st ON st.staff_id = a.staff_id
+         JOIN shift_patterns sp ON sp.shift_id = a.shift_id
+         WHERE a.run_id = $1 AND NOT sp.is_on_call
+         ORDER BY a.staff_id, a.day",
+    )
+    .bind(run_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    use chrono::Datelike;
+    let weeks = rows
+        .iter()
+        .map(|r| (r.day.iso_week().year(), r.day.iso_week().week()))
+        .collect::<std::collections::HashSet<_>>()
+        .len()
+        .max(1) as i64;
+
+    let mut by_staff: std::collections::BTreeMap<i64, StaffUtilization> =
+        std::collections::BTreeMap::new();
+    for row in rows {
+        let hours = crate::routes::shift_patterns::shift_duration_minutes(
+            row.start_time,
+            row.end_time,
+            row.is_night,
+            row.break_minutes,
+        ) as f64
+            / 60.0;
+        let entry = by_staff.entry(row.staff_id).or_insert(StaffUtilization {
+            staff_id: row.staff_id,
+            full_name: row.full_name,
+            assigned_hours: 0.0,
+            max_weekly_hours: row.max_weekly_hours.unwrap_or_else(default_weekly_hours),
+            weeks,
+            utilization_pct: 0.0,
+            over_contract: false,
+        });
+        entry.assigned_hours += hours;
+    }
+    let mut utilization: Vec<StaffUtilization> = by_staff.into_values().collect();
+    for entry in &mut utilization {
+        let capacity = f64::from(entry.max_weekly_hours) * weeks as f64;
+        entry.utilization_pct = if capacity > 0.0 {
+            entry.assigned_hours / capacity * 100.0
+        } else {
+            0.0
+        };
+        entry.over_contract = entry.assigned_hours > capacity;
+    }
+    Ok(Json(utilization))
+}
+
+pub async fn get_run(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+) -> Result<Json<RunResponse>, AppError> {
+    let run = sqlx::query_as::<_, SolverRun>(&format!(
+        "SELECT {RUN_COLUMNS} FROM solver_runs WHERE run_id = $1"
+    ))
+    .bind(run_id)
+    .fetch_one(&state.pool)
+    .await?;
+    let time_zone = run_time_zone(&state.pool, run_id).await?;
+    let local_day = local_day(run.created_at, &time_zone);
+    Ok(Json(RunResponse {
+        run,
+        time_zone,
+        local_day,
+    }))
+}
+
+pub async fn list_runs(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+) -> Result<Json<Vec<SolverRun>>, AppError> {
+    let runs = sqlx::query_as::<_, SolverRun>(
+        "SELECT r.run_id, r.scenario_id, r.policy_id, r.status, r.solver_status, r.objective,
+                r.workers, r.priority, r.failure_reason, r.failure_detail, r.effective_weights,
+                r.started_at, r.finished_at, r.created_at
+         FROM solver_runs r
+         JOIN scenarios s ON s.scenario_id = r.scenario_id
+         WHERE s.unit_id = $1
+         ORDER BY r.run_id DESC",
+    )
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(runs))
+}
+
+/// Assignment rows with the unit's time zone alongside, for local rendering.
+#[derive(Debug, Serialize)]
+pub struct AssignmentsResponse {
+    pub time_zone: String,
+    pub items: Vec<Assignment>,
+}
+
+pub async fn list_assignments(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+) -> Result<Json<AssignmentsResponse>, AppError> {
+    let items = sqlx::query_as::<_, Assignment>(
+        "SELECT assignment_id, run_id, staff_id, day, shift_id, source
+         FROM assignments WHERE run_id = $1 ORDER BY day, shift_id, staff_id",
+    )
+    .bind(run_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let time_zone = run_time_zone(&state.pool, run_id).await?;
+    Ok(Json(AssignmentsResponse { time_zone, items }))
+}
+
+/// An assignment with its staff and shift names resolved for display.
+#[derive(Debug, Serialize, FromRow)]
+pub struct AssignmentDetail {
+    pub assignment_id: i64,
+    pub run_id: i64,
+    pub staff_id: i64,
+    pub full_name: String,
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    pub shift_name: String,
+    pub source: String,
+}
+
+pub async fn get_assignment(
+    State(state): State<AppState>,
+    Path(assignment_id): Path<i64>,
+) -> Result<Json<AssignmentDetail>, AppError> {
+    let assignment = sqlx::query_as::<_, AssignmentDetail>(
+        "SELECT a.assignment_id, a.run_id, a.staff_id, st.full_name,
+                a.day, a.shift_id, sp.name AS shift_name, a.source
+         FROM assignments a
+         JOIN staffs st ON st.staff_id = a.staff_id
+         JOIN shift_patterns sp ON sp.shift_id = a.shift_id
+         WHERE a.assignment_id = $1",
+    )
+    .bind(assignment_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .ok_or((
+        StatusCode::NOT_FOUND,
+        format!("assignment {assignment_id} does not exist"),
+    ))?;
+    Ok(Json(assignment))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateAssignmentBody {
+    pub staff_id: i64,
+    pub day: NaiveDate,
+    pub shift_id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReassignBody {
+    pub staff_id: Option<i64>,
+    pub day: Option<NaiveDate>,
+    pub shift_id: Option<i64>,
+}
+
+/// The acting user for manual edits, when the caller identified themselves.
+async fn edit_actor(
+    state: &AppState,
+    auth: Option<&crate::auth::CurrentUser>,
+    headers: &HeaderMap,
+) -> Option<i64> {
+    match super::users::current_user(state, auth, headers).await {
+        Ok(user) => Some(user.user_id),
+        Err(_) => None,
+    }
+}
+
+/// Manually add an assignment to a run. The row is marked `MANUAL` and the
+/// edit lands in the audit log so it shows up in the run's history.
+pub async fn create_assignment(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    Path(run_id): Path<i64>,
+    headers: HeaderMap,
+    Json(body): Json<CreateAssignmentBody>,
+) -> Result<(StatusCode, Json<Assignment>), AppError> {
+    let actor = edit_actor(&state, auth.as_ref(), &headers).await;
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let assignment = sqlx::query_as::<_, Assignment>(
+        "INSERT INTO assignments (run_id, staff_id, day, shift_id, source)
+         VALUES ($1, $2, $3, $4, 'MANUAL')
+         RETURNING assignment_id, run_id, staff_id, day, shift_id, source",
+    )
+    .bind(run_id)
+    .bind(body.staff_id)
+    .bind(body.day)
+    .bind(body.shift_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    super::audit::record(
+        &mut *tx,
+        actor,
+        None,
+        "assignment.created",
+        "assignment",
+        Some(assignment.assignment_id),
+        &serde_json::json!({
+            "run_id": run_id,
+            "to": { "staff_id": body.staff_id, "day": body.day, "shift_id": body.shift_id },
+        }),
+    )
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+    Ok((StatusCode::CREATED, Json(assignment)))
+}
+
+/// Move an assignment to another staff, day or shift. The row becomes
+/// `MANUAL` (it no longer reflects the solver's choice) and the before/after
+/// cells are audited for the run's history.
+pub async fn reassign_assignment(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    Path(assignment_id): Path<i64>,
+    headers: HeaderMap,
+    Json(body): Json<ReassignBody>,
+) -> Result<Json<Assignment>, AppError> {
+    let actor = edit_actor(&state, auth.as_ref(), &headers).await;
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let before = sqlx::query_as::<_, Assignment>(
+        "SELECT assignment_id, run_id, staff_id, day, shift_id, source
+         FROM assignments WHERE assignment_id = $1 FOR UPDATE",
+    )
+    .bind(assignment_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?
+    .ok_or((
+        StatusCode::NOT_FOUND,
+        format!("assignment {assignment_id} does not exist"),
+    ))?;
+    let after = sqlx::query_as::<_, Assignment>(
+        "UPDATE assignments
+         SET staff_id = COALESCE($2, staff_id),
+             day = COALESCE($3, day),
+             shift_id = COALESCE($4, shift_id),
+             source = 'MANUAL'
+         WHERE assignment_id = $1
+         RETURNING assignment_id, run_id, staff_id, day, shift_id, source",
+    )
+    .bind(assignment_id)
+    .bind(body.staff_id)
+    .bind(body.day)
+    .bind(body.shift_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    super::audit::record(
+        &mut *tx,
+        actor,
+        None,
+        "assignment.moved",
+        "assignment",
+        Some(assignment_id),
+        &serde_json::json!({
+            "run_id": before.run_id,
+            "from": { "staff_id": before.staff_id, "day": before.day, "shift_id": before.shift_id },
+            "to": { "staff_id": after.staff_id, "day": after.day, "shift_id": after.shift_id },
+        }),
+    )
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+    Ok(Json(after))
+}
+
+/// Remove a single assignment, auditing the cell it occupied.
+pub async fn delete_assignment(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    Path(assignment_id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<StatusCode, AppError> {
+    let actor = edit_actor(&state, auth.as_ref(), &headers).await;
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let before = sqlx::query_as::<_, Assignment>(
+        "DELETE FROM assignments WHERE assignment_id = $1
+         RETURNING assignment_id, run_id, staff_id, day, shift_id, source",
+    )
+    .bind(assignment_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?
+    .ok_or((
+        StatusCode::NOT_FOUND,
+        format!("assignment {assignment_id} does not exist"),
+    ))?;
+    super::audit::record(
+        &mut *tx,
+        actor,
+        None,
+        "assignment.deleted",
+        "assignment",
+        Some(assignment_id),
+        &serde_json::json!({
+            "run_id": before.run_id,
+            "from": { "staff_id": before.staff_id, "day": before.day, "shift_id": before.shift_id },
+        }),
+    )
+    .await
+    .map_err(internal_error)?;
+    tx.commit().await.map_err(internal_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UnassignStaffBody {
+    pub staff_id: i64,
+    /// First day to clear; everything on or after it is lifted.
+    pub from_day: NaiveDate,
+}
+
+/// A cell left open by `unassign_staff`, ready for re-staffing.
+#[derive(Debug, Serialize, FromRow)]
+pub struct OpenCell {
+    pub day: NaiveDate,
+    pub shift_id: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnassignResult {
+    pub removed: i64,
+    /// The now-unstaffed (day, shift) cells, ordered by day then shift.
+    pub open_cells: Vec<OpenCell>,
+}
+
+/// Lift all of a staff's assignments in a run on or after `from_day` — the
+/// "nurse resigned mid-cycle" operation. Past days are immutable history and
+/// are refused; the open cells come back so a supervisor can re-staff them
+/// via post-fill or manual edits.
+pub async fn unassign_staff(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    Path(run_id): Path<i64>,
+    headers: HeaderMap,
+    Json(body): Json<UnassignStaffBody>,
+) -> Result<Json<UnassignResult>, AppError> {
+    let time_zone = run_time_zone(&state.pool, run_id).await?;
+    let today = local_day(Utc::now(), &time_zone);
+    if body.from_day < today {
+        return Err(AppError::Validation(format!(
+                "from_day {} is in the past (today is {today} in {time_zone}); past days cannot be unassigned",
+                body.from_day
+            ),
+        ));
+    }
+    let actor = edit_actor(&state, auth.as_ref(), &headers).await;
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let open_cells = sqlx::query_as::<_, OpenCell>(
+        "DELETE FROM assignments
+         WHERE run_id = $1 AND staff_id = $2 AND day >= $3
+         RETURNING day, shift_id",
+    )
+    .bind(run_id)
+    .bind(body.staff_id)
+    .bind(body.from_day)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+    let mut open_cells = open_cells;
+    open_cells.sort_by_key(|c| (c.day, c.shift_id));
+    if !open_cells.is_empty() {
+        super::audit::record(
+            &mut *tx,
+            actor,
+            None,
+            "assignment.staff_unassigned",
+            "assignment",
+            None,
+            &serde_json::json!({
+                "run_id": run_id,
+                "staff_id": body.staff_id,
+                "from_day": body.from_day,
+                "open_cells": open_cells,
+            }),
+        )
+        .await
+        .map_err(internal_error)?;
+    }
+    tx.commit().await.map_err(internal_error)?;
+    Ok(Json(UnassignResult {
+        removed: open_cells.len() as i64,
+        open_cells,
+    }))
+}
+
+/// How postfill picks among eligible staff for an open slot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PostfillStrategy {
+    /// Fewest assignments in the run so far; spreads the work (default).
+    LeastLoaded,
+    /// Longest-serving staff first (earliest `created_at`).
+    Seniority,
+    /// Cycle through the roster in staff-id order, continuing past the
+    /// run's most recently assigned staff.
+    RoundRobin,
+}
+
+impl PostfillStrategy {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "least_loaded" => Some(Self::LeastLoaded),
+            "seniority" => Some(Self::Seniority),
+            "round_robin" => Some(Self::RoundRobin),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::LeastLoaded => "least_loaded",
+            Self::Seniority => "seniority",
+            Self::RoundRobin => "round_robin",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PostfillBody {
+    /// Overrides the active policy's `hard_rules.postfill_strategy`.
+    pub strategy: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PostfillResult {
+    pub strategy: String,
+    pub filled: i64,
+    /// Cells still short after postfill: no eligible staff remained.
+    pub unfilled: Vec<OpenCell>,
+}
+
+/// Greedily staff the run's understaffed coverage cells from the unit's
+/// enabled roster. Staff already working that day, or explicitly marked
+/// unavailable for the cell, are never picked; the strategy only decides
+/// the order among the rest. New rows carry source `POSTFILL` so reports
+/// can tell them from the solver's.
+pub async fn postfill_run(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    Path(run_id): Path<i64>,
+    headers: HeaderMap,
+    Json(body): Json<PostfillBody>,
+) -> Result<Json<PostfillResult>, AppError> {
+    let unit: Option<(i64,)> = sqlx::query_as(
+        "SELECT sc.unit_id FROM solver_runs r
+         JOIN scenarios sc ON sc.scenario_id = r.scenario_id
+         WHERE r.run_id = $1",
+    )
+    .bind(run_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let Some((unit_id,)) = unit else {
+        return Err(AppError::Status(
+            StatusCode::NOT_FOUND,
+            format!("run {run_id} does not exist"),
+        ));
+    };
+
+    // Strategy precedence: request body, then the active policy's
+    // hard_rules, then the least-loaded default.
+    let policy_rules: Option<(Value,)> =
+        sqlx::query_as("SELECT hard_rules FROM policy_sets WHERE unit_id = $1 AND is_active")
+            .bind(unit_id)
+            .fetch_optional(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    let requested = body.strategy.clone().or_else(|| {
+        policy_rules.and_then(|(rules,)| rules["postfill_strategy"].as_str().map(str::to_string))
+    });
+    let strategy = match requested.as_deref() {
+        None => PostfillStrategy::LeastLoaded,
+        Some(value) => PostfillStrategy::parse(value).ok_or_else(|| {
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "unknown postfill strategy '{value}', expected 'least_loaded', 'seniority' or 'round_robin'"
+                ),
+            )
+        })?,
+    };
+
+    #[derive(FromRow)]
+    struct Deficit {
+        day: NaiveDate,
+        shift_id: i64,
+        missing: i64,
+    }
+    let deficits: Vec<Deficit> = sqlx::query_as(
+        "SELECT c.day, c.shift_id,
+                c.required_count - (
+                    SELECT count(*) FROM assignments a
+                    WHERE a.run_id = $1 AND a.day = c.day AND a.shift_id = c.shift_id
+                ) AS missing
+         FROM coverage_requirement c
+         WHERE c.unit_id = $2
+           AND c.required_count > (
+               SELECT count(*) FROM assignments a
+               WHERE a.run_id = $1 AND a.day = c.day AND a.shift_id = c.shift_id
+           )
+         ORDER BY c.day, c.shift_id",
+    )
+    .bind(run_id)
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    #[derive(FromRow)]
+    struct Candidate {
+        staff_id: i64,
+        created_at: DateTime<Utc>,
+    }
+    let candidates: Vec<Candidate> = sqlx::query_as(
+        "SELECT staff_id, created_at FROM staffs
+         WHERE unit_id = $1 AND is_enabled ORDER BY staff_id",
+    )
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+
+    let existing: Vec<(i64, NaiveDate)> =
+        sqlx::query_as("SELECT staff_id, day FROM assignments WHERE run_id = $1")
+            .bind(run_id)
+            .fetch_all(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    let mut load: HashMap<i64, i64> = HashMap::new();
+    let mut busy: HashSet<(i64, NaiveDate)> = HashSet::new();
+    for &(staff_id, day) in &existing {
+        *load.entry(staff_id).or_insert(0) += 1;
+        busy.insert((staff_id, day));
+    }
+    let unavailable: HashSet<(i64, NaiveDate, i64)> = sqlx::query_as(
+        "SELECT av.staff_id, av.day, av.shift_id
+         FROM availability av
+         JOIN staffs s ON s.staff_id = av.staff_id
+         WHERE s.unit_id = $1 AND av.value = 0",
+    )
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .into_iter()
+    .collect();
+
+    // Round robin resumes after whoever got the run's latest assignment.
+    let last_assigned: Option<(i64,)> = sqlx::query_as(
+        "SELECT staff_id FROM assignments WHERE run_id = $1
+         ORDER BY assignment_id DESC LIMIT 1",
+    )
+    .bind(run_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let mut cursor = last_assigned
+        .and_then(|(staff_id,)| candidates.iter().position(|c| c.staff_id == staff_id))
+        .map(|position| position + 1)
+        .unwrap_or(0);
+
+    let mut picks: Vec<(i64, NaiveDate, i64)> = Vec::new();
+    let mut unfilled = Vec::new();
+    for cell in &deficits {
+        let mut short = false;
+        for _ in 0..cell.missing {
+            let eligible = |c: &Candidate| {
+                !busy.contains(&(c.staff_id, cell.day))
+                    && !unavailable.contains(&(c.staff_id, cell.day, cell.shift_id))
+            };
+            let pick = match strategy {
+                PostfillStrategy::LeastLoaded => candidates
+                    .iter()
+                    .filter(|c| eligible(c))
+                    .min_by_key(|c| (load.get(&c.staff_id).copied().unwrap_or(0), c.staff_id)),
+                PostfillStrategy::Seniority => candidates
+                    .iter()
+                    .filter(|c| eligible(c))
+                    .min_by_key(|c| (c.created_at, c.staff_id)),
+                PostfillStrategy::RoundRobin => (0..candidates.len())
+                    .map(|offset| &candidates[(cursor + offset) % candidates.len()])
+                    .find(|c| eligible(c)),
+            };
+            let Some(picked) = pick else {
+                short = true;
+                break;
+            };
+            busy.insert((picked.staff_id, cell.day));
+            *load.entry(picked.staff_id).or_insert(0) += 1;
+            if strategy == PostfillStrategy::RoundRobin {
+                let position = candidates
+                    .iter()
+                    .position(|c| c.staff_id == picked.staff_id)
+                    .unwrap_or(cursor);
+                cursor = position + 1;
+            }
+            picks.push((picked.staff_id, cell.day, cell.shift_id));
+        }
+        if short {
+            unfilled.push(OpenCell {
+                day: cell.day,
+                shift_id: cell.shift_id,
+            });
+        }
+    }
+
+    let actor = edit_actor(&state, auth.as_ref(), &headers).await;
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    for &(staff_id, day, shift_id) in &picks {
+        sqlx::query(
+            "INSERT INTO assignments (run_id, staff_id, day, shift_id, source)
+             VALUES ($1, $2, $3, $4, 'POSTFILL')",
+        )
+        .bind(run_id)
+        .bind(staff_id)
+        .bind(day)
+        .bind(shift_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+    if !picks.is_empty() {
+        super::audit::record(
+            &mut *tx,
+            actor,
+            None,
+            "assignment.postfilled",
+            "solver_run",
+            Some(run_id),
+            &serde_json::json!({
+                "strategy": strategy.as_str(),
+                "filled": picks.len(),
+            }),
+        )
+        .await
+        .map_err(internal_error)?;
+    }
+    tx.commit().await.map_err(internal_error)?;
+    Ok(Json(PostfillResult {
+        strategy: strategy.as_str().to_string(),
+        filled: picks.len() as i64,
+        unfilled,
+    }))
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct AssignmentChange {
+    pub audit_id: i64,
+    /// `assignment.created`, `assignment.moved` or `assignment.deleted`.
+    pub action: String,
+    pub assignment_id: Option<i64>,
+    pub actor_user_id: Option<i64>,
+    pub actor_name: Option<String>,
+    /// The before/after cells as recorded at edit time.
+    pub detail: Value,
+    pub at: DateTime<Utc>,
+}
+
+/// The ordered trail of manual edits made to a run's roster, oldest first.
+pub async fn assignment_history(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+) -> Result<Json<Vec<AssignmentChange>>, AppError> {
+    let changes = sqlx::query_as::<_, AssignmentChange>(
+        "SELECT al.audit_id, al.action, al.entity_id AS assignment_id,
+                al.user_id AS actor_user_id, u.full_name AS actor_name,
+                al.detail, al.at
+         FROM audit_log al
+         LEFT JOIN users u ON u.user_id = al.user_id
+         WHERE al.entity_type = 'assignment'
+           AND (al.detail->>'run_id')::bigint = $1
+         ORDER BY al.audit_id",
+    )
+    .bind(run_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(changes))
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct SourceCount {
+    pub source: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceBreakdown {
+    pub total: i64,
+    pub sources: Vec<SourceCount>,
+    /// Share of assignments that did not come straight from the solver
+    /// (anything other than `MODEL`). `None` when the run has no assignments.
+    pub deviated_pct: Option<f64>,
+}
+
+/// How much of the run's roster is raw solver output versus post-fill and
+/// manual edits.
+pub async fn source_breakdown(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+) -> Result<Json<SourceBreakdown>, AppError> {
+    let sources = sqlx::query_as::<_, SourceCount>(
+        "SELECT source, count(*) AS count
+         FROM assignments WHERE run_id = $1
+         GROUP BY source ORDER BY source",
+    )
+    .bind(run_id)
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let total: i64 = sources.iter().map(|s| s.count).sum();
+    let deviated: i64 = sources
+        .iter()
+        .filter(|s| s.source != "MODEL")
+        .map(|s| s.count)
+        .sum();
+    let deviated_pct = (total > 0).then(|| deviated as f64 * 100.0 / total as f64);
+    Ok(Json(SourceBreakdown {
+        total,
+        sources,
+        deviated_pct,
+    }))
+}
@@ -0,0 +1,518 @@
+//! Staff (nurses) belonging to a unit.
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::internal_error;
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Staff {
+    pub staff_id: i64,
+    pub unit_id: i64,
+    pub code: String,
+    pub full_name: String,
+    pub role: Option<String>,
+    pub skills: Vec<String>,
+    pub max_weekly_hours: Option<i32>,
+    pub is_enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateStaffBody {
+    pub code: String,
+    pub full_name: String,
+    pub role: Option<String>,
+    pub skills: Option<Vec<String>>,
+    pub max_weekly_hours: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatchStaffBody {
+    pub code: Option<String>,
+    pub full_name: Option<String>,
+    pub role: Option<String>,
+    pub skills: Option<Vec<String>>,
+    pub max_weekly_hours: Option<i32>,
+    pub is_enabled: Option<bool>,
+}
+
+const STAFF_COLUMNS: &str =
+    "staff_id, unit_id, code, full_name, role, skills, max_weekly_hours, is_enabled, created_at";
+
+pub async fn create_staff(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Json(body): Json<CreateStaffBody>,
+) -> Result<(StatusCode, Json<Staff>), AppError> {
+    let mut errors = super::ValidationErrors::new();
+    if body.code.trim().is_empty() {
+        errors.add("code", "must not be empty");
+    }
+    if body.full_name.trim().is_empty() {
+        errors.add("full_name", "must not be empty");
+    }
+    if let Some(hours) = body.max_weekly_hours {
+        if hours <= 0 {
+            errors.add("max_weekly_hours", format!("must be positive, got {hours}"));
+        }
+    }
+    errors.check()?;
+    let staff = sqlx::query_as::<_, Staff>(&format!(
+        "INSERT INTO staffs (unit_id, code, full_name, role, skills, max_weekly_hours)
+         VALUES ($1, $2, $3, $4, COALESCE($5, '{{}}'), $6)
+         RETURNING {STAFF_COLUMNS}"
+    ))
+    .bind(unit_id)
+    .bind(&body.code)
+    .bind(&body.full_name)
+    .bind(&body.role)
+    .bind(&body.skills)
+    .bind(body.max_weekly_hours)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok((StatusCode::CREATED, Json(staff)))
+}
+
+pub async fn list_staffs_by_unit(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+) -> Result<Json<Vec<Staff>>, AppError> {
+    let staffs = sqlx::query_as::<_, Staff>(&format!(
+        "SELECT {STAFF_COLUMNS} FROM staffs WHERE unit_id = $1 ORDER BY staff_id"
+    ))
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(staffs))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RosterQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+/// One staff plus how much of the window they marked themselves available
+/// for, so the scheduling UI can paint the roster in a single request.
+#[derive(Debug, Serialize, FromRow)]
+pub struct RosterEntry {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub staff: Staff,
+    /// Distinct (day, shift) cells in `[from, to]` with an availability of 1.
+    pub available_cells: i64,
+}
+
+/// Enabled staff of a unit with their availability counts for the range.
+/// The counts come from one aggregated subquery rather than a query per
+/// staff.
+pub async fn unit_roster(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Query(query): Query<RosterQuery>,
+) -> Result<Json<Vec<RosterEntry>>, AppError> {
+    let roster = sqlx::query_as::<_, RosterEntry>(
+        "SELECT s.staff_id, s.unit_id, s.code, s.full_name, s.role, s.skills,
+                s.max_weekly_hours, s.is_enabled, s.created_at,
+                COALESCE(av.cells, 0) AS available_cells
+         FROM staffs s
+         LEFT JOIN (
+             SELECT staff_id, count(*) AS cells
+             FROM availability
+             WHERE day BETWEEN $2 AND $3 AND value > 0
+             GROUP BY staff_id
+         ) av ON av.staff_id = s.staff_id
+         WHERE s.unit_id = $1 AND s.is_enabled
+         ORDER BY s.staff_id",
+    )
+    .bind(unit_id)
+    .bind(query.from)
+    .bind(query.to)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(roster))
+}
+
+pub async fn get_staff(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+) -> Result<Json<Staff>, AppError> {
+    let staff = sqlx::query_as::<_, Staff>(&format!(
+        "SELECT {STAFF_COLUMNS} FROM staffs WHERE staff_id = $1"
+    ))
+    .bind(staff_id)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(staff))
+}
+
+pub async fn patch_staff(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+    Json(body): Json<PatchStaffBody>,
+) -> Result<Json<Staff>, AppError> {
+    let staff = sqlx::query_as::<_, Staff>(&format!(
+        "UPDATE staffs
+         SET code = COALESCE($2, code),
+             full_name = COALESCE($3, full_name),
+             role = COALESCE($4, role),
+             skills = COALESCE($5, skills),
+             max_weekly_hours = COALESCE($6, max_weekly_hours),
+             is_enabled = COALESCE($7, is_enabled)
+         WHERE staff_id = $1
+         RETURNING {STAFF_COLUMNS}"
+    ))
+    .bind(staff_id)
+    .bind(&body.code)
+    .bind(&body.full_name)
+    .bind(&body.role)
+    .bind(&body.skills)
+    .bind(body.max_weekly_hours)
+    .bind(body.is_enabled)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(staff))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransferBody {
+    pub staff_ids: Vec<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferResult {
+    pub transferred: usize,
+    /// Availability and preference rows dropped during the transfer.
+    pub cleared_inputs: u64,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct SkillExpiry {
+    pub staff_id: i64,
+    pub skill: String,
+    pub expires_on: NaiveDate,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertSkillExpiryBody {
+    pub skill: String,
+    pub expires_on: NaiveDate,
+}
+
+/// Expiry dates on a staff member's skills, soonest first. Skills without
+/// a row never expire.
+pub async fn list_skill_expiries(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+) -> Result<Json<Vec<SkillExpiry>>, AppError> {
+    // Surface a 404 for unknown staff rather than an empty list.
+    sqlx::query("SELECT staff_id FROM staffs WHERE staff_id = $1")
+        .bind(staff_id)
+        .fetch_one(&state.pool)
+        .await?;
+    let expiries = sqlx::query_as::<_, SkillExpiry>(
+        "SELECT staff_id, skill, expires_on FROM staff_skill_expiry
+         WHERE staff_id = $1 ORDER BY expires_on, skill",
+    )
+    .bind(staff_id)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(expiries))
+}
+
+/// Set (or move) the expiry date of one of the staff member's skills. The
+/// skill must be in the staff's `skills` list — an expiry on a skill they
+/// don't hold is a typo, not a record.
+pub async fn upsert_skill_expiry(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+    Json(body): Json<UpsertSkillExpiryBody>,
+) -> Result<Json<SkillExpiry>, AppError> {
+    let (skills,): (Vec<String>,) =
+        sqlx::query_as("SELECT skills FROM staffs WHERE staff_id = $1")
+            .bind(staff_id)
+            .fetch_one(&state.pool)
+            .await?;
+    if !skills.contains(&body.skill) {
+        return Err(AppError::Validation(format!(
+            "staff {staff_id} does not hold skill '{}'",
+            body.skill
+        )));
+    }
+    let expiry = sqlx::query_as::<_, SkillExpiry>(
+        "INSERT INTO staff_skill_expiry (staff_id, skill, expires_on)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (staff_id, skill) DO UPDATE SET expires_on = EXCLUDED.expires_on
+         RETURNING staff_id, skill, expires_on",
+    )
+    .bind(staff_id)
+    .bind(&body.skill)
+    .bind(body.expires_on)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(expiry))
+}
+
+/// Remove a skill's expiry, making it permanent again.
+pub async fn delete_skill_expiry(
+    State(state): State<AppState>,
+    Path((staff_id, skill)): Path<(i64, String)>,
+) -> Result<StatusCode, AppError> {
+    let deleted = sqlx::query("DELETE FROM staff_skill_expiry WHERE staff_id = $1 AND skill = $2")
+        .bind(staff_id)
+        .bind(&skill)
+        .execute(&state.pool)
+        .await?
+        .rows_affected();
+    if deleted == 0 {
+        return Err(AppError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Move staff to another unit in one transaction, keeping their ids and
+/// assignment history. Availability and preferences are cleared rather than
+/// moved: they reference shift patterns of the old unit, which mean nothing
+/// in the new one.
+pub async fn transfer_staffs(
+    State(state): State<AppState>,
+    Path(to_unit_id): Path<i64>,
+    Json(body): Json<TransferBody>,
+) -> Result<Json<TransferResult>, AppError> {
+    if body.staff_ids.is_empty() {
+        return Err(AppError::BadRequest("staff_ids must not be empty".to_string()));
+    }
+    let unit_exists: Option<(i64,)> = sqlx::query_as("SELECT unit_id FROM units WHERE unit_id = $1")
+        .bind(to_unit_id)
+        .fetch_optional(&state.pool)
+        .await
+        .map_err(internal_error)?;
+    if unit_exists.is_none() {
+        return Err(AppError::Status(
+            StatusCode::NOT_FOUND,
+            format!("unit {to_unit_id} does not exist"),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await.map_err(internal_error)?;
+    let mut cleared_inputs = 0u64;
+    for &staff_id in &body.staff_ids {
+        let staff: Option<(String, i64)> =
+            sqlx::query_as("SELECT code, unit_id FROM staffs WHERE staff_id = $1 FOR UPDATE")
+                .bind(staff_id)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(internal_error)?;
+        let Some((code, from_unit_id)) = staff else {
+            return Err(AppError::Status(
+                StatusCode::NOT_FOUND,
+                format!("staff {staff_id} does not exist"),
+            ));
+        };
+        if from_unit_id == to_unit_id {
+            continue;
+        }
+        let collision: Option<(i64,)> = sqlx::query_as(
+            "SELECT staff_id FROM staffs WHERE unit_id = $1 AND code = $2",
+        )
+        .bind(to_unit_id)
+        .bind(&code)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+        if collision.is_some() {
+            return Err(AppError::Conflict(format!("code '{code}' already exists in unit {to_unit_id}"),
+            ));
+        }
+        for table in ["availability", "preferences"] {
+            cleared_inputs += sqlx::query(&format!("DELETE FROM {table} WHERE staff_id = $1"))
+                .bind(staff_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal_error)?
+                .rows_affected();
+        }
+        sqlx::query("UPDATE staffs SET unit_id = $2 WHERE staff_id = $1")
+            .bind(staff_id)
+            .bind(to_unit_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal_error)?;
+    }
+    tx.commit().await.map_err(internal_error)?;
+    Ok(Json(TransferResult {
+        transferred: body.staff_ids.len(),
+        cleared_inputs,
+    }))
+}
+
+pub async fn delete_staff(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    sqlx::query("DELETE FROM staffs WHERE staff_id = $1")
+        .bind(staff_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProfileQuery {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StaffProfile {
+    #[serde(flatten)]
+    pub staff: Staff,
+    /// The run the aggregates were computed from: the unit's most recent
+    /// succeeded run, if any.
+    pub run_id: Option<i64>,
+    pub total_assignments: i64,
+    pub nights_worked: i64,
+    pub assigned_hours: f64,
+    /// Hours over contracted capacity in the window; never negative.
+    pub overtime_hours: f64,
+    /// Share of (day, shift) cells in the window with an availability
+    /// entry, as a percentage.
+    pub availability_completeness_pct: f64,
+}
+
+/// The consolidated read behind the per-nurse detail screen: the staff row
+/// plus stats over a date window, so the UI makes one call instead of five.
+/// Assignment-derived figures come from the unit's latest succeeded run.
+pub async fn staff_profile(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+    Query(query): Query<ProfileQuery>,
+) -> Result<Json<StaffProfile>, AppError> {
+    if query.from > query.to {
+        return Err(AppError::BadRequest("`from` must not be after `to`".to_string(),
+        ));
+    }
+    let staff = sqlx::query_as::<_, Staff>(&format!(
+        "SELECT {STAFF_COLUMNS} FROM staffs WHERE staff_id = $1"
+    ))
+    .bind(staff_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .ok_or_else(|| {
+        AppError::Status(
+            StatusCode::NOT_FOUND,
+            format!("staff {staff_id} does not exist"),
+        )
+    })?;
+
+    let run_id: Option<i64> = sqlx::query_as::<_, (i64,)>(
+        "SELECT r.run_id
+         FROM solver_runs r
+         JOIN scenarios sc ON sc.scenario_id = r.scenario_id
+         WHERE sc.unit_id = $1 AND r.status = 'succeeded'
+         ORDER BY r.run_id DESC
+         LIMIT 1",
+    )
+    .bind(staff.unit_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .map(|(id,)| id);
+
+    let mut total_assignments = 0i64;
+    let mut nights_worked = 0i64;
+    let mut assigned_hours = 0.0f64;
+    let mut weeks: std::collections::HashSet<(i32, u32)> = std::collections::HashSet::new();
+    if let Some(run_id) = run_id {
+        #[derive(sqlx::FromRow)]
+        struct Row {
+            day: NaiveDate,
+            start_time: chrono::NaiveTime,
+            end_time: chrono::NaiveTime,
+            is_night: bool,
+            is_on_call: bool,
+            break_minutes: i32,
+        }
+        let rows: Vec<Row> = sqlx::query_as(
+            "SELECT a.day, sp.start_time, sp.end_time, sp.is_night, sp.is_on_call, sp.break_minutes
+             FROM assignments a
+             JOIN shift_patterns sp ON sp.shift_id = a.shift_id
+             WHERE a.run_id = $1 AND a.staff_id = $2 AND a.day BETWEEN $3 AND $4",
+        )
+        .bind(run_id)
+        .bind(staff_id)
+        .bind(query.from)
+        .bind(query.to)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?;
+        use chrono::Datelike;
+        for row in rows {
+            total_assignments += 1;
+            if row.is_night {
+                nights_worked += 1;
+            }
+            if !row.is_on_call {
+                weeks.insert((row.day.iso_week().year(), row.day.iso_week().week()));
+                assigned_hours += super::shift_patterns::shift_duration_minutes(
+                    row.start_time,
+                    row.end_time,
+                    row.is_night,
+                    row.break_minutes,
+                ) as f64
+                    / 60.0;
+            }
+        }
+    }
+    let weekly_hours = staff.max_weekly_hours.unwrap_or_else(|| {
+        std::env::var("DEFAULT_WEEKLY_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(40)
+    });
+    let capacity = f64::from(weekly_hours) * weeks.len() as f64;
+    let overtime_hours = (assigned_hours - capacity).max(0.0);
+
+    // Completeness: availability rows present vs every (day, shift) cell
+    // the unit's patterns span in the window.
+    let (filled, shifts): (i64, i64) = sqlx::query_as(
+        "SELECT (SELECT count(*) FROM availability
+                 WHERE staff_id = $1 AND day BETWEEN $2 AND $3),
+                (SELECT count(*) FROM shift_patterns WHERE unit_id = $4)",
+    )
+    .bind(staff_id)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(staff.unit_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    let days = (query.to - query.from).num_days() + 1;
+    let cells = days * shifts;
+    let availability_completeness_pct = if cells > 0 {
+        filled as f64 / cells as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    Ok(Json(StaffProfile {
+        staff,
+        run_id,
+        total_assignments,
+        nights_worked,
+        assigned_hours,
+        overtime_hours,
+        availability_completeness_pct,
+    }))
+}
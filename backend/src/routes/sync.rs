@@ -0,0 +1,87 @@
+//! Incremental sync: everything in a unit that changed after a cursor.
+
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Deserialize)]
+pub struct ChangesQuery {
+    /// Return records modified strictly after this instant.
+    pub since: DateTime<Utc>,
+}
+
+/// A row deleted after the cursor; clients drop their local copy.
+#[derive(Debug, Serialize, FromRow)]
+pub struct Tombstone {
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub deleted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangesResponse {
+    /// Pass this back as the next `since` to continue the sync.
+    pub now: DateTime<Utc>,
+    pub staffs: Vec<super::staffs::Staff>,
+    pub shift_patterns: Vec<super::shift_patterns::ShiftPattern>,
+    pub coverage: Vec<super::coverage::CoverageRequirement>,
+    pub tombstones: Vec<Tombstone>,
+}
+
+/// Delta sync for mobile clients: staff, shifts and coverage touched after
+/// `since`, plus tombstones for rows deleted in the meantime.
+pub async fn unit_changes(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Query(query): Query<ChangesQuery>,
+) -> Result<Json<ChangesResponse>, AppError> {
+    let (now,): (DateTime<Utc>,) = sqlx::query_as("SELECT now()")
+        .fetch_one(&state.pool)
+        .await?;
+    let staffs = sqlx::query_as(
+        "SELECT staff_id, unit_id, code, full_name, role, skills, max_weekly_hours, is_enabled,
+                created_at
+         FROM staffs WHERE unit_id = $1 AND updated_at > $2 ORDER BY staff_id",
+    )
+    .bind(unit_id)
+    .bind(query.since)
+    .fetch_all(&state.pool)
+    .await?;
+    let shift_patterns = sqlx::query_as(&format!(
+        "SELECT {} FROM shift_patterns WHERE unit_id = $1 AND updated_at > $2 ORDER BY shift_id",
+        super::shift_patterns::SHIFT_COLUMNS,
+    ))
+    .bind(unit_id)
+    .bind(query.since)
+    .fetch_all(&state.pool)
+    .await?;
+    let coverage = sqlx::query_as(
+        "SELECT coverage_id, unit_id, day, shift_id, required_count, required_skill
+         FROM coverage_requirement WHERE unit_id = $1 AND updated_at > $2
+         ORDER BY coverage_id",
+    )
+    .bind(unit_id)
+    .bind(query.since)
+    .fetch_all(&state.pool)
+    .await?;
+    let tombstones = sqlx::query_as(
+        "SELECT entity_type, entity_id, deleted_at
+         FROM tombstones WHERE unit_id = $1 AND deleted_at > $2 ORDER BY tombstone_id",
+    )
+    .bind(unit_id)
+    .bind(query.since)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(ChangesResponse {
+        now,
+        staffs,
+        shift_patterns,
+        coverage,
+        tombstones,
+    }))
+}
@@ -0,0 +1,128 @@
+//! Approved time-off ranges and the checks that keep assignments out of
+//! them.
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::internal_error;
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct TimeOff {
+    pub time_off_id: i64,
+    pub staff_id: i64,
+    pub start_day: NaiveDate,
+    pub end_day: NaiveDate,
+    pub status: String,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTimeOffBody {
+    pub start_day: NaiveDate,
+    pub end_day: NaiveDate,
+    /// Defaults to `approved`; use `requested` for pending requests.
+    pub status: Option<String>,
+    pub reason: Option<String>,
+}
+
+const TIME_OFF_COLUMNS: &str =
+    "time_off_id, staff_id, start_day, end_day, status, reason, created_at";
+
+pub async fn create_time_off(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+    Json(body): Json<CreateTimeOffBody>,
+) -> Result<(StatusCode, Json<TimeOff>), AppError> {
+    if body.start_day > body.end_day {
+        return Err(AppError::BadRequest(
+            "`start_day` must not be after `end_day`".to_string(),
+        ));
+    }
+    let time_off = sqlx::query_as::<_, TimeOff>(&format!(
+        "INSERT INTO time_off (staff_id, start_day, end_day, status, reason)
+         VALUES ($1, $2, $3, COALESCE($4, 'approved'), $5)
+         RETURNING {TIME_OFF_COLUMNS}"
+    ))
+    .bind(staff_id)
+    .bind(body.start_day)
+    .bind(body.end_day)
+    .bind(&body.status)
+    .bind(&body.reason)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok((StatusCode::CREATED, Json(time_off)))
+}
+
+pub async fn list_time_off(
+    State(state): State<AppState>,
+    Path(staff_id): Path<i64>,
+) -> Result<Json<Vec<TimeOff>>, AppError> {
+    let rows = sqlx::query_as::<_, TimeOff>(&format!(
+        "SELECT {TIME_OFF_COLUMNS} FROM time_off WHERE staff_id = $1 ORDER BY start_day"
+    ))
+    .bind(staff_id)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(rows))
+}
+
+pub async fn delete_time_off(
+    State(state): State<AppState>,
+    Path(time_off_id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    sqlx::query("DELETE FROM time_off WHERE time_off_id = $1")
+        .bind(time_off_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct TimeOffViolation {
+    pub assignment_id: i64,
+    pub staff_id: i64,
+    pub full_name: String,
+    pub day: NaiveDate,
+    pub shift_id: i64,
+    pub time_off_id: i64,
+}
+
+/// Assignments in a run that land inside an approved time-off range.
+pub async fn run_timeoff_violations(
+    State(state): State<AppState>,
+    Path(run_id): Path<i64>,
+) -> Result<Json<Vec<TimeOffViolation>>, AppError> {
+    let violations = violations_for_run(&state.pool, run_id).await?;
+    Ok(Json(violations))
+}
+
+/// Shared query behind the endpoint and the strict ingest check.
+pub async fn violations_for_run<'e, E>(
+    executor: E,
+    run_id: i64,
+) -> Result<Vec<TimeOffViolation>, (StatusCode, String)>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as::<_, TimeOffViolation>(
+        "SELECT a.assignment_id, a.staff_id, st.full_name, a.day, a.shift_id, t.time_off_id
+         FROM assignments a
+         JOIN staffs st ON st.staff_id = a.staff_id
+         JOIN time_off t ON t.staff_id = a.staff_id
+                        AND a.day BETWEEN t.start_day AND t.end_day
+                        AND t.status = 'approved'
+         WHERE a.run_id = $1
+         ORDER BY a.day, a.staff_id",
+    )
+    .bind(run_id)
+    .fetch_all(executor)
+    .await
+    .map_err(internal_error)
+}
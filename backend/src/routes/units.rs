@@ -0,0 +1,389 @@
+//! Units (wards) within an organization.
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::internal_error;
+use crate::db::AppState;
+use crate::error::AppError;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Unit {
+    pub unit_id: i64,
+    pub organization_id: i64,
+    pub site_id: Option<i64>,
+    pub name: String,
+    /// How strictly this unit's shift/coverage setup is validated:
+    /// `none`, `hour` (shift boundaries on whole hours) or `shift`
+    /// (shift definitions must not overlap).
+    pub planning_granularity: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Unit row with its site joined in, for `?expand=site`.
+#[derive(Debug, Serialize, FromRow)]
+pub struct UnitWithSite {
+    pub unit_id: i64,
+    pub organization_id: i64,
+    pub site_id: Option<i64>,
+    pub name: String,
+    pub planning_granularity: String,
+    pub created_at: DateTime<Utc>,
+    pub site_name: Option<String>,
+    pub site_time_zone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateUnitBody {
+    pub name: String,
+    pub site_id: Option<i64>,
+    pub planning_granularity: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatchUnitBody {
+    pub name: Option<String>,
+    pub site_id: Option<i64>,
+    pub planning_granularity: Option<String>,
+}
+
+/// 422 unless the value is one of the known granularities.
+fn validate_granularity(value: &Option<String>) -> Result<(), (StatusCode, String)> {
+    match value.as_deref() {
+        None | Some("none") | Some("hour") | Some("shift") => Ok(()),
+        Some(other) => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("unknown planning_granularity '{other}', expected 'none', 'hour' or 'shift'"),
+        )),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListUnitsQuery {
+    /// `expand=site` joins the site name and time zone into each row.
+    pub expand: Option<String>,
+}
+
+/// Either the plain rows or the site-expanded rows, depending on `expand`.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ListUnitsResponse {
+    Plain(Vec<Unit>),
+    WithSite(Vec<UnitWithSite>),
+}
+
+pub async fn create_unit(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    Path(org_id): Path<i64>,
+    headers: axum::http::HeaderMap,
+    Json(body): Json<CreateUnitBody>,
+) -> Result<(StatusCode, Json<Unit>), AppError> {
+    super::organizations::require_active_org(&state, auth.as_ref(), &headers, org_id).await?;
+    let mut errors = super::ValidationErrors::new();
+    if body.name.trim().is_empty() {
+        errors.add("name", "must not be empty");
+    }
+    if let Err((_, message)) = validate_granularity(&body.planning_granularity) {
+        errors.add("planning_granularity", message);
+    }
+    errors.check()?;
+    // A unit's site must belong to the same organization as the unit.
+    if let Some(site_id) = body.site_id {
+        let site_org: Option<(i64,)> =
+            sqlx::query_as("SELECT organization_id FROM organization_site WHERE site_id = $1")
+                .bind(site_id)
+                .fetch_optional(&state.pool)
+                .await?;
+        match site_org {
+            None => {
+                return Err(AppError::Validation(format!(
+                    "site {site_id} does not exist"
+                )))
+            }
+            Some((site_org_id,)) if site_org_id != org_id => {
+                return Err(AppError::Validation(format!(
+                    "site {site_id} belongs to organization {site_org_id}, not {org_id}"
+                )))
+            }
+            Some(_) => {}
+        }
+    }
+    let unit = sqlx::query_as::<_, Unit>(
+        "INSERT INTO units (organization_id, site_id, name, planning_granularity)
+         VALUES ($1, $2, $3, COALESCE($4, 'none'))
+         RETURNING unit_id, organization_id, site_id, name, planning_granularity, created_at",
+    )
+    .bind(org_id)
+    .bind(body.site_id)
+    .bind(&body.name)
+    .bind(&body.planning_granularity)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok((StatusCode::CREATED, Json(unit)))
+}
+
+pub async fn list_units(
+    State(state): State<AppState>,
+    Path(org_id): Path<i64>,
+    Query(query): Query<ListUnitsQuery>,
+) -> Result<Json<ListUnitsResponse>, AppError> {
+    if query.expand.as_deref() == Some("site") {
+        // LEFT JOIN so units without a site keep null site fields.
+        let units = sqlx::query_as::<_, UnitWithSite>(
+            "SELECT u.unit_id, u.organization_id, u.site_id, u.name, u.planning_granularity,
+                    u.created_at, s.name AS site_name, s.time_zone AS site_time_zone
+             FROM units u
+             LEFT JOIN organization_site s ON s.site_id = u.site_id
+             WHERE u.organization_id = $1
+             ORDER BY u.unit_id",
+        )
+        .bind(org_id)
+        .fetch_all(&state.pool)
+        .await?;
+        return Ok(Json(ListUnitsResponse::WithSite(units)));
+    }
+    let units = sqlx::query_as::<_, Unit>(
+        "SELECT unit_id, organization_id, site_id, name, planning_granularity, created_at
+         FROM units WHERE organization_id = $1 ORDER BY unit_id",
+    )
+    .bind(org_id)
+    .fetch_all(&state.pool)
+    .await?;
+    Ok(Json(ListUnitsResponse::Plain(units)))
+}
+
+/// The unit's configured validation strictness (`none`, `hour` or `shift`).
+pub async fn planning_granularity(
+    pool: &sqlx::PgPool,
+    unit_id: i64,
+) -> Result<String, (StatusCode, String)> {
+    let granularity: Option<(String,)> =
+        sqlx::query_as("SELECT planning_granularity FROM units WHERE unit_id = $1")
+            .bind(unit_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(internal_error)?;
+    granularity.map(|(g,)| g).ok_or((
+        StatusCode::NOT_FOUND,
+        format!("unit {unit_id} does not exist"),
+    ))
+}
+
+/// Resolve the time zone for a unit: its site's zone, or UTC.
+pub async fn unit_time_zone(
+    pool: &sqlx::PgPool,
+    unit_id: i64,
+) -> Result<String, (StatusCode, String)> {
+    let (time_zone,): (String,) = sqlx::query_as(
+        "SELECT COALESCE(os.time_zone, 'UTC')
+         FROM units u
+         LEFT JOIN organization_site os ON os.site_id = u.site_id
+         WHERE u.unit_id = $1",
+    )
+    .bind(unit_id)
+    .fetch_one(pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(time_zone)
+}
+
+pub async fn get_unit(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+) -> Result<Json<Unit>, AppError> {
+    let unit = sqlx::query_as::<_, Unit>(
+        "SELECT unit_id, organization_id, site_id, name, planning_granularity, created_at
+         FROM units WHERE unit_id = $1",
+    )
+    .bind(unit_id)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(unit))
+}
+
+pub async fn patch_unit(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+    Json(body): Json<PatchUnitBody>,
+) -> Result<Json<Unit>, AppError> {
+    validate_granularity(&body.planning_granularity)?;
+    let unit = sqlx::query_as::<_, Unit>(
+        "UPDATE units
+         SET name = COALESCE($2, name), site_id = COALESCE($3, site_id),
+             planning_granularity = COALESCE($4, planning_granularity)
+         WHERE unit_id = $1
+         RETURNING unit_id, organization_id, site_id, name, planning_granularity, created_at",
+    )
+    .bind(unit_id)
+    .bind(&body.name)
+    .bind(body.site_id)
+    .bind(&body.planning_granularity)
+    .fetch_one(&state.pool)
+    .await?;
+    Ok(Json(unit))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigIssue {
+    /// `shift_misaligned`, `shift_overlap` or `coverage_foreign_shift`.
+    pub kind: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigIssuesReport {
+    pub planning_granularity: String,
+    pub issues: Vec<ConfigIssue>,
+}
+
+/// Audit the unit's shift and coverage setup against its planning
+/// granularity, listing definitions that look like data-entry mistakes
+/// (misaligned or overlapping shifts, coverage pointing at another unit's
+/// shift) before they reach the solver.
+pub async fn config_issues(
+    State(state): State<AppState>,
+    Path(unit_id): Path<i64>,
+) -> Result<Json<ConfigIssuesReport>, AppError> {
+    let granularity = planning_granularity(&state.pool, unit_id).await?;
+
+    #[derive(sqlx::FromRow)]
+    struct ShiftRow {
+        shift_id: i64,
+        name: String,
+        start_time: chrono::NaiveTime,
+        end_time: chrono::NaiveTime,
+        is_night: bool,
+    }
+    let shifts: Vec<ShiftRow> = sqlx::query_as(
+        "SELECT shift_id, name, start_time, end_time, is_night
+         FROM shift_patterns WHERE unit_id = $1 ORDER BY shift_id",
+    )
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await?;
+
+    let mut issues = Vec::new();
+    if granularity == "hour" {
+        use chrono::Timelike;
+        for shift in &shifts {
+            if shift.start_time.minute() != 0
+                || shift.start_time.second() != 0
+                || shift.end_time.minute() != 0
+                || shift.end_time.second() != 0
+            {
+                issues.push(ConfigIssue {
+                    kind: "shift_misaligned".to_string(),
+                    message: format!(
+                        "shift {} '{}' ({}-{}) does not start and end on a whole hour",
+                        shift.shift_id, shift.name, shift.start_time, shift.end_time
+                    ),
+                });
+            }
+        }
+    }
+    if granularity == "shift" {
+        for (i, a) in shifts.iter().enumerate() {
+            for b in &shifts[i + 1..] {
+                if super::shift_patterns::shifts_overlap(
+                    a.start_time, a.end_time, a.is_night,
+                    b.start_time, b.end_time, b.is_night,
+                ) {
+                    issues.push(ConfigIssue {
+                        kind: "shift_overlap".to_string(),
+                        message: format!(
+                            "shifts {} '{}' and {} '{}' overlap",
+                            a.shift_id, a.name, b.shift_id, b.name
+                        ),
+                    });
+                }
+            }
+        }
+    }
+    let foreign: Vec<(i64, i64)> = sqlx::query_as(
+        "SELECT c.shift_id, count(*)
+         FROM coverage_requirement c
+         JOIN shift_patterns sp ON sp.shift_id = c.shift_id
+         WHERE c.unit_id = $1 AND sp.unit_id <> $1
+         GROUP BY c.shift_id ORDER BY c.shift_id",
+    )
+    .bind(unit_id)
+    .fetch_all(&state.pool)
+    .await?;
+    for (shift_id, cells) in foreign {
+        issues.push(ConfigIssue {
+            kind: "coverage_foreign_shift".to_string(),
+            message: format!(
+                "{cells} coverage cell(s) reference shift {shift_id}, which belongs to another unit"
+            ),
+        });
+    }
+    Ok(Json(ConfigIssuesReport {
+        planning_granularity: granularity,
+        issues,
+    }))
+}
+
+/// Shared `?dry_run=true` option for the destructive cascade deletes:
+/// preview what would be removed without mutating anything.
+#[derive(Debug, Deserialize)]
+pub struct DryRunQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// What deleting a unit would take with it.
+#[derive(Debug, Serialize)]
+pub struct UnitDeletePreview {
+    pub dry_run: bool,
+    pub staffs: i64,
+    pub shift_patterns: i64,
+    pub coverage_cells: i64,
+    pub scenarios: i64,
+    pub runs: i64,
+}
+
+pub async fn delete_unit(
+    State(state): State<AppState>,
+    user: Option<crate::auth::CurrentUser>,
+    Path(unit_id): Path<i64>,
+    Query(query): Query<DryRunQuery>,
+) -> Result<axum::response::Response, AppError> {
+    use axum::response::IntoResponse;
+    crate::auth::require_role(user.as_ref(), &["admin"])?;
+    if query.dry_run {
+        let (staffs, shift_patterns, coverage_cells, scenarios, runs): (i64, i64, i64, i64, i64) =
+            sqlx::query_as(
+                "SELECT (SELECT count(*) FROM staffs WHERE unit_id = $1),
+                        (SELECT count(*) FROM shift_patterns WHERE unit_id = $1),
+                        (SELECT count(*) FROM coverage_requirement WHERE unit_id = $1),
+                        (SELECT count(*) FROM scenarios WHERE unit_id = $1),
+                        (SELECT count(*) FROM solver_runs r
+                         JOIN scenarios s ON s.scenario_id = r.scenario_id
+                         WHERE s.unit_id = $1)",
+            )
+            .bind(unit_id)
+            .fetch_one(&state.pool)
+            .await?;
+        return Ok(Json(UnitDeletePreview {
+            dry_run: true,
+            staffs,
+            shift_patterns,
+            coverage_cells,
+            scenarios,
+            runs,
+        })
+        .into_response());
+    }
+    sqlx::query("DELETE FROM units WHERE unit_id = $1")
+        .bind(unit_id)
+        .execute(&state.pool)
+        .await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
@@ -0,0 +1,316 @@
+//! Users of the planning UI.
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::internal_error;
+use crate::db::AppState;
+use crate::error::AppError;
+
+/// Internal row including the password hash. Never serialized to clients;
+/// responses use [`PublicUser`].
+#[derive(Debug, FromRow)]
+pub struct User {
+    pub user_id: i64,
+    pub organization_id: Option<i64>,
+    pub full_name: String,
+    pub role: String,
+    pub password_hash: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The user as the API exposes it: everything except the hash, which never
+/// leaves the process.
+#[derive(Debug, Serialize, FromRow)]
+pub struct PublicUser {
+    pub user_id: i64,
+    pub organization_id: Option<i64>,
+    pub full_name: String,
+    pub role: String,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateUserBody {
+    pub organization_id: Option<i64>,
+    pub full_name: String,
+    pub role: Option<String>,
+    /// Plaintext; hashed server-side with argon2id before insert.
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatchUserBody {
+    pub organization_id: Option<i64>,
+    pub full_name: Option<String>,
+    pub role: Option<String>,
+    pub password: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// Minimum password length; anything shorter is a 422 before hashing.
+const MIN_PASSWORD_CHARS: usize = 8;
+
+fn validate_password(password: &str) -> Result<(), (StatusCode, String)> {
+    if password.chars().count() < MIN_PASSWORD_CHARS {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("password must be at least {MIN_PASSWORD_CHARS} characters"),
+        ));
+    }
+    Ok(())
+}
+
+const USER_COLUMNS: &str =
+    "user_id, organization_id, full_name, role, password_hash, is_active, created_at";
+const PUBLIC_USER_COLUMNS: &str =
+    "user_id, organization_id, full_name, role, is_active, created_at";
+
+pub async fn create_user(
+    State(state): State<AppState>,
+    Json(body): Json<CreateUserBody>,
+) -> Result<(StatusCode, Json<PublicUser>), AppError> {
+    validate_password(&body.password)?;
+    let hash = hash_password(&body.password)?;
+    let user = sqlx::query_as::<_, PublicUser>(&format!(
+        "INSERT INTO users (organization_id, full_name, role, password_hash)
+         VALUES ($1, $2, COALESCE($3, 'staff'), $4)
+         RETURNING {PUBLIC_USER_COLUMNS}"
+    ))
+    .bind(body.organization_id)
+    .bind(&body.full_name)
+    .bind(&body.role)
+    .bind(&hash)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok((StatusCode::CREATED, Json(user)))
+}
+
+pub async fn list_users(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<PublicUser>>, AppError> {
+    let users = sqlx::query_as::<_, PublicUser>(&format!(
+        "SELECT {PUBLIC_USER_COLUMNS} FROM users ORDER BY user_id"
+    ))
+    .fetch_all(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(users))
+}
+
+pub async fn get_user(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+) -> Result<Json<PublicUser>, AppError> {
+    let user = sqlx::query_as::<_, PublicUser>(&format!(
+        "SELECT {PUBLIC_USER_COLUMNS} FROM users WHERE user_id = $1"
+    ))
+    .bind(user_id)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(user))
+}
+
+pub async fn patch_user(
+    State(state): State<AppState>,
+    Path(user_id): Path<i64>,
+    Json(body): Json<PatchUserBody>,
+) -> Result<Json<PublicUser>, AppError> {
+    if let Some(password) = body.password.as_deref() {
+        validate_password(password)?;
+    }
+    let hash = body.password.as_deref().map(hash_password).transpose()?;
+    let user = sqlx::query_as::<_, PublicUser>(&format!(
+        "UPDATE users
+         SET organization_id = COALESCE($2, organization_id),
+             full_name = COALESCE($3, full_name),
+             role = COALESCE($4, role),
+             password_hash = COALESCE($5, password_hash),
+             is_active = COALESCE($6, is_active)
+         WHERE user_id = $1
+         RETURNING {PUBLIC_USER_COLUMNS}"
+    ))
+    .bind(user_id)
+    .bind(body.organization_id)
+    .bind(&body.full_name)
+    .bind(&body.role)
+    .bind(&hash)
+    .bind(body.is_active)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(user))
+}
+
+/// True for rows written before server-side hashing existed: anything that
+/// is not a PHC-format argon2 string.
+pub fn is_legacy_hash(hash: &str) -> bool {
+    !hash.starts_with("$argon2")
+}
+
+/// Hash a password (or a legacy stored secret) with argon2id.
+pub fn hash_password(password: &str) -> Result<String, (StatusCode, String)> {
+    use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+    let salt = SaltString::generate(&mut OsRng);
+    argon2::Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(internal_error)
+}
+
+/// Verify a password against a user's stored hash, transparently upgrading
+/// legacy-format rows to argon2 on success. Login-time callers get the
+/// rehash for free. Returns whether the password matched.
+pub async fn verify_and_upgrade(
+    pool: &sqlx::PgPool,
+    user_id: i64,
+    password: &str,
+) -> Result<bool, (StatusCode, String)> {
+    use argon2::password_hash::{PasswordHash, PasswordVerifier};
+    let (stored,): (String,) =
+        sqlx::query_as("SELECT password_hash FROM users WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await
+            .map_err(internal_error)?;
+    if is_legacy_hash(&stored) {
+        // Legacy rows hold the client-supplied secret verbatim.
+        if stored != password {
+            return Ok(false);
+        }
+        let upgraded = hash_password(password)?;
+        sqlx::query("UPDATE users SET password_hash = $2 WHERE user_id = $1")
+            .bind(user_id)
+            .bind(&upgraded)
+            .execute(pool)
+            .await
+            .map_err(internal_error)?;
+        return Ok(true);
+    }
+    let parsed = PasswordHash::new(&stored).map_err(internal_error)?;
+    Ok(argon2::Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok())
+}
+
+#[derive(Debug, Serialize)]
+pub struct RehashResult {
+    pub upgraded: usize,
+}
+
+/// Batch-upgrade every legacy-format hash. Legacy rows store the secret
+/// itself, so it can be rehashed in place without user interaction.
+pub async fn rehash_passwords(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+) -> Result<Json<RehashResult>, AppError> {
+    crate::auth::require_role(auth.as_ref(), &["admin"])?;
+    let rows: Vec<(i64, String)> =
+        sqlx::query_as("SELECT user_id, password_hash FROM users ORDER BY user_id")
+            .fetch_all(&state.pool)
+            .await
+            .map_err(internal_error)?;
+    let mut upgraded = 0;
+    for (user_id, stored) in rows {
+        if !is_legacy_hash(&stored) {
+            continue;
+        }
+        let hash = hash_password(&stored)?;
+        sqlx::query("UPDATE users SET password_hash = $2 WHERE user_id = $1")
+            .bind(user_id)
+            .bind(&hash)
+            .execute(&state.pool)
+            .await
+            .map_err(internal_error)?;
+        upgraded += 1;
+    }
+    Ok(Json(RehashResult { upgraded }))
+}
+
+/// Identify the calling user. With bearer auth enabled the identity is the
+/// token subject the middleware decoded — the `X-User-Id` header is ignored,
+/// so it cannot impersonate anyone. The header remains as a fallback only
+/// when auth is disabled (local development and tests run token-free).
+pub(crate) async fn current_user(
+    state: &AppState,
+    auth: Option<&crate::auth::CurrentUser>,
+    headers: &HeaderMap,
+) -> Result<User, (StatusCode, String)> {
+    let user_id: i64 = match auth {
+        Some(auth) => auth.user_id,
+        None => headers
+            .get("x-user-id")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .ok_or((
+                StatusCode::UNAUTHORIZED,
+                "missing or invalid X-User-Id header".to_string(),
+            ))?,
+    };
+    let user = sqlx::query_as::<_, User>(&format!(
+        "SELECT {USER_COLUMNS} FROM users WHERE user_id = $1"
+    ))
+    .bind(user_id)
+    .fetch_optional(&state.pool)
+    .await
+    .map_err(internal_error)?
+    .ok_or((StatusCode::UNAUTHORIZED, format!("unknown user {user_id}")))?;
+    if !user.is_active {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("user {user_id} is inactive"),
+        ));
+    }
+    Ok(user)
+}
+
+/// Organizations the calling user may access: their own org for regular
+/// users, every org for admins with no org binding.
+pub async fn my_organizations(
+    State(state): State<AppState>,
+    auth: Option<crate::auth::CurrentUser>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<super::organizations::Organization>>, AppError> {
+    let user = current_user(&state, auth.as_ref(), &headers).await?;
+    let orgs = match (user.organization_id, user.role.as_str()) {
+        (None, "admin") => sqlx::query_as(
+            "SELECT organization_id, name, status, created_at
+             FROM organizations ORDER BY organization_id",
+        )
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?,
+        (Some(org_id), _) => sqlx::query_as(
+            "SELECT organization_id, name, status, created_at
+             FROM organizations WHERE organization_id = $1",
+        )
+        .bind(org_id)
+        .fetch_all(&state.pool)
+        .await
+        .map_err(internal_error)?,
+        (None, _) => Vec::new(),
+    };
+    Ok(Json(orgs))
+}
+
+pub async fn delete_user(
+    State(state): State<AppState>,
+    user: Option<crate::auth::CurrentUser>,
+    Path(user_id): Path<i64>,
+) -> Result<StatusCode, AppError> {
+    crate::auth::require_role(user.as_ref(), &["admin"])?;
+    sqlx::query("DELETE FROM users WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&state.pool)
+        .await
+        .map_err(internal_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,365 @@
+mod common;
+
+use axum::http::StatusCode;
+
+use common::{req, req_with_headers, setup};
+use serde_json::json;
+use maywin_api::auth;
+
+#[tokio::test]
+async fn bearer_tokens_guard_the_api_when_a_secret_is_set() {
+    // Process-wide: this binary holds the only tests that set JWT_SECRET,
+    // and they all set the same value so parallel runs cannot race.
+    std::env::set_var("JWT_SECRET", "test-secret");
+    let (app, pool) = setup().await;
+
+    // Unprotected: health stays outside the guarded tree.
+    let (status, _) = req(&app, "GET", "/health", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = req(&app, "GET", "/api/v1/organizations", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "missing bearer token");
+
+    let (status, body) = req_with_headers(
+        &app,
+        "GET",
+        "/api/v1/organizations",
+        None,
+        &[("Authorization", "Bearer not-a-jwt")],
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "invalid token");
+
+    // Expired beyond the validation leeway.
+    let expired = auth::issue_token(1, None, "admin", -120).unwrap();
+    let (status, body) = req_with_headers(
+        &app,
+        "GET",
+        "/api/v1/organizations",
+        None,
+        &[("Authorization", &format!("Bearer {expired}"))],
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["error"], "token expired");
+
+    let token = auth::issue_token(1, None, "admin", 3600).unwrap();
+    let (status, orgs) = req_with_headers(
+        &app,
+        "GET",
+        "/api/v1/organizations",
+        None,
+        &[("Authorization", &format!("Bearer {token}"))],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{orgs}");
+    assert!(orgs.is_array());
+
+    drop(pool);
+}
+
+#[tokio::test]
+async fn login_issues_tokens_and_admin_gates_org_delete() {
+    std::env::set_var("JWT_SECRET", "test-secret");
+    let (app, _pool) = setup().await;
+
+    // Bootstrap with a minted admin token; real deployments seed the first
+    // admin out of band.
+    let admin_token = auth::issue_token(1, None, "admin", 3600).unwrap();
+    let admin_auth = format!("Bearer {admin_token}");
+    let (_, org) = req_with_headers(
+        &app,
+        "POST",
+        "/api/v1/organizations",
+        Some(json!({ "name": "Hospital" })),
+        &[("Authorization", &admin_auth)],
+    )
+    .await;
+    let org_id = org["organization_id"].as_i64().unwrap();
+    let (status, user) = req_with_headers(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "organization_id": org_id, "full_name": "Planner One", "password": "planner-pw" })),
+        &[("Authorization", &admin_auth)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{user}");
+
+    // Login stays public and exchanges credentials for a token.
+    let (status, login) = req(
+        &app,
+        "POST",
+        "/api/v1/auth/login",
+        Some(json!({ "full_name": "Planner One", "password": "planner-pw" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{login}");
+    assert_eq!(login["role"], "staff");
+    assert_eq!(login["organization_id"], org_id);
+    let staff_token = login["token"].as_str().unwrap().to_string();
+
+    // The token is a real JWT: three dot-separated base64 segments whose
+    // claims round-trip through the middleware on the next request.
+    assert_eq!(staff_token.split('.').count(), 3);
+
+    // Wrong password: same 401 as an unknown user.
+    let (status, body) = req(
+        &app,
+        "POST",
+        "/api/v1/auth/login",
+        Some(json!({ "full_name": "Planner One", "password": "wrong-pw" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, missing) = req(
+        &app,
+        "POST",
+        "/api/v1/auth/login",
+        Some(json!({ "full_name": "Nobody", "password": "planner-pw" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body, missing);
+
+    // Deactivated accounts cannot log in even with the right password.
+    let user_id = user["user_id"].as_i64().unwrap();
+    let (status, _) = req_with_headers(
+        &app,
+        "PATCH",
+        &format!("/api/v1/users/{user_id}"),
+        Some(json!({ "is_active": false })),
+        &[("Authorization", &admin_auth)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = req(
+        &app,
+        "POST",
+        "/api/v1/auth/login",
+        Some(json!({ "full_name": "Planner One", "password": "planner-pw" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+    req_with_headers(
+        &app,
+        "PATCH",
+        &format!("/api/v1/users/{user_id}"),
+        Some(json!({ "is_active": true })),
+        &[("Authorization", &admin_auth)],
+    )
+    .await;
+
+    // The staff token works, but tenant deletion is admin-only. Use an
+    // org with no users so the FK does not get in the way.
+    let (_, doomed) = req_with_headers(
+        &app,
+        "POST",
+        "/api/v1/organizations",
+        Some(json!({ "name": "Closing Site" })),
+        &[("Authorization", &admin_auth)],
+    )
+    .await;
+    let doomed_id = doomed["organization_id"].as_i64().unwrap();
+    let staff_auth = format!("Bearer {staff_token}");
+    let (status, _) = req_with_headers(
+        &app,
+        "DELETE",
+        &format!("/api/v1/organizations/{doomed_id}"),
+        None,
+        &[("Authorization", &staff_auth)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, body) = req_with_headers(
+        &app,
+        "DELETE",
+        &format!("/api/v1/organizations/{doomed_id}"),
+        None,
+        &[("Authorization", &admin_auth)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT, "{body}");
+}
+
+#[tokio::test]
+async fn destructive_routes_require_the_admin_role() {
+    std::env::set_var("JWT_SECRET", "test-secret");
+    let (app, _pool) = setup().await;
+
+    let admin_token = auth::issue_token(1, None, "admin", 3600).unwrap();
+    let admin_auth = format!("Bearer {admin_token}");
+    let staff_token = auth::issue_token(2, None, "staff", 3600).unwrap();
+    let staff_auth = format!("Bearer {staff_token}");
+
+    let (_, org) = req_with_headers(
+        &app,
+        "POST",
+        "/api/v1/organizations",
+        Some(json!({ "name": "Hospital" })),
+        &[("Authorization", &admin_auth)],
+    )
+    .await;
+    let org_id = org["organization_id"].as_i64().unwrap();
+    let (_, unit) = req_with_headers(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "Ward A" })),
+        &[("Authorization", &admin_auth)],
+    )
+    .await;
+    let unit_id = unit["unit_id"].as_i64().unwrap();
+    let (_, user) = req_with_headers(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "full_name": "Leaver", "password": "leaver-pw" })),
+        &[("Authorization", &admin_auth)],
+    )
+    .await;
+    let user_id = user["user_id"].as_i64().unwrap();
+
+    // Staff can read but not destroy.
+    for path in [
+        format!("/api/v1/units/{unit_id}"),
+        format!("/api/v1/users/{user_id}"),
+        format!("/api/v1/organizations/{org_id}"),
+    ] {
+        let (status, body) = req_with_headers(
+            &app,
+            "DELETE",
+            &path,
+            None,
+            &[("Authorization", &staff_auth)],
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{path}: {body}");
+    }
+
+    // Admin tears the same things down, children first.
+    for path in [
+        format!("/api/v1/units/{unit_id}"),
+        format!("/api/v1/users/{user_id}"),
+        format!("/api/v1/organizations/{org_id}"),
+    ] {
+        let (status, body) = req_with_headers(
+            &app,
+            "DELETE",
+            &path,
+            None,
+            &[("Authorization", &admin_auth)],
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT, "{path}: {body}");
+    }
+}
+
+#[tokio::test]
+async fn spoofed_identity_header_cannot_outrank_the_token() {
+    std::env::set_var("JWT_SECRET", "test-secret");
+    let (app, _pool) = setup().await;
+
+    let admin_token = auth::issue_token(1, None, "admin", 3600).unwrap();
+    let admin_auth = format!("Bearer {admin_token}");
+    // A real admin row for the spoofed header to point at.
+    let (_, admin) = req_with_headers(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "full_name": "Root", "role": "admin", "password": "root-password" })),
+        &[("Authorization", &admin_auth)],
+    )
+    .await;
+    let admin_id = admin["user_id"].as_i64().unwrap().to_string();
+    let (_, staff) = req_with_headers(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "full_name": "Nurse", "role": "staff", "password": "nurse-password" })),
+        &[("Authorization", &admin_auth)],
+    )
+    .await;
+    let staff_id = staff["user_id"].as_i64().unwrap();
+    let staff_token = auth::issue_token(staff_id, None, "staff", 3600).unwrap();
+    let staff_auth = format!("Bearer {staff_token}");
+
+    // With auth enabled, identity is the token subject; an X-User-Id header
+    // naming an admin changes nothing.
+    let (status, body) = req_with_headers(
+        &app,
+        "GET",
+        "/api/v1/audit-log",
+        None,
+        &[("Authorization", &staff_auth), ("X-User-Id", &admin_id)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+    let (status, _) = req_with_headers(
+        &app,
+        "GET",
+        "/api/v1/audit-log",
+        None,
+        &[("Authorization", &admin_auth), ("X-User-Id", &admin_id)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn scenario_rehash_is_admin_only() {
+    std::env::set_var("JWT_SECRET", "test-secret");
+    let (app, _pool) = setup().await;
+
+    let staff_token = auth::issue_token(2, None, "staff", 3600).unwrap();
+    let (status, body) = req_with_headers(
+        &app,
+        "POST",
+        "/api/v1/admin/scenarios/rehash",
+        None,
+        &[("Authorization", &format!("Bearer {staff_token}"))],
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+
+    let admin_token = auth::issue_token(1, None, "admin", 3600).unwrap();
+    let (status, body) = req_with_headers(
+        &app,
+        "POST",
+        "/api/v1/admin/scenarios/rehash",
+        None,
+        &[("Authorization", &format!("Bearer {admin_token}"))],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+}
+
+#[tokio::test]
+async fn password_rehash_is_admin_only() {
+    std::env::set_var("JWT_SECRET", "test-secret");
+    let (app, _pool) = setup().await;
+
+    let staff_token = auth::issue_token(2, None, "staff", 3600).unwrap();
+    let (status, body) = req_with_headers(
+        &app,
+        "POST",
+        "/api/v1/admin/rehash-passwords",
+        None,
+        &[("Authorization", &format!("Bearer {staff_token}"))],
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+
+    let admin_token = auth::issue_token(1, None, "admin", 3600).unwrap();
+    let (status, body) = req_with_headers(
+        &app,
+        "POST",
+        "/api/v1/admin/rehash-passwords",
+        None,
+        &[("Authorization", &format!("Bearer {admin_token}"))],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use axum::http::StatusCode;
+
+use common::{req, setup};
+
+// Lives in its own binary: it needs `APP_ENV` set and `JWT_SECRET` unset
+// process-wide, which would race with the token tests in `auth.rs`.
+#[tokio::test]
+async fn missing_secret_fails_closed_outside_dev() {
+    std::env::set_var("APP_ENV", "production");
+    // Production APP_ENV also drops the solver URL fallback.
+    std::env::set_var("FASTAPI_SOLVER_URL", "http://localhost:8000");
+    std::env::remove_var("JWT_SECRET");
+    let (app, _pool) = setup().await;
+
+    // Health stays outside the guarded tree.
+    let (status, _) = req(&app, "GET", "/health", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = req(&app, "GET", "/api/v1/organizations", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "{body}");
+    assert_eq!(body["error"], "JWT_SECRET is not configured");
+}
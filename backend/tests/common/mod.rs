@@ -0,0 +1,123 @@
+//! Shared test harness: each test gets its own freshly-migrated database.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt;
+use serde_json::Value;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use tower::ServiceExt;
+
+use maywin_api::db::AppState;
+
+/// Create a unique database, run migrations, and return the app router
+/// plus a pool for direct seeding/asserting.
+pub async fn setup() -> (Router, PgPool) {
+    let admin_url = std::env::var("TEST_DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost:5432/postgres".to_string());
+    let db_name = format!("maywin_test_{}", uuid::Uuid::new_v4().simple());
+    let admin = PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&admin_url)
+        .await
+        .expect("connect admin database");
+    sqlx::query(&format!("CREATE DATABASE {db_name}"))
+        .execute(&admin)
+        .await
+        .expect("create test database");
+    let base = admin_url
+        .rsplit_once('/')
+        .expect("database url has a path")
+        .0;
+    let pool = PgPoolOptions::new()
+        .max_connections(5)
+        .connect(&format!("{base}/{db_name}"))
+        .await
+        .expect("connect test database");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    (maywin_api::app(AppState::new(pool.clone())), pool)
+}
+
+/// Fire a single request at the router and decode the JSON response.
+pub async fn req(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    req_with_headers(app, method, uri, body, &[]).await
+}
+
+/// Like [`req`] but with extra request headers (e.g. identity headers).
+#[allow(dead_code)]
+pub async fn req_with_headers(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+    headers: &[(&str, &str)],
+) -> (StatusCode, Value) {
+    let mut builder = Request::builder().method(method).uri(uri);
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    let request = match body {
+        Some(json) => {
+            builder = builder.header("content-type", "application/json");
+            builder.body(Body::from(json.to_string())).unwrap()
+        }
+        None => builder.body(Body::empty()).unwrap(),
+    };
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let value = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes).unwrap_or(Value::String(
+            String::from_utf8_lossy(&bytes).into_owned(),
+        ))
+    };
+    (status, value)
+}
+
+/// Serve the router on an ephemeral local port and return its base URL.
+/// Needed for flows (like `create_run`) that issue real HTTP calls.
+#[allow(dead_code)]
+pub async fn spawn_app(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind test listener");
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("test server error");
+    });
+    format!("http://{addr}")
+}
+
+/// Seed an organization and unit through the API, returning their ids.
+#[allow(dead_code)]
+pub async fn seed_org_and_unit(app: &Router) -> (i64, i64) {
+    let (status, org) = req(
+        app,
+        "POST",
+        "/api/v1/organizations",
+        Some(serde_json::json!({ "name": "Test Hospital" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let org_id = org["organization_id"].as_i64().unwrap();
+    let (status, unit) = req(
+        app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(serde_json::json!({ "name": "Ward A" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    (org_id, unit["unit_id"].as_i64().unwrap())
+}
@@ -0,0 +1,692 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{req, req_with_headers, seed_org_and_unit, setup};
+
+async fn seed_shift(app: &axum::Router, unit_id: i64, name: &str) -> i64 {
+    let (status, shift) = req(
+        app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": name, "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    shift["shift_id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn clear_coverage_zeroes_cells_in_range() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let shift_id = seed_shift(&app, unit_id, "Morning").await;
+
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-06", "shift_id": shift_id, "required_count": 2 },
+            { "day": "2025-01-07", "shift_id": shift_id, "required_count": 3 },
+            { "day": "2025-01-20", "shift_id": shift_id, "required_count": 1 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/coverage/clear?from=2025-01-06&to=2025-01-12"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["affected"], 2);
+
+    let (status, rows) = req(&app, "GET", &format!("/api/v1/units/{unit_id}/coverage"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = rows.as_array().unwrap();
+    assert_eq!(rows[0]["required_count"], 0);
+    assert_eq!(rows[1]["required_count"], 0);
+    // Outside the range stays untouched.
+    assert_eq!(rows[2]["required_count"], 1);
+}
+
+#[tokio::test]
+async fn demand_totals_group_by_iso_week() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let shift_id = seed_shift(&app, unit_id, "Morning").await;
+
+    // Two cells in ISO week 2 of 2025, one in week 3.
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-06", "shift_id": shift_id, "required_count": 2 },
+            { "day": "2025-01-07", "shift_id": shift_id, "required_count": 3 },
+            { "day": "2025-01-13", "shift_id": shift_id, "required_count": 1 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, weeks) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/coverage/demand?from=2025-01-06&to=2025-01-19"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let weeks = weeks.as_array().unwrap();
+    assert_eq!(weeks.len(), 2);
+    assert_eq!(weeks[0]["iso_week"], 2);
+    assert_eq!(weeks[0]["week_start"], "2025-01-06");
+    assert_eq!(weeks[0]["total_required"], 5);
+    assert_eq!(weeks[1]["iso_week"], 3);
+    assert_eq!(weeks[1]["total_required"], 1);
+
+    // A range ending mid-week only counts the days inside it.
+    let (_, weeks) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/coverage/demand?from=2025-01-06&to=2025-01-06"),
+        None,
+    )
+    .await;
+    assert_eq!(weeks.as_array().unwrap()[0]["total_required"], 2);
+}
+
+#[tokio::test]
+async fn clear_coverage_accepts_week_sentinel() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let shift_id = seed_shift(&app, unit_id, "Morning").await;
+
+    // The unit has no site, so its time zone defaults to UTC.
+    let today = chrono::Utc::now().date_naive();
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": today, "shift_id": shift_id, "required_count": 2 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/coverage/clear?from=week&to=week"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["affected"], 1);
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/coverage/clear?from=nonsense&to=week"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+}
+
+#[tokio::test]
+async fn copy_coverage_clones_template_week_onto_targets() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let shift_id = seed_shift(&app, unit_id, "Morning").await;
+
+    // Template week (Mon 2025-01-06 .. Sun 2025-01-12) with two tuned cells.
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-06", "shift_id": shift_id, "required_count": 3 },
+            { "day": "2025-01-08", "shift_id": shift_id, "required_count": 1, "required_skill": "ICU" }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, result) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/coverage/copy"),
+        Some(json!({
+            "from_start": "2025-01-06",
+            "from_end": "2025-01-12",
+            "to_starts": ["2025-01-13", "2025-01-20"]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{result}");
+    assert_eq!(result["copied"], 4);
+
+    let (_, rows) = req(&app, "GET", &format!("/api/v1/units/{unit_id}/coverage"), None).await;
+    let rows = rows.as_array().unwrap();
+    assert_eq!(rows.len(), 6);
+    // Wednesday's skill cell lands on the following Wednesdays.
+    assert_eq!(rows[3]["day"], "2025-01-15");
+    assert_eq!(rows[3]["required_skill"], "ICU");
+    assert_eq!(rows[5]["day"], "2025-01-22");
+    assert_eq!(rows[5]["required_count"], 1);
+
+    // A non-week source range is rejected.
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/coverage/copy"),
+        Some(json!({
+            "from_start": "2025-01-06",
+            "from_end": "2025-01-10",
+            "to_starts": ["2025-01-13"]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    // So is a target that starts on a different weekday.
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/coverage/copy"),
+        Some(json!({
+            "from_start": "2025-01-06",
+            "from_end": "2025-01-12",
+            "to_starts": ["2025-01-14"]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+}
+
+#[tokio::test]
+async fn validate_coverage_flags_undersupplied_skill() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let shift_id = seed_shift(&app, unit_id, "Morning").await;
+
+    // Two general nurses, but only one holds the rare ECMO skill.
+    for (code, skills) in [
+        ("N1", json!(["ICU"])),
+        ("N2", json!(["ICU", "ECMO"])),
+    ] {
+        let (status, _) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/staffs"),
+            Some(json!({ "code": code, "full_name": code, "skills": skills })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-06", "shift_id": shift_id, "required_count": 2, "required_skill": "ICU" },
+            { "day": "2025-01-06", "shift_id": shift_id, "required_count": 2, "required_skill": "ECMO" }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // The second upsert overwrote the first (same cell), so re-add the ICU
+    // demand on another day to check both skills in one report.
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-07", "shift_id": shift_id, "required_count": 2, "required_skill": "ICU" }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, skills) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/coverage/validate"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{skills}");
+    let skills = skills.as_array().unwrap();
+    assert_eq!(skills.len(), 2);
+
+    // Sorted by skill name: ECMO first, one qualified nurse for a demand of two.
+    assert_eq!(skills[0]["skill"], "ECMO");
+    assert_eq!(skills[0]["in_deficit"], true);
+    assert_eq!(skills[0]["total_required"], 2);
+    assert_eq!(skills[0]["total_available"], 1);
+    let cells = skills[0]["deficit_cells"].as_array().unwrap();
+    assert_eq!(cells.len(), 1);
+    assert_eq!(cells[0]["day"], "2025-01-06");
+    assert_eq!(cells[0]["available"], 1);
+
+    assert_eq!(skills[1]["skill"], "ICU");
+    assert_eq!(skills[1]["in_deficit"], false);
+    assert_eq!(skills[1]["deficit_cells"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn validate_coverage_respects_availability() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let shift_id = seed_shift(&app, unit_id, "Morning").await;
+
+    let (status, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "N1", "skills": ["ICU"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-06", "shift_id": shift_id, "required_count": 1, "required_skill": "ICU" }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Available at first, then marked off for the cell.
+    let (status, skills) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/coverage/validate"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(skills[0]["in_deficit"], false);
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/availability/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "value": 0 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, skills) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/coverage/validate"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(skills[0]["in_deficit"], true);
+    assert_eq!(skills[0]["total_available"], 0);
+}
+
+#[tokio::test]
+async fn coverage_csv_resolves_shift_names() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let shift_id = seed_shift(&app, unit_id, "Morning").await;
+
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-06", "shift_id": shift_id, "required_count": 2, "required_skill": "ICU" },
+            { "day": "2025-01-13", "shift_id": shift_id, "required_count": 3 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/coverage.csv"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let csv = body.as_str().unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "day,shift_name,required_count,required_skill"
+    );
+    assert_eq!(lines.next().unwrap(), "2025-01-06,Morning,2,ICU");
+
+    // The CSV route honours the same filters as the JSON listing.
+    let (status, body) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/coverage.csv?from=2025-01-13"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let csv = body.as_str().unwrap();
+    assert_eq!(csv.lines().count(), 2, "{csv}");
+    assert_eq!(csv.lines().nth(1).unwrap(), "2025-01-13,Morning,3,");
+
+    let (status, _) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/coverage.csv?from=2025-02-01&to=2025-01-01"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn org_coverage_joins_names_and_pages_by_cursor() {
+    let (app, _pool) = setup().await;
+    let (org_id, unit_a) = seed_org_and_unit(&app).await;
+    let (_, unit) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "Ward B" })),
+    )
+    .await;
+    let unit_b = unit["unit_id"].as_i64().unwrap();
+
+    let mut shift_ids = Vec::new();
+    for unit_id in [unit_a, unit_b] {
+        let (_, shift) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/shift-patterns"),
+            Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+        )
+        .await;
+        shift_ids.push(shift["shift_id"].as_i64().unwrap());
+        let (status, _) = req(
+            &app,
+            "PUT",
+            &format!("/api/v1/units/{unit_id}/coverage"),
+            Some(json!({ "items": [
+                { "day": "2025-01-06", "shift_id": shift_ids.last().unwrap(), "required_count": 2 },
+                { "day": "2025-01-13", "shift_id": shift_ids.last().unwrap(), "required_count": 3 }
+            ]})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // Page through everything two at a time.
+    let (status, page) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/organizations/{org_id}/coverage?limit=2"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    assert_eq!(page["entries"].as_array().unwrap().len(), 2);
+    assert_eq!(page["entries"][0]["unit_name"], "Ward A");
+    assert_eq!(page["entries"][0]["shift_name"], "Morning");
+    let cursor = page["next_cursor"].as_i64().unwrap();
+    let (_, page2) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/organizations/{org_id}/coverage?limit=3&after={cursor}"),
+        None,
+    )
+    .await;
+    assert_eq!(page2["entries"].as_array().unwrap().len(), 2);
+    assert!(page2["next_cursor"].is_null());
+    assert_eq!(page2["entries"][0]["unit_name"], "Ward B");
+
+    // Window and unit filters narrow the listing.
+    let (_, filtered) = req(
+        &app,
+        "GET",
+        &format!(
+            "/api/v1/organizations/{org_id}/coverage?from=2025-01-13&to=2025-01-13&unit_id={unit_b}"
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(filtered["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(filtered["entries"][0]["required_count"], 3);
+}
+
+#[tokio::test]
+async fn demand_aggregate_is_cached_until_a_coverage_write() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-06", "shift_id": shift_id, "required_count": 2 }
+        ]})),
+    )
+    .await;
+
+    let uri = format!("/api/v1/units/{unit_id}/coverage/demand?from=2025-01-06&to=2025-01-12");
+    let (status, first) = req(&app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK, "{first}");
+    assert_eq!(first[0]["total_required"], 2);
+
+    // Mutate behind the cache's back: the second read is served stale,
+    // while `Cache-Control: no-cache` bypasses the cache.
+    sqlx::query("UPDATE coverage_requirement SET required_count = 5 WHERE unit_id = $1")
+        .bind(unit_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (_, second) = req(&app, "GET", &uri, None).await;
+    assert_eq!(second[0]["total_required"], 2);
+    let (_, fresh) = req_with_headers(&app, "GET", &uri, None, &[("Cache-Control", "no-cache")]).await;
+    assert_eq!(fresh[0]["total_required"], 5);
+
+    // A write through the API invalidates the unit's cached aggregates.
+    req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-07", "shift_id": shift_id, "required_count": 1 }
+        ]})),
+    )
+    .await;
+    let (_, third) = req(&app, "GET", &uri, None).await;
+    assert_eq!(third[0]["total_required"], 6, "{third}");
+}
+
+#[tokio::test]
+async fn expired_certifications_stop_counting_toward_skill_coverage() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let shift_id = seed_shift(&app, unit_id, "Morning").await;
+
+    let (status, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice", "skills": ["ECMO"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+
+    // An expiry on a skill the staff doesn't hold is a typo, not a record.
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/staffs/{staff_id}/skill-expiries"),
+        Some(json!({ "skill": "ICU", "expires_on": "2025-03-31" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, expiry) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/staffs/{staff_id}/skill-expiries"),
+        Some(json!({ "skill": "ECMO", "expires_on": "2025-03-31" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{expiry}");
+    assert_eq!(expiry["expires_on"], "2025-03-31");
+
+    // One ECMO cell before the expiry, one after.
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-03-03", "shift_id": shift_id, "required_count": 1, "required_skill": "ECMO" },
+            { "day": "2025-04-07", "shift_id": shift_id, "required_count": 1, "required_skill": "ECMO" }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, skills) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/coverage/validate"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{skills}");
+    let ecmo = &skills.as_array().unwrap()[0];
+    assert_eq!(ecmo["skill"], "ECMO");
+    // Covered while certified, in deficit once the certification lapsed.
+    assert_eq!(ecmo["total_available"], 1);
+    assert_eq!(ecmo["in_deficit"], true);
+    let deficits = ecmo["deficit_cells"].as_array().unwrap();
+    assert_eq!(deficits.len(), 1);
+    assert_eq!(deficits[0]["day"], "2025-04-07");
+
+    // Dropping the expiry restores the later cell.
+    let (status, _) = req(
+        &app,
+        "DELETE",
+        &format!("/api/v1/staffs/{staff_id}/skill-expiries/ECMO"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, skills) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/coverage/validate"),
+        None,
+    )
+    .await;
+    assert_eq!(skills.as_array().unwrap()[0]["in_deficit"], false);
+}
+
+#[tokio::test]
+async fn coverage_listing_filters_by_window_shift_and_page() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let morning = seed_shift(&app, unit_id, "Morning").await;
+    let night = seed_shift(&app, unit_id, "Night").await;
+
+    // Three weeks of demand, two shifts per day.
+    let mut items = Vec::new();
+    for offset in 0..21 {
+        let day = format!("2025-01-{:02}", 6 + offset);
+        for shift_id in [morning, night] {
+            items.push(json!({ "day": day, "shift_id": shift_id, "required_count": 1 }));
+        }
+    }
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": items })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // A 7-day window returns only rows in range.
+    let (status, rows) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/coverage?from=2025-01-13&to=2025-01-19"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = rows.as_array().unwrap();
+    assert_eq!(rows.len(), 14);
+    assert_eq!(rows[0]["day"], "2025-01-13");
+    assert_eq!(rows[13]["day"], "2025-01-19");
+
+    // Shift filter stacks with the window.
+    let (_, rows) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/coverage?from=2025-01-13&to=2025-01-19&shift_id={night}"),
+        None,
+    )
+    .await;
+    assert_eq!(rows.as_array().unwrap().len(), 7);
+
+    // Pagination walks the full set without overlap.
+    let (_, page) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/coverage?limit=40"),
+        None,
+    )
+    .await;
+    assert_eq!(page.as_array().unwrap().len(), 40);
+    let (_, rest) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/coverage?limit=40&offset=40"),
+        None,
+    )
+    .await;
+    assert_eq!(rest.as_array().unwrap().len(), 2);
+
+    // An inverted range is a 422, not an empty list.
+    let (status, body) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/coverage?from=2025-01-19&to=2025-01-13"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+}
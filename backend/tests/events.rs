@@ -0,0 +1,48 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{req, seed_org_and_unit, setup};
+
+#[tokio::test]
+async fn event_log_records_scenarios_and_pages_by_id() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    for nurse in ["Alice", "Bob"] {
+        let (status, _) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/scenarios"),
+            Some(json!({ "payload": { "nurses": [nurse], "days": [], "shifts": [] } })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (status, events) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/events?unit_id={unit_id}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let events = events.as_array().unwrap().clone();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["event_type"], "scenario.created");
+
+    // `after` pages past the first event.
+    let first_id = events[0]["event_id"].as_i64().unwrap();
+    let (_, page) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/events?unit_id={unit_id}&after={first_id}"),
+        None,
+    )
+    .await;
+    let page = page.as_array().unwrap();
+    assert_eq!(page.len(), 1);
+    assert!(page[0]["event_id"].as_i64().unwrap() > first_id);
+}
@@ -0,0 +1,21 @@
+mod common;
+
+use axum::http::StatusCode;
+
+use common::{req, setup};
+
+#[tokio::test]
+async fn health_reflects_database_connectivity() {
+    let (app, pool) = setup().await;
+
+    let (status, body) = req(&app, "GET", "/health", None).await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["status"], "ok");
+    assert!(body["db_latency_ms"].is_u64(), "{body}");
+
+    // With the pool gone the same probe must degrade, not lie.
+    pool.close().await;
+    let (status, body) = req(&app, "GET", "/health", None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE, "{body}");
+    assert_eq!(body["status"], "degraded");
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{req, seed_org_and_unit, setup};
+
+#[tokio::test]
+async fn kpi_export_joins_runs_and_respects_window() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    for (started_at, total) in [("2025-03-01T08:00:00Z", 10), ("2025-06-01T08:00:00Z", 20)] {
+        let (run_id,): (i64,) = sqlx::query_as(
+            "INSERT INTO solver_runs (scenario_id, status, started_at)
+             VALUES ($1, 'succeeded', $2::timestamptz) RETURNING run_id",
+        )
+        .bind(scenario_id)
+        .bind(started_at)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO kpi (run_id, total_assignments, overtime_hours, avg_satisfaction)
+             VALUES ($1, $2, 1.5, 80)",
+        )
+        .bind(run_id)
+        .bind(total)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let (status, body) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/kpi/export.csv?unit_id={unit_id}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let csv = body.as_str().unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "run_id,scenario_id,started_at,total_assignments,understaffed_cells,overtime_hours,avg_satisfaction"
+    );
+    assert_eq!(lines.clone().count(), 2);
+    assert!(lines.next().unwrap().contains(",10,0,1.5,80"));
+
+    // The window keeps only the March run.
+    let (_, body) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/kpi/export.csv?unit_id={unit_id}&from=2025-02-01&to=2025-04-01"),
+        None,
+    )
+    .await;
+    assert_eq!(body.as_str().unwrap().lines().count(), 2);
+}
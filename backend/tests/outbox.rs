@@ -0,0 +1,59 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::routing::post;
+use axum::Router;
+use serde_json::json;
+
+use common::setup;
+use maywin_api::outbox;
+
+/// Webhook receiver that counts the deliveries it accepts.
+async fn spawn_webhook(hits: Arc<AtomicUsize>) -> String {
+    let router = Router::new().route(
+        "/hook",
+        post(move || {
+            hits.fetch_add(1, Ordering::SeqCst);
+            async { "ok" }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{addr}/hook")
+}
+
+#[tokio::test]
+async fn persisted_event_is_delivered_once_across_restarts() {
+    let (_app, pool) = setup().await;
+    let hits = Arc::new(AtomicUsize::new(0));
+    let url = spawn_webhook(hits.clone()).await;
+    let http = reqwest::Client::new();
+
+    // The event is persisted before any dispatcher exists — a dispatcher
+    // crash between commit and delivery must not lose it.
+    let mut tx = pool.begin().await.unwrap();
+    outbox::enqueue(&mut tx, "run.succeeded", &json!({ "run_id": 1 }))
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+
+    let delivered = outbox::deliver_pending(&pool, &http, &url).await;
+    assert_eq!(delivered, 1);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+    let (sent,): (bool,) =
+        sqlx::query_as("SELECT sent_at IS NOT NULL FROM outbox WHERE event_type = 'run.succeeded'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(sent);
+
+    // A freshly "restarted" dispatcher sees nothing left to send.
+    let delivered = outbox::deliver_pending(&pool, &http, &url).await;
+    assert_eq!(delivered, 0);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,256 @@
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::{req, seed_org_and_unit, setup};
+
+#[tokio::test]
+async fn json_patch_edits_a_single_hard_rule() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (status, policy) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/policy-sets"),
+        Some(json!({
+            "name": "default",
+            "weights": { "understaff_penalty": 50 },
+            "hard_rules": { "max_nights_per_week": 3 }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let policy_id = policy["policy_id"].as_i64().unwrap();
+
+    let patch = json!([
+        { "op": "replace", "path": "/hard_rules/max_nights_per_week", "value": 2 },
+        { "op": "add", "path": "/weights/overtime_penalty", "value": 10 }
+    ]);
+    let request = Request::builder()
+        .method("PATCH")
+        .uri(format!("/api/v1/policy-sets/{policy_id}"))
+        .header("content-type", "application/json-patch+json")
+        .body(Body::from(patch.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let updated: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(updated["hard_rules"]["max_nights_per_week"], 2);
+    assert_eq!(updated["weights"]["overtime_penalty"], 10);
+    // Untouched keys survive.
+    assert_eq!(updated["weights"]["understaff_penalty"], 50);
+}
+
+#[tokio::test]
+async fn full_object_patch_still_works() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, policy) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/policy-sets"),
+        Some(json!({ "name": "default" })),
+    )
+    .await;
+    let policy_id = policy["policy_id"].as_i64().unwrap();
+    let (status, updated) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/policy-sets/{policy_id}"),
+        Some(json!({ "weights": { "understaff_penalty": 80 } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["weights"]["understaff_penalty"], 80);
+}
+
+#[tokio::test]
+async fn policy_runs_lists_only_runs_using_the_policy() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, policy) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/policy-sets"),
+        Some(json!({ "name": "Strict", "weights": { "overtime": 50 } })),
+    )
+    .await;
+    let policy_id = policy["policy_id"].as_i64().unwrap();
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    // One run with the policy (plus KPIs), one without.
+    let (with_policy,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, policy_id, status) VALUES ($1, $2, 'succeeded')
+         RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .bind(policy_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO kpi (run_id, total_assignments, overtime_hours) VALUES ($1, 12, 4.5)",
+    )
+    .bind(with_policy)
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded')")
+        .bind(scenario_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, runs) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/policy-sets/{policy_id}/runs"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{runs}");
+    let runs = runs.as_array().unwrap();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0]["run_id"].as_i64().unwrap(), with_policy);
+    assert_eq!(runs[0]["total_assignments"], 12);
+    assert_eq!(runs[0]["overtime_hours"], 4.5);
+
+    // The keyset cursor skips past the returned run.
+    let (_, rest) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/policy-sets/{policy_id}/runs?after={with_policy}"),
+        None,
+    )
+    .await;
+    assert_eq!(rest.as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn effective_policy_merges_defaults_under_policy_weights() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (status, policy) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/policy-sets"),
+        Some(json!({
+            "name": "Nights heavy",
+            "weights": { "overtime": 42 },
+            "hard_rules": { "max_consecutive_days": 5 }
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let policy_id = policy["policy_id"].as_i64().unwrap();
+
+    let (status, effective) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/policy-sets/{policy_id}/effective"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{effective}");
+    // The policy's value wins, and default keys it never mentions appear.
+    assert_eq!(effective["weights"]["overtime"], 42);
+    assert_eq!(effective["weights"]["understaffing"], 100);
+    assert_eq!(effective["weights"]["preference"], 1);
+    assert_eq!(effective["hard_rules"]["max_consecutive_days"], 5);
+
+    let (status, _) = req(&app, "GET", "/api/v1/policy-sets/9999/effective", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn concurrent_activations_leave_exactly_one_active_policy() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let mut policy_ids = Vec::new();
+    for name in ["A", "B"] {
+        let (_, policy) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/policy-sets"),
+            Some(json!({ "name": name })),
+        )
+        .await;
+        policy_ids.push(policy["policy_id"].as_i64().unwrap());
+    }
+
+    // Hammer both activations from two tasks; losers get 409, never a 500.
+    let mut handles = Vec::new();
+    for &policy_id in &policy_ids {
+        let app = app.clone();
+        handles.push(tokio::spawn(async move {
+            for _ in 0..10 {
+                let (status, body) = req(
+                    &app,
+                    "POST",
+                    &format!("/api/v1/policy-sets/{policy_id}/activate"),
+                    None,
+                )
+                .await;
+                assert!(
+                    status == StatusCode::OK || status == StatusCode::CONFLICT,
+                    "{status}: {body}"
+                );
+            }
+        }));
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    let (active,): (i64,) =
+        sqlx::query_as("SELECT count(*) FROM policy_sets WHERE unit_id = $1 AND is_active")
+            .bind(unit_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(active, 1);
+
+    // The winner is reflected on the row the API returns.
+    let (_, policies) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/policy-sets"),
+        None,
+    )
+    .await;
+    let actives: Vec<_> = policies
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|p| p["is_active"] == true)
+        .collect();
+    assert_eq!(actives.len(), 1);
+}
+
+#[tokio::test]
+async fn patching_a_missing_policy_is_a_404() {
+    let (app, _pool) = setup().await;
+    let (status, body) = req(
+        &app,
+        "PATCH",
+        "/api/v1/policy-sets/999999",
+        Some(json!({ "name": "Ghost" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+    assert_eq!(body["error"]["code"], "not_found");
+}
@@ -0,0 +1,698 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{req, seed_org_and_unit, setup};
+
+#[tokio::test]
+async fn combined_staff_inputs_save_is_atomic() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    let (status, result) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staff-inputs/bulk"),
+        Some(json!({
+            "availability": [ { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "value": 1 } ],
+            "preferences":  [ { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "penalty": 5 } ]
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result["availability_upserted"], 1);
+    assert_eq!(result["preferences_upserted"], 1);
+
+    // A bad preference row rolls the whole save back, availability included.
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staff-inputs/bulk"),
+        Some(json!({
+            "availability": [ { "staff_id": staff_id, "day": "2025-01-07", "shift_id": shift_id, "value": 1 } ],
+            "preferences":  [ { "staff_id": 9999, "day": "2025-01-07", "shift_id": shift_id, "penalty": 5 } ]
+        })),
+    )
+    .await;
+    assert_ne!(status, StatusCode::OK);
+    let (count,): (i64,) = sqlx::query_as(
+        "SELECT count(*) FROM availability WHERE staff_id = $1 AND day = '2025-01-07'",
+    )
+    .bind(staff_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn range_delete_clears_availability_and_preferences() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    for (path, field) in [("availability", "value"), ("preferences", "penalty")] {
+        let (status, _) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/{path}/bulk"),
+            Some(json!({ "items": [
+                { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, field: 1 },
+                { "staff_id": staff_id, "day": "2025-02-03", "shift_id": shift_id, field: 1 }
+            ]})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        // Only the January row falls inside the range.
+        let (status, body) = req(
+            &app,
+            "DELETE",
+            &format!("/api/v1/staffs/{staff_id}/{path}?from=2025-01-01&to=2025-01-31"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["deleted"], 1);
+
+        // An unbounded wipe is not allowed.
+        let (status, _) = req(
+            &app,
+            "DELETE",
+            &format!("/api/v1/staffs/{staff_id}/{path}"),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn preferences_summary_totals_and_top_avoided() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let mut shift_ids = Vec::new();
+    for name in ["Morning", "Night"] {
+        let (_, shift) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/shift-patterns"),
+            Some(json!({ "name": name, "start_time": "07:00:00", "end_time": "15:00:00" })),
+        )
+        .await;
+        shift_ids.push(shift["shift_id"].as_i64().unwrap());
+    }
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/preferences/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_ids[0], "penalty": 1 },
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_ids[1], "penalty": 8 },
+            { "staff_id": staff_id, "day": "2025-01-07", "shift_id": shift_ids[1], "penalty": 6 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, summary) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/staffs/{staff_id}/preferences/summary?from=2025-01-01&to=2025-01-31"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["total_penalty"], 15);
+    assert_eq!(summary["entry_count"], 3);
+    assert_eq!(summary["avg_penalty"], 5.0);
+    let top = summary["top_avoided"].as_array().unwrap();
+    assert_eq!(top[0]["shift_name"], "Night");
+    assert_eq!(top[0]["total_penalty"], 14);
+}
+
+#[tokio::test]
+async fn gzipped_bulk_upload_is_inflated_before_extraction() {
+    use axum::body::Body;
+    use http_body_util::BodyExt;
+    use std::io::Write;
+    use tower::ServiceExt;
+
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    let payload = json!({ "items": [
+        { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "value": 0 }
+    ]})
+    .to_string();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(payload.as_bytes()).unwrap();
+    let gzipped = encoder.finish().unwrap();
+
+    let request = axum::http::Request::builder()
+        .method("POST")
+        .uri("/api/v1/availability/bulk")
+        .header("content-type", "application/json")
+        .header("content-encoding", "gzip")
+        .body(Body::from(gzipped))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(
+        status,
+        StatusCode::OK,
+        "{}",
+        String::from_utf8_lossy(&bytes)
+    );
+
+    let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM availability WHERE staff_id = $1")
+        .bind(staff_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn penalties_above_the_configured_ceiling_are_rejected() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    std::env::set_var("MAX_PREFERENCE_PENALTY", "100");
+    let (status, error) = req(
+        &app,
+        "POST",
+        "/api/v1/preferences/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "penalty": 5 },
+            { "staff_id": staff_id, "day": "2025-01-07", "shift_id": shift_id, "penalty": 101 }
+        ]})),
+    )
+    .await;
+    std::env::remove_var("MAX_PREFERENCE_PENALTY");
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(error["fields"]["items[1].penalty"][0]
+        .as_str()
+        .unwrap()
+        .contains("exceeds the maximum of 100"));
+
+    // Rejected before the transaction: the in-range row wasn't saved either.
+    let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM preferences")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn normalize_rescales_penalties_into_the_target_range() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/preferences/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "penalty": 200 },
+            { "staff_id": staff_id, "day": "2025-01-07", "shift_id": shift_id, "penalty": 50 },
+            { "staff_id": staff_id, "day": "2025-01-08", "shift_id": shift_id, "penalty": 0 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, result) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/staffs/{staff_id}/preferences/normalize"),
+        Some(json!({ "max": 10 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{result}");
+    assert_eq!(result["updated"], 3);
+    assert_eq!(result["from_max"], 200);
+    assert_eq!(result["to_max"], 10);
+
+    let rows: Vec<(i32,)> = sqlx::query_as(
+        "SELECT penalty FROM preferences WHERE staff_id = $1 ORDER BY day",
+    )
+    .bind(staff_id)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    // 200 -> 10, 50 -> round(2.5) = 3 (numeric rounds half away from zero), 0 -> 0.
+    assert_eq!(rows, vec![(10,), (3,), (0,)]);
+}
+
+#[tokio::test]
+async fn availability_matrix_pivots_staff_by_day() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let mut staff_ids = Vec::new();
+    for (code, name) in [("N1", "Alice"), ("N2", "Bob")] {
+        let (_, staff) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/staffs"),
+            Some(json!({ "code": code, "full_name": name })),
+        )
+        .await;
+        staff_ids.push(staff["staff_id"].as_i64().unwrap());
+    }
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/availability/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_ids[0], "day": "2025-01-06", "shift_id": shift_id, "value": 1 },
+            { "staff_id": staff_ids[0], "day": "2025-01-07", "shift_id": shift_id, "value": 0 },
+            { "staff_id": staff_ids[1], "day": "2025-01-08", "shift_id": shift_id, "value": 1 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = req(
+        &app,
+        "GET",
+        &format!(
+            "/api/v1/units/{unit_id}/availability/matrix.csv?from=2025-01-06&to=2025-01-08"
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let csv = body.as_str().unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "staff_code,2025-01-06,2025-01-07,2025-01-08"
+    );
+    // Unrecorded cells stay blank rather than defaulting to 0.
+    assert_eq!(lines.next().unwrap(), "N1,1,0,");
+    assert_eq!(lines.next().unwrap(), "N2,,,1");
+    assert!(lines.next().is_none());
+
+    // Filtering on a shift that has no rows leaves the grid blank.
+    let (status, body) = req(
+        &app,
+        "GET",
+        &format!(
+            "/api/v1/units/{unit_id}/availability/matrix.csv?from=2025-01-06&to=2025-01-06&shift_id={}",
+            shift_id + 1
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_str().unwrap().lines().nth(1).unwrap(), "N1,");
+}
+
+#[tokio::test]
+async fn availability_listing_filters_and_clamps_the_range() {
+    let (app, _pool) = setup().await;
+    let (org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, other_unit) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "Ward B" })),
+    )
+    .await;
+    let other_unit_id = other_unit["unit_id"].as_i64().unwrap();
+
+    let mut staff_ids = Vec::new();
+    for (unit, code) in [(unit_id, "N1"), (other_unit_id, "N2")] {
+        let (_, staff) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit}/staffs"),
+            Some(json!({ "code": code, "full_name": format!("Nurse {code}") })),
+        )
+        .await;
+        staff_ids.push(staff["staff_id"].as_i64().unwrap());
+    }
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    let items: Vec<_> = staff_ids
+        .iter()
+        .map(|staff_id| {
+            json!({ "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "value": 1 })
+        })
+        .collect();
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/availability/bulk",
+        Some(json!({ "items": items })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // unit_id joins through staffs, so the other ward's nurse is excluded.
+    let (status, rows) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/availability?unit_id={unit_id}&from=2025-01-01&to=2025-01-31"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{rows}");
+    let rows = rows.as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["staff_id"], staff_ids[0]);
+    assert_eq!(rows[0]["value"], 1);
+
+    // An inverted range is a client error, not an empty result.
+    let (status, _) = req(
+        &app,
+        "GET",
+        "/api/v1/availability?from=2025-02-01&to=2025-01-01",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // A span beyond 92 days is clamped: the row on 2025-01-06 is inside the
+    // clamped window, so asking for a whole year still finds it.
+    let (status, rows) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/availability?staff_id={}&from=2025-01-01&to=2025-12-31", staff_ids[0]),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(rows.as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn bulk_availability_rejects_bad_values_and_duplicates_atomically() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    // One good row, one out-of-range value, one duplicate of the first:
+    // the whole batch must bounce with both problems named by index.
+    let (status, body) = req(
+        &app,
+        "POST",
+        "/api/v1/availability/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "value": 1 },
+            { "staff_id": staff_id, "day": "2025-01-07", "shift_id": shift_id, "value": 2 },
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "value": 0 },
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+    assert!(body["fields"]["items[1].value"][0]
+        .as_str()
+        .unwrap()
+        .contains("must be 0 or 1"));
+    assert!(body["fields"]["items[2]"][0]
+        .as_str()
+        .unwrap()
+        .contains("duplicate cell"));
+    // No partial writes: the valid first row was not stored either.
+    let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM availability")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    // The same check guards the combined grid-editor save.
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staff-inputs/bulk"),
+        Some(json!({ "availability": [
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "value": -1 },
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn preference_listing_filters_by_threshold_and_knows_missing_staff() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/preferences/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "penalty": 2 },
+            { "staff_id": staff_id, "day": "2025-01-07", "shift_id": shift_id, "penalty": 8 },
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, rows) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/preferences?unit_id={unit_id}&from=2025-01-01&to=2025-01-31"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{rows}");
+    assert_eq!(rows.as_array().unwrap().len(), 2);
+
+    // min_penalty keeps only the strong aversion.
+    let (status, rows) = req(
+        &app,
+        "GET",
+        &format!(
+            "/api/v1/preferences?staff_id={staff_id}&from=2025-01-01&to=2025-01-31&min_penalty=5"
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = rows.as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["penalty"], 8);
+
+    let (status, _) = req(
+        &app,
+        "GET",
+        "/api/v1/preferences?from=2025-02-01&to=2025-01-01",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = req(
+        &app,
+        "GET",
+        "/api/v1/preferences?staff_id=999999&from=2025-01-01&to=2025-01-31",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // The 92-day clamp from the availability listing applies here too: both
+    // rows sit inside the clamped window, so a year-long request finds them.
+    let (status, rows) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/preferences?staff_id={staff_id}&from=2025-01-01&to=2025-12-31"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(rows.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn negative_and_oversized_penalties_bounce_the_whole_batch() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    // Even without MAX_PREFERENCE_PENALTY configured the default cap of
+    // 1000 holds, and the negative row is named alongside it.
+    let (status, error) = req(
+        &app,
+        "POST",
+        "/api/v1/preferences/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "penalty": -5 },
+            { "staff_id": staff_id, "day": "2025-01-07", "shift_id": shift_id, "penalty": 3 },
+            { "staff_id": staff_id, "day": "2025-01-08", "shift_id": shift_id, "penalty": 1_000_000 },
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{error}");
+    assert!(error["fields"]["items[0].penalty"][0]
+        .as_str()
+        .unwrap()
+        .contains("must not be negative"));
+    assert!(error["fields"]["items[2].penalty"][0]
+        .as_str()
+        .unwrap()
+        .contains("exceeds the maximum of 1000"));
+
+    // Atomic: the valid middle row was not written.
+    let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM preferences")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
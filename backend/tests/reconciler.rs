@@ -0,0 +1,84 @@
+mod common;
+
+use serde_json::json;
+
+use common::{req, seed_org_and_unit, setup};
+use maywin_api::reconciler;
+
+#[tokio::test]
+async fn kpi_less_succeeded_runs_are_backfilled() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice", "max_weekly_hours": 8 })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    // Two staff required on a day with one assigned: one understaffed cell.
+    req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-06", "shift_id": shift_id, "required_count": 2 }
+        ]})),
+    )
+    .await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    for day in ["2025-01-06", "2025-01-07"] {
+        sqlx::query(
+            "INSERT INTO assignments (run_id, staff_id, day, shift_id) VALUES ($1, $2, $3::date, $4)",
+        )
+        .bind(run_id)
+        .bind(staff_id)
+        .bind(day)
+        .bind(shift_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let backfilled = reconciler::backfill_missing_kpis(&pool).await;
+    assert_eq!(backfilled, 1);
+
+    let (total, understaffed, overtime): (i32, i32, f64) = sqlx::query_as(
+        "SELECT total_assignments, understaffed_cells, overtime_hours FROM kpi WHERE run_id = $1",
+    )
+    .bind(run_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(total, 2);
+    assert_eq!(understaffed, 1);
+    // Two 8h shifts in one ISO week against an 8h contract.
+    assert_eq!(overtime, 8.0);
+
+    // A second pass finds nothing left to do.
+    assert_eq!(reconciler::backfill_missing_kpis(&pool).await, 0);
+}
@@ -0,0 +1,451 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{req, seed_org_and_unit, setup};
+
+#[tokio::test]
+async fn hash_endpoint_matches_create_scenario_hash() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let payload = json!({ "nurses": ["Alice"], "days": ["2025-01-06"], "shifts": ["Morning"] });
+    let (status, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": payload })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // Same content with keys in a different order hashes identically.
+    let reordered = json!({ "shifts": ["Morning"], "days": ["2025-01-06"], "nurses": ["Alice"] });
+    let (status, hashed) = req(
+        &app,
+        "POST",
+        "/api/v1/scenarios/hash",
+        Some(json!({ "payload": reordered })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(hashed["input_hash"], scenario["input_hash"]);
+}
+
+#[tokio::test]
+async fn create_scenario_rejects_oversized_horizon() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    // 400-day span: 2025-01-01 .. 2026-02-04.
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {
+            "nurses": ["Alice"],
+            "days": ["2025-01-01", "2026-02-04"],
+            "shifts": ["Morning"]
+        }})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "body: {body}");
+}
+
+#[tokio::test]
+async fn delete_scenario_guards_runs_unless_forced() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (status, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": [], "days": [], "shifts": [] } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    // Seed a succeeded run directly.
+    sqlx::query("INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded')")
+        .bind(scenario_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, _) = req(
+        &app,
+        "DELETE",
+        &format!("/api/v1/scenarios/{scenario_id}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, body) = req(
+        &app,
+        "DELETE",
+        &format!("/api/v1/scenarios/{scenario_id}?force=true"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["deleted_runs"], 1);
+
+    let (remaining,): (i64,) =
+        sqlx::query_as("SELECT count(*) FROM scenarios WHERE scenario_id = $1")
+            .bind(scenario_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(remaining, 0);
+}
+
+#[tokio::test]
+async fn complexity_reports_counts_and_difficulty() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let payload = json!({
+        "nurses": ["Alice", "Bob", "Carol"],
+        "days": ["2025-01-06", "2025-01-07"],
+        "shifts": ["Morning", "Night"],
+        "demand": {
+            "2025-01-06": { "Morning": 2, "Night": 1 },
+            "2025-01-07": { "Morning": 2, "Night": 1 }
+        }
+    });
+    let (status, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": payload })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    let (status, body) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/scenarios/{scenario_id}/complexity"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["staff_count"], 3);
+    assert_eq!(body["shift_count"], 2);
+    assert_eq!(body["day_count"], 2);
+    assert_eq!(body["coverage_cells"], 4);
+    assert_eq!(body["estimated_variables"], 12);
+    assert_eq!(body["difficulty"], "trivial");
+}
+
+#[tokio::test]
+async fn admin_rehash_merges_legacy_duplicates() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    // Two rows with the same payload but stale, non-canonical hashes, as
+    // left behind by the pre-canonicalization code.
+    let payload = json!({ "nurses": ["Alice"], "days": [], "shifts": [] });
+    let mut ids = Vec::new();
+    for legacy_hash in ["legacy-a", "legacy-b"] {
+        let (id,): (i64,) = sqlx::query_as(
+            "INSERT INTO scenarios (unit_id, payload, input_hash) VALUES ($1, $2, $3)
+             RETURNING scenario_id",
+        )
+        .bind(unit_id)
+        .bind(&payload)
+        .bind(legacy_hash)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        ids.push(id);
+    }
+    // A run referencing the older duplicate must survive the merge.
+    sqlx::query("INSERT INTO solver_runs (scenario_id) VALUES ($1)")
+        .bind(ids[0])
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, result) = req(&app, "POST", "/api/v1/admin/scenarios/rehash", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result["merged"], 1);
+    assert_eq!(result["rehashed"], 1);
+
+    // Only the newest row remains, carrying the canonical hash and the run.
+    let remaining: Vec<(i64, String)> =
+        sqlx::query_as("SELECT scenario_id, input_hash FROM scenarios WHERE unit_id = $1")
+            .bind(unit_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].0, ids[1]);
+    assert_ne!(remaining[0].1, "legacy-b");
+    let (run_scenario,): (i64,) = sqlx::query_as("SELECT scenario_id FROM solver_runs")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(run_scenario, ids[1]);
+}
+
+#[tokio::test]
+async fn source_stats_count_scenarios_per_channel() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    for (n, source) in [(1, "web"), (2, "web"), (3, "chatbot")] {
+        let (status, _) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/scenarios"),
+            Some(json!({ "payload": { "n": n }, "source": source })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    let (status, stats) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/scenarios/sources"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{stats}");
+    let stats = stats.as_array().unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0]["source"], "web");
+    assert_eq!(stats[0]["count"], 2);
+    assert_eq!(stats[1]["source"], "chatbot");
+    assert_eq!(stats[1]["count"], 1);
+
+    // A window in the past excludes everything created today.
+    let (_, stats) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/scenarios/sources?to=2020-01-01"),
+        None,
+    )
+    .await;
+    assert_eq!(stats.as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn payload_embedding_another_unit_is_rejected() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "unit_id": unit_id + 1, "nurses": [] } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+    assert!(body["error"]["message"].as_str().unwrap().contains("unit"));
+
+    // A matching embedded id (or none at all) still works.
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "unit_id": unit_id, "nurses": [] } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn rehash_writes_an_audit_entry() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "n": 1 } })),
+    )
+    .await;
+
+    let (status, result) = req(&app, "POST", "/api/v1/admin/scenarios/rehash", None).await;
+    assert_eq!(status, StatusCode::OK, "{result}");
+
+    let (action, detail): (String, serde_json::Value) = sqlx::query_as(
+        "SELECT action, detail FROM audit_log ORDER BY audit_id DESC LIMIT 1",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(action, "scenarios.rehashed");
+    assert_eq!(detail["merged"], 0);
+}
+
+#[tokio::test]
+async fn dry_run_delete_previews_counts_without_deleting() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "n": 1 } })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let _ = run_id;
+
+    // A preview bypasses the force gate and reports the blast radius.
+    let (status, preview) = req(
+        &app,
+        "DELETE",
+        &format!("/api/v1/scenarios/{scenario_id}?dry_run=true"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{preview}");
+    assert_eq!(preview["dry_run"], true);
+    assert_eq!(preview["deleted_runs"], 1);
+
+    // Nothing was mutated.
+    let (scenarios, runs): (i64, i64) = sqlx::query_as(
+        "SELECT (SELECT count(*) FROM scenarios), (SELECT count(*) FROM solver_runs)",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!((scenarios, runs), (1, 1));
+
+    // Without dry_run the succeeded run still blocks a plain delete.
+    let (status, _) = req(
+        &app,
+        "DELETE",
+        &format!("/api/v1/scenarios/{scenario_id}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn missing_ids_are_404s_not_500s() {
+    let (app, _pool) = setup().await;
+
+    for path in [
+        "/api/v1/scenarios/999999",
+        "/api/v1/policy-sets/999999",
+        "/api/v1/solver-runs/999999",
+        "/api/v1/solver-runs/999999/kpi",
+    ] {
+        let (status, body) = req(&app, "GET", path, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{path}: {body}");
+        assert_eq!(body["error"]["code"], "not_found", "{path}");
+    }
+}
+
+#[tokio::test]
+async fn scenario_sources_are_validated_and_normalized() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    // Case-insensitive input, canonical lowercase on the stored row.
+    let (status, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {}, "source": "Chatbot" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{scenario}");
+    assert_eq!(scenario["source"], "chatbot");
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "other": 1 }, "source": "email" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+}
+
+#[tokio::test]
+async fn bulk_delete_requires_a_filter_and_protects_used_scenarios() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let mut ids = Vec::new();
+    for day in ["2025-01-06", "2025-01-07", "2025-01-08"] {
+        let (status, scenario) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/scenarios"),
+            Some(json!({ "payload": {
+                "nurses": ["Alice"], "days": [day], "shifts": ["Morning"]
+            }})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        ids.push(scenario["scenario_id"].as_i64().unwrap());
+    }
+    // Backdate the first two; the third stays today and must survive the
+    // `before` filter.
+    sqlx::query("UPDATE scenarios SET created_at = '2025-02-01' WHERE scenario_id = ANY($1)")
+        .bind(&ids[..2])
+        .execute(&pool)
+        .await
+        .unwrap();
+    // The second backdated scenario is referenced by a run.
+    sqlx::query("INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded')")
+        .bind(ids[1])
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    // No filter at all is refused outright.
+    let (status, body) = req(&app, "DELETE", "/api/v1/scenarios", None).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+    // The used scenario matches, so without force nothing is deleted.
+    let (status, body) = req(
+        &app,
+        "DELETE",
+        &format!("/api/v1/scenarios?unit_id={unit_id}&status=ready&before=2026-01-01"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM scenarios")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 3);
+
+    // With force both backdated scenarios and the dependent run go.
+    let (status, body) = req(
+        &app,
+        "DELETE",
+        &format!("/api/v1/scenarios?unit_id={unit_id}&status=ready&before=2026-01-01&force=true"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["deleted_scenarios"], 2);
+    assert_eq!(body["deleted_runs"], 1);
+    let remaining: Vec<i64> = sqlx::query_scalar("SELECT scenario_id FROM scenarios")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(remaining, vec![ids[2]]);
+}
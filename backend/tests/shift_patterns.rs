@@ -0,0 +1,417 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{req, seed_org_and_unit, setup};
+
+#[tokio::test]
+async fn reorder_controls_list_order() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let mut ids = Vec::new();
+    for name in ["Night", "Morning", "Evening"] {
+        let (status, shift) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/shift-patterns"),
+            Some(json!({ "name": name, "start_time": "07:00:00", "end_time": "15:00:00" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        ids.push(shift["shift_id"].as_i64().unwrap());
+    }
+
+    // Natural planner order: Morning, Evening, Night.
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/shift-patterns/order"),
+        Some(json!({ "shift_ids": [ids[1], ids[2], ids[0]] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, shifts) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let names: Vec<&str> = shifts
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Morning", "Evening", "Night"]);
+}
+
+#[tokio::test]
+async fn three_shift_template_materializes_and_guards_existing() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (status, shifts) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns/from-template?template=three_shift"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let shifts = shifts.as_array().unwrap();
+    assert_eq!(shifts.len(), 3);
+    assert_eq!(shifts[0]["name"], "Morning");
+    assert_eq!(shifts[2]["name"], "Night");
+    assert_eq!(shifts[2]["is_night"], true);
+
+    // A second application without overwrite is refused.
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns/from-template?template=three_shift"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!(
+            "/api/v1/units/{unit_id}/shift-patterns/from-template?template=two_shift&overwrite=true"
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (_, listed) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        None,
+    )
+    .await;
+    assert_eq!(listed.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn reorder_rejects_foreign_shift_ids() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/shift-patterns/order"),
+        Some(json!({ "shift_ids": [999999] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn usage_report_counts_references_and_day_span() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    let (status, _) = req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-06", "shift_id": shift_id, "required_count": 2 },
+            { "day": "2025-01-07", "shift_id": shift_id, "required_count": 1 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/availability/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_id, "day": "2025-01-10", "shift_id": shift_id, "value": 1 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, usage) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/shift-patterns/{shift_id}/usage"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{usage}");
+    assert_eq!(usage["coverage_cells"], 2);
+    assert_eq!(usage["availability"], 1);
+    assert_eq!(usage["preferences"], 0);
+    assert_eq!(usage["assignments"], 0);
+    assert_eq!(usage["first_used"], "2025-01-06");
+    assert_eq!(usage["last_used"], "2025-01-10");
+
+    let (status, _) = req(&app, "GET", "/api/v1/shift-patterns/9999/usage", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn break_minutes_reduce_counted_hours() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+
+    // 8h shift with a 60-minute unpaid break counts as 7 worked hours.
+    let (status, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({
+            "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00",
+            "break_minutes": 60
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(shift["break_minutes"], 60);
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": ["Alice"], "days": ["2025-01-06"], "shifts": ["Morning"] } })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO assignments (run_id, staff_id, day, shift_id) VALUES ($1, $2, '2025-01-06', $3)",
+    )
+    .bind(run_id)
+    .bind(staff_id)
+    .bind(shift_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let (status, utilization) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/utilization"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(utilization.as_array().unwrap()[0]["assigned_hours"], 7.0);
+}
+
+#[tokio::test]
+async fn breaks_longer_than_the_shift_are_rejected() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({
+            "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00",
+            "break_minutes": 480
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    // Shrinking the shift below an existing break must also fail.
+    let (status, _) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/shift-patterns/{shift_id}"),
+        Some(json!({ "break_minutes": 30 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/shift-patterns/{shift_id}"),
+        Some(json!({ "end_time": "07:20:00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+    let (status, _) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/shift-patterns/{shift_id}"),
+        Some(json!({ "break_minutes": -5 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn duplicate_names_within_a_unit_are_rejected() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, first) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = first["shift_id"].as_i64().unwrap();
+
+    // Same name, different case: still ambiguous for the solver's mapping.
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "morning", "code": "M2", "start_time": "08:00:00", "end_time": "16:00:00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+
+    // Renaming onto an existing name is caught the same way.
+    let (_, second) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Evening", "start_time": "15:00:00", "end_time": "23:00:00" })),
+    )
+    .await;
+    let second_id = second["shift_id"].as_i64().unwrap();
+    let (status, _) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/shift-patterns/{second_id}"),
+        Some(json!({ "name": "MORNING" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // Patching a shift without renaming it does not trip over itself.
+    let (status, _) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/shift-patterns/{shift_id}"),
+        Some(json!({ "name": "Morning", "break_minutes": 15 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn overnight_and_zero_length_shift_times_are_handled() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let url = format!("/api/v1/units/{unit_id}/shift-patterns");
+
+    // Equal times are ambiguous and rejected outright.
+    let (status, body) = req(
+        &app,
+        "POST",
+        &url,
+        Some(json!({ "name": "Empty", "start_time": "08:00:00", "end_time": "08:00:00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+    // Crossing midnight without saying is_night auto-sets the flag, and the
+    // derived duration wraps instead of going negative.
+    let (status, night) = req(
+        &app,
+        "POST",
+        &url,
+        Some(json!({ "name": "Night", "start_time": "22:00:00", "end_time": "06:00:00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{night}");
+    assert_eq!(night["is_night"], true);
+    assert_eq!(night["duration_minutes"], 8 * 60);
+
+    // Explicitly denying is_night on a midnight-crossing shift is an error.
+    let (status, body) = req(
+        &app,
+        "POST",
+        &url,
+        Some(json!({
+            "name": "Confused", "start_time": "22:00:00", "end_time": "06:00:00",
+            "is_night": false
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+    // The derived duration subtracts the unpaid break.
+    let (status, day) = req(
+        &app,
+        "POST",
+        &url,
+        Some(json!({
+            "name": "Day", "start_time": "07:00:00", "end_time": "15:00:00",
+            "break_minutes": 30
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{day}");
+    assert_eq!(day["duration_minutes"], 8 * 60 - 30);
+
+    // Patching a day shift across midnight flips is_night too.
+    let (status, patched) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/shift-patterns/{}", day["shift_id"]),
+        Some(json!({ "start_time": "20:00:00", "end_time": "04:00:00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{patched}");
+    assert_eq!(patched["is_night"], true);
+    assert_eq!(patched["duration_minutes"], 8 * 60 - 30);
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+
+use common::{req, seed_org_and_unit, setup};
+
+/// Slow solver stub that records the order payloads arrive in; each call
+/// holds the single permit long enough for the queue to fill behind it.
+async fn spawn_slow_solver(order: Arc<Mutex<Vec<String>>>) -> String {
+    let router = Router::new().route(
+        "/solve",
+        post(move |Json(payload): Json<Value>| {
+            let order = order.clone();
+            async move {
+                let marker = payload["nurses"][0].as_str().unwrap_or("?").to_string();
+                order.lock().unwrap().push(marker);
+                tokio::time::sleep(std::time::Duration::from_millis(400)).await;
+                Json(json!({ "status": "OPTIMAL", "objective_value": 0 }))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{addr}")
+}
+
+/// Poll until `check` passes or a couple of seconds elapse.
+async fn wait_for(mut check: impl FnMut() -> bool) {
+    for _ in 0..200 {
+        if check() {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("condition not reached in time");
+}
+
+#[tokio::test]
+async fn high_priority_run_overtakes_the_queue() {
+    // This binary owns these env vars; a single permit forces queueing.
+    std::env::set_var("SOLVER_MAX_CONCURRENCY", "1");
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let solver_url = spawn_slow_solver(order.clone()).await;
+    std::env::set_var("FASTAPI_SOLVER_URL", &solver_url);
+
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let mut scenario_ids = Vec::new();
+    for marker in ["first", "low", "high"] {
+        let (status, scenario) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/scenarios"),
+            Some(json!({ "payload": {
+                "nurses": [marker], "days": ["2025-01-06"], "shifts": ["Morning"]
+            }})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        scenario_ids.push(scenario["scenario_id"].as_i64().unwrap());
+    }
+
+    let start_run = |scenario_id: i64, priority: i32| {
+        let app = app.clone();
+        tokio::spawn(async move {
+            req(
+                &app,
+                "POST",
+                &format!("/api/v1/scenarios/{scenario_id}/run"),
+                Some(json!({ "priority": priority })),
+            )
+            .await
+        })
+    };
+
+    // The first run takes the only permit and sits in the solver.
+    let first = start_run(scenario_ids[0], 0);
+    wait_for(|| order.lock().unwrap().len() == 1).await;
+
+    // A low-priority run queues behind it...
+    let low = start_run(scenario_ids[1], 0);
+    loop {
+        let (_, queue) = req(&app, "GET", "/api/v1/solver-runs/queue", None).await;
+        if queue.as_array().unwrap().len() == 1 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    // ...then a high-priority run arrives later but is first in line.
+    let high = start_run(scenario_ids[2], 10);
+    let queue = loop {
+        let (_, queue) = req(&app, "GET", "/api/v1/solver-runs/queue", None).await;
+        if queue.as_array().unwrap().len() == 2 {
+            break queue;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    };
+    assert_eq!(queue[0]["priority"], 10);
+    assert_eq!(queue[1]["priority"], 0);
+
+    for task in [first, low, high] {
+        let (status, run) = task.await.unwrap();
+        assert_eq!(status, StatusCode::CREATED, "{run}");
+        assert_eq!(run["status"], "succeeded");
+    }
+    // Arrival order at the solver: the permit freed by "first" went to
+    // "high" even though "low" had waited longer.
+    assert_eq!(*order.lock().unwrap(), vec!["first", "high", "low"]);
+}
@@ -0,0 +1,1845 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+
+use common::{req, req_with_headers, seed_org_and_unit, setup};
+
+/// `create_run` reads env vars (solver URL, own base URL), so tests that
+/// exercise the solve pipeline must not run concurrently.
+static ENV_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Stand-in for the FastAPI solver that always returns the given response.
+async fn spawn_solver(response: Value) -> String {
+    let router = Router::new().route(
+        "/solve",
+        post(move || {
+            let response = response.clone();
+            async move { Json(response) }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn failed_run_records_machine_readable_reason() {
+    let _guard = ENV_LOCK.lock().await;
+    // Nothing listens here, so the solver call fails.
+    std::env::set_var("FASTAPI_SOLVER_URL", "http://127.0.0.1:9");
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": [], "days": [], "shifts": [] } })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+
+    let (_, runs) = req(&app, "GET", &format!("/api/v1/units/{unit_id}/solver-runs"), None).await;
+    let run = &runs.as_array().unwrap()[0];
+    assert_eq!(run["status"], "failed");
+    assert_eq!(run["failure_reason"], "solver_error");
+    assert!(run["failure_detail"].as_str().unwrap().contains("unreachable"));
+}
+
+#[tokio::test]
+async fn utilization_reports_hours_against_contract() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice", "max_weekly_hours": 8 })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+
+    // Overnight shift: 23:00-07:00 spans midnight and must count as 8h.
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Night", "start_time": "23:00:00", "end_time": "07:00:00", "is_night": true })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": ["Alice"], "days": ["2025-01-06"], "shifts": ["Night"] } })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    for day in ["2025-01-06", "2025-01-07"] {
+        sqlx::query(
+            "INSERT INTO assignments (run_id, staff_id, day, shift_id) VALUES ($1, $2, $3::date, $4)",
+        )
+        .bind(run_id)
+        .bind(staff_id)
+        .bind(day)
+        .bind(shift_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let (status, utilization) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/utilization"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let rows = utilization.as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    // Two 8h night shifts against an 8h weekly contract in one ISO week.
+    assert_eq!(rows[0]["assigned_hours"], 16.0);
+    assert_eq!(rows[0]["max_weekly_hours"], 8);
+    assert_eq!(rows[0]["weeks"], 1);
+    assert_eq!(rows[0]["utilization_pct"], 200.0);
+    assert_eq!(rows[0]["over_contract"], true);
+}
+
+/// Like [`spawn_solver`] but also records the payload it was sent.
+async fn spawn_capturing_solver(
+    response: Value,
+    captured: std::sync::Arc<std::sync::Mutex<Option<Value>>>,
+) -> String {
+    let router = Router::new().route(
+        "/solve",
+        post(move |Json(payload): Json<Value>| {
+            let response = response.clone();
+            let captured = captured.clone();
+            async move {
+                *captured.lock().unwrap() = Some(payload);
+                Json(response)
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn empty_policy_weights_fall_back_to_defaults() {
+    let _guard = ENV_LOCK.lock().await;
+    let captured = std::sync::Arc::new(std::sync::Mutex::new(None));
+    let solver_url = spawn_capturing_solver(
+        json!({ "status": "OPTIMAL", "objective_value": 0 }),
+        captured.clone(),
+    )
+    .await;
+    std::env::set_var("FASTAPI_SOLVER_URL", &solver_url);
+
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (status, policy) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/policy-sets"),
+        Some(json!({ "name": "Blank", "weights": {} })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{policy}");
+    let policy_id = policy["policy_id"].as_i64().unwrap();
+
+    // The scenario pins one weight; the defaults must fill in the rest.
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {
+            "nurses": [], "days": [], "shifts": [],
+            "weights": { "overtime": 99 }
+        }})),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    let (status, run) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({ "policy_id": policy_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{run}");
+
+    let forwarded = captured.lock().unwrap().take().unwrap();
+    assert_eq!(forwarded["weights"]["understaffing"], 100);
+    assert_eq!(forwarded["weights"]["preference"], 1);
+    // The scenario's own value survives the merge.
+    assert_eq!(forwarded["weights"]["overtime"], 99);
+
+    // The effective set is recorded on the run for reproducibility.
+    assert_eq!(run["effective_weights"]["understaffing"], 100);
+    assert_eq!(run["effective_weights"]["overtime"], 99);
+}
+
+#[tokio::test]
+async fn source_breakdown_counts_deviation_from_model() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    // Three solver assignments and one manual override.
+    for (day, source) in [
+        ("2025-01-06", "MODEL"),
+        ("2025-01-07", "MODEL"),
+        ("2025-01-08", "MODEL"),
+        ("2025-01-09", "MANUAL"),
+    ] {
+        sqlx::query(
+            "INSERT INTO assignments (run_id, staff_id, day, shift_id, source)
+             VALUES ($1, $2, $3::date, $4, $5)",
+        )
+        .bind(run_id)
+        .bind(staff_id)
+        .bind(day)
+        .bind(shift_id)
+        .bind(source)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let (status, breakdown) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/source-breakdown"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{breakdown}");
+    assert_eq!(breakdown["total"], 4);
+    assert_eq!(breakdown["deviated_pct"], 25.0);
+    let sources = breakdown["sources"].as_array().unwrap();
+    assert_eq!(sources.len(), 2);
+    assert_eq!(sources[0]["source"], "MANUAL");
+    assert_eq!(sources[0]["count"], 1);
+    assert_eq!(sources[1]["source"], "MODEL");
+    assert_eq!(sources[1]["count"], 3);
+
+    // A run without assignments reports no deviation percentage.
+    let (empty_run,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let (_, breakdown) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{empty_run}/source-breakdown"),
+        None,
+    )
+    .await;
+    assert_eq!(breakdown["total"], 0);
+    assert!(breakdown["deviated_pct"].is_null());
+}
+
+#[tokio::test]
+async fn run_notes_are_attributed_and_listed() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": [], "days": [], "shifts": [] } })),
+    )
+    .await;
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario["scenario_id"].as_i64().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let (_, user) = req(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "full_name": "Planner", "password": "s3cret-pw" })),
+    )
+    .await;
+    let user_id = user["user_id"].as_i64().unwrap().to_string();
+
+    let (status, note) = req_with_headers(
+        &app,
+        "POST",
+        &format!("/api/v1/solver-runs/{run_id}/notes"),
+        Some(json!({ "note": "approved for July cycle" })),
+        &[("x-user-id", user_id.as_str())],
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(note["user_name"], "Planner");
+
+    // Anonymous notes are still accepted, just unattributed.
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/solver-runs/{run_id}/notes"),
+        Some(json!({ "note": "second pass" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (_, notes) = req(&app, "GET", &format!("/api/v1/solver-runs/{run_id}/notes"), None).await;
+    let notes = notes.as_array().unwrap();
+    assert_eq!(notes.len(), 2);
+    assert_eq!(notes[0]["note"], "approved for July cycle");
+    assert!(notes[1]["user_id"].is_null());
+}
+
+#[tokio::test]
+async fn on_call_hours_do_not_count_toward_contract() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice", "max_weekly_hours": 8 })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+
+    let (_, working) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let (_, on_call) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Standby", "start_time": "15:00:00", "end_time": "23:00:00", "is_on_call": true })),
+    )
+    .await;
+    assert_eq!(on_call["is_on_call"], true);
+
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": ["Alice"], "days": ["2025-01-06"], "shifts": [] } })),
+    )
+    .await;
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario["scenario_id"].as_i64().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    // One worked shift plus one on-call the same day.
+    for shift in [&working, &on_call] {
+        sqlx::query(
+            "INSERT INTO assignments (run_id, staff_id, day, shift_id) VALUES ($1, $2, '2025-01-06', $3)",
+        )
+        .bind(run_id)
+        .bind(staff_id)
+        .bind(shift["shift_id"].as_i64().unwrap())
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let (_, utilization) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/utilization"),
+        None,
+    )
+    .await;
+    let rows = utilization.as_array().unwrap();
+    // Only the worked 8h count; the on-call block leaves Alice at contract.
+    assert_eq!(rows[0]["assigned_hours"], 8.0);
+    assert_eq!(rows[0]["over_contract"], false);
+}
+
+#[tokio::test]
+async fn empty_nurse_stats_derives_satisfaction_from_preferences() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    // Alice penalizes the assigned cell with 30.
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/preferences/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "penalty": 30 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": ["Alice"], "days": ["2025-01-06"], "shifts": ["Morning"] } })),
+    )
+    .await;
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario["scenario_id"].as_i64().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/solver-runs/{run_id}/ingest-result"),
+        Some(json!({
+            "assignments": [ { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id } ],
+            "understaffed_cells": 0,
+            "nurse_stats": []
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, kpi) = req(&app, "GET", &format!("/api/v1/solver-runs/{run_id}/kpi"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    // 100 minus the 30-point penalty on the one assigned cell.
+    assert_eq!(kpi["avg_satisfaction"], 70.0);
+
+    // The compact summary carries the same headline without extra calls.
+    let (status, summary) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/summary"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["status"], "succeeded");
+    assert_eq!(summary["assignment_count"], 1);
+    assert_eq!(summary["understaffed_cells"], 0);
+    assert_eq!(summary["avg_satisfaction"], 70.0);
+}
+
+#[tokio::test]
+async fn summary_handles_runs_without_kpi() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": [], "days": [], "shifts": [] } })),
+    )
+    .await;
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id) VALUES ($1) RETURNING run_id",
+    )
+    .bind(scenario["scenario_id"].as_i64().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let (status, summary) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/summary"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(summary["status"], "queued");
+    assert_eq!(summary["assignment_count"], 0);
+    assert!(summary["avg_satisfaction"].is_null());
+}
+
+#[tokio::test]
+async fn renamed_shift_still_maps_via_code() {
+    let _guard = ENV_LOCK.lock().await;
+    // The solver answers with the shift *code*, not the current name.
+    let solver_url = spawn_solver(json!({
+        "status": "OPTIMAL",
+        "objective_value": 0,
+        "assignments": [ { "day": "2025-01-06", "shift": "MORNING", "nurse": "Alice" } ],
+        "understaffed": [],
+        "nurse_stats": []
+    }))
+    .await;
+    std::env::set_var("FASTAPI_SOLVER_URL", &solver_url);
+
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    assert_eq!(shift["code"], "MORNING");
+
+    // Rename the shift; its code stays stable.
+    let (status, renamed) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/shift-patterns/{shift_id}"),
+        Some(json!({ "name": "AM Shift" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(renamed["code"], "MORNING");
+
+    let (status, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {
+            "nurses": ["Alice"],
+            "days": ["2025-01-06"],
+            "shifts": ["AM Shift"],
+            "demand": { "2025-01-06": { "AM Shift": 1 } }
+        }})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    let (status, run) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "run failed: {run}");
+    assert_eq!(run["status"], "succeeded");
+
+    // Timestamps serialize as RFC3339 UTC with an explicit Z, and the run
+    // response carries the unit's time zone plus a local_day convenience.
+    let (status, fetched) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{}", run["run_id"].as_i64().unwrap()),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(fetched["created_at"].as_str().unwrap().ends_with('Z'));
+    assert_eq!(fetched["time_zone"], "UTC");
+    assert!(fetched["local_day"].is_string());
+
+    let (mapped_shift_id,): (i64,) =
+        sqlx::query_as("SELECT shift_id FROM assignments WHERE run_id = $1")
+            .bind(run["run_id"].as_i64().unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(mapped_shift_id, shift_id);
+}
+
+#[tokio::test]
+async fn consecutive_day_violations_use_policy_hard_rules() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    let (_, policy) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/policy-sets"),
+        Some(json!({ "name": "Rules", "hard_rules": { "max_consecutive_days": 3 } })),
+    )
+    .await;
+    let policy_id = policy["policy_id"].as_i64().unwrap();
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, policy_id, status) VALUES ($1, $2, 'succeeded')
+         RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .bind(policy_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    // Four consecutive days, a gap, then two more: only the first stretch
+    // breaks the limit of three.
+    for day in ["2025-01-06", "2025-01-07", "2025-01-08", "2025-01-09", "2025-01-11", "2025-01-12"] {
+        sqlx::query(
+            "INSERT INTO assignments (run_id, staff_id, day, shift_id) VALUES ($1, $2, $3::date, $4)",
+        )
+        .bind(run_id)
+        .bind(staff_id)
+        .bind(day)
+        .bind(shift_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let (status, report) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/consecutive-day-violations"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{report}");
+    assert_eq!(report["max_consecutive_days"], 3);
+    let violations = report["violations"].as_array().unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0]["start_day"], "2025-01-06");
+    assert_eq!(violations[0]["end_day"], "2025-01-09");
+    assert_eq!(violations[0]["length"], 4);
+
+    // A query override tightens the limit and catches the second stretch too.
+    let (_, report) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/consecutive-day-violations?limit=1"),
+        None,
+    )
+    .await;
+    assert_eq!(report["violations"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn check_only_run_records_verdict_without_assignments() {
+    let _guard = ENV_LOCK.lock().await;
+    // The solver answers with assignments, but a check must not persist them.
+    let solver_url = spawn_solver(json!({
+        "status": "OPTIMAL",
+        "objective_value": 12.5,
+        "assignments": [ { "day": "2025-01-06", "shift": "MORNING", "nurse": "Alice" } ]
+    }))
+    .await;
+    std::env::set_var("FASTAPI_SOLVER_URL", &solver_url);
+
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": [], "days": [], "shifts": [] } })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    let (status, run) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({ "check_only": true })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{run}");
+    assert_eq!(run["status"], "checked");
+    assert_eq!(run["solver_status"], "OPTIMAL");
+    assert_eq!(run["objective"], 12.5);
+
+    let run_id = run["run_id"].as_i64().unwrap();
+    let (assignments,): (i64,) =
+        sqlx::query_as("SELECT count(*) FROM assignments WHERE run_id = $1")
+            .bind(run_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(assignments, 0);
+    let (kpis,): (i64,) = sqlx::query_as("SELECT count(*) FROM kpi WHERE run_id = $1")
+        .bind(run_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(kpis, 0);
+}
+
+#[tokio::test]
+async fn single_assignment_lookup_resolves_names() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario["scenario_id"].as_i64().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let (assignment_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO assignments (run_id, staff_id, day, shift_id)
+         VALUES ($1, $2, '2025-01-06', $3) RETURNING assignment_id",
+    )
+    .bind(run_id)
+    .bind(staff_id)
+    .bind(shift_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let (status, found) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/assignments/{assignment_id}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{found}");
+    assert_eq!(found["full_name"], "Alice");
+    assert_eq!(found["shift_name"], "Morning");
+    assert_eq!(found["day"], "2025-01-06");
+    assert_eq!(found["source"], "MODEL");
+
+    let (status, _) = req(&app, "GET", "/api/v1/assignments/999999", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn over_limit_ingest_is_rejected_before_inserting() {
+    let _guard = ENV_LOCK.lock().await;
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'running') RETURNING run_id",
+    )
+    .bind(scenario["scenario_id"].as_i64().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    std::env::set_var("MAX_INGEST_ASSIGNMENTS", "2");
+    let assignments: Vec<_> = (0..3)
+        .map(|i| json!({ "staff_id": i, "day": "2025-01-06", "shift_id": 1 }))
+        .collect();
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/solver-runs/{run_id}/ingest-result"),
+        Some(json!({ "assignments": assignments, "understaffed_cells": 0 })),
+    )
+    .await;
+    std::env::remove_var("MAX_INGEST_ASSIGNMENTS");
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+    assert!(body["error"]["message"].as_str().unwrap().contains("limit"));
+
+    let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM assignments WHERE run_id = $1")
+        .bind(run_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn active_run_registry_reflects_terminal_state() {
+    let _guard = ENV_LOCK.lock().await;
+    let solver_url = spawn_solver(json!({ "status": "OPTIMAL", "objective_value": 0 })).await;
+    std::env::set_var("FASTAPI_SOLVER_URL", &solver_url);
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": [], "days": [], "shifts": [] } })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    // Keep the finished entry around long enough to observe it.
+    std::env::set_var("JOB_REGISTRY_TTL_SECS", "60");
+
+    let (status, run) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({})),
+    )
+    .await;
+    std::env::remove_var("JOB_REGISTRY_TTL_SECS");
+    assert_eq!(status, StatusCode::CREATED, "{run}");
+    let run_id = run["run_id"].as_i64().unwrap();
+
+    let (status, active) = req(&app, "GET", "/api/v1/solver-runs/active", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let active = active.as_array().unwrap();
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0]["run_id"].as_i64().unwrap(), run_id);
+    assert_eq!(active[0]["status"], "succeeded");
+    assert!(active[0]["started_at"].is_string());
+    assert_eq!(active[0]["attempt"], 1);
+}
+
+#[tokio::test]
+async fn oversized_worker_requests_are_clamped_with_a_warning() {
+    let _guard = ENV_LOCK.lock().await;
+    let captured = std::sync::Arc::new(std::sync::Mutex::new(None));
+    let solver_url = spawn_capturing_solver(
+        json!({ "status": "OPTIMAL", "objective_value": 0 }),
+        captured.clone(),
+    )
+    .await;
+    std::env::set_var("FASTAPI_SOLVER_URL", &solver_url);
+    std::env::set_var("SOLVER_MAX_WORKERS", "4");
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": [], "days": [], "shifts": [] } })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+
+    let (status, run) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({ "workers": 1000 })),
+    )
+    .await;
+    std::env::remove_var("SOLVER_MAX_WORKERS");
+    assert_eq!(status, StatusCode::CREATED, "{run}");
+
+    // The stored and forwarded value is the ceiling, and the response says so.
+    assert_eq!(run["workers"], 4);
+    assert!(
+        run["warning"].as_str().unwrap().contains("clamped from 1000 to 4"),
+        "{run}"
+    );
+    let forwarded = captured.lock().unwrap().take().unwrap();
+    assert_eq!(forwarded["workers"], 4);
+}
+
+#[tokio::test]
+async fn omitted_workers_default_to_the_configured_max() {
+    let _guard = ENV_LOCK.lock().await;
+    let solver_url = spawn_solver(json!({ "status": "OPTIMAL", "objective_value": 0 })).await;
+    std::env::set_var("FASTAPI_SOLVER_URL", &solver_url);
+    std::env::set_var("SOLVER_MAX_WORKERS", "2");
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": [], "days": [], "shifts": [] } })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+
+    let (status, run) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({})),
+    )
+    .await;
+    std::env::remove_var("SOLVER_MAX_WORKERS");
+    assert_eq!(status, StatusCode::CREATED, "{run}");
+    assert_eq!(run["workers"], 2);
+    assert!(run.get("warning").is_none(), "{run}");
+}
+
+#[tokio::test]
+async fn manual_reassignment_lands_in_the_run_history() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let mut staff_ids = Vec::new();
+    for code in ["N1", "N2"] {
+        let (_, staff) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/staffs"),
+            Some(json!({ "code": code, "full_name": code })),
+        )
+        .await;
+        staff_ids.push(staff["staff_id"].as_i64().unwrap());
+    }
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    let (_, user) = req(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "full_name": "Head Nurse", "password": "s3cret-pw" })),
+    )
+    .await;
+    let user_id = user["user_id"].as_i64().unwrap();
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO assignments (run_id, staff_id, day, shift_id) VALUES ($1, $2, '2025-01-06', $3)",
+    )
+    .bind(run_id)
+    .bind(staff_ids[0])
+    .bind(shift_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let (assignment_id,): (i64,) =
+        sqlx::query_as("SELECT assignment_id FROM assignments WHERE run_id = $1")
+            .bind(run_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    // Hand the shift to the other nurse; the row becomes a manual override.
+    let user_header = user_id.to_string();
+    let (status, moved) = common::req_with_headers(
+        &app,
+        "PATCH",
+        &format!("/api/v1/assignments/{assignment_id}"),
+        Some(json!({ "staff_id": staff_ids[1] })),
+        &[("x-user-id", &user_header)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{moved}");
+    assert_eq!(moved["staff_id"], staff_ids[1]);
+    assert_eq!(moved["source"], "MANUAL");
+
+    let (status, _) = req(
+        &app,
+        "DELETE",
+        &format!("/api/v1/assignments/{assignment_id}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, history) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/assignment-history"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{history}");
+    let entries = history.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["action"], "assignment.moved");
+    assert_eq!(entries[0]["actor_user_id"], user_id);
+    assert_eq!(entries[0]["actor_name"], "Head Nurse");
+    assert_eq!(entries[0]["detail"]["from"]["staff_id"], staff_ids[0]);
+    assert_eq!(entries[0]["detail"]["to"]["staff_id"], staff_ids[1]);
+    assert_eq!(entries[1]["action"], "assignment.deleted");
+    assert!(entries[1]["actor_user_id"].is_null());
+}
+
+#[tokio::test]
+async fn ambiguous_staff_names_fail_unless_a_strategy_resolves_them() {
+    let _guard = ENV_LOCK.lock().await;
+    let solver_url = spawn_solver(json!({
+        "status": "OPTIMAL",
+        "objective_value": 0,
+        "assignments": [ { "day": "2025-01-06", "shift": "MORNING", "nurse": "Alice" } ]
+    }))
+    .await;
+    std::env::set_var("FASTAPI_SOLVER_URL", &solver_url);
+
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    // Two nurses named Alice; only the second one is enabled.
+    let mut staff_ids = Vec::new();
+    for code in ["N1", "N2"] {
+        let (_, staff) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/staffs"),
+            Some(json!({ "code": code, "full_name": "Alice" })),
+        )
+        .await;
+        staff_ids.push(staff["staff_id"].as_i64().unwrap());
+    }
+    let (_, _) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/staffs/{}", staff_ids[0]),
+        Some(json!({ "is_enabled": false })),
+    )
+    .await;
+    let (_, _shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": [], "days": [], "shifts": [] } })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    // Default strategy: the ambiguity fails the run with a clear message.
+    let (status, error) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(
+        error["error"]["message"].as_str().unwrap().contains("ambiguous"),
+        "{error}"
+    );
+
+    // prefer_enabled picks the single enabled Alice.
+    std::env::set_var("AMBIGUOUS_NAME_STRATEGY", "prefer_enabled");
+    let (status, run) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({})),
+    )
+    .await;
+    std::env::remove_var("AMBIGUOUS_NAME_STRATEGY");
+    assert_eq!(status, StatusCode::CREATED, "{run}");
+    let (assigned,): (i64,) = sqlx::query_as(
+        "SELECT staff_id FROM assignments WHERE run_id = $1",
+    )
+    .bind(run["run_id"].as_i64().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(assigned, staff_ids[1]);
+}
+
+#[tokio::test]
+async fn staff_filter_prunes_the_forwarded_nurse_list() {
+    let _guard = ENV_LOCK.lock().await;
+    let captured = std::sync::Arc::new(std::sync::Mutex::new(None));
+    let solver_url = spawn_capturing_solver(
+        json!({ "status": "OPTIMAL", "objective_value": 0 }),
+        captured.clone(),
+    )
+    .await;
+    std::env::set_var("FASTAPI_SOLVER_URL", &solver_url);
+
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let mut staff_ids = Vec::new();
+    for (code, name, enabled) in [
+        ("N1", "Alice", true),
+        ("N2", "Bob", false),
+        ("N3", "Cara", true),
+    ] {
+        let (_, staff) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/staffs"),
+            Some(json!({ "code": code, "full_name": name })),
+        )
+        .await;
+        let staff_id = staff["staff_id"].as_i64().unwrap();
+        if !enabled {
+            let (_, _) = req(
+                &app,
+                "PATCH",
+                &format!("/api/v1/staffs/{staff_id}"),
+                Some(json!({ "is_enabled": false })),
+            )
+            .await;
+        }
+        staff_ids.push(staff_id);
+    }
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {
+            "nurses": ["Alice", "Bob", "Cara"], "days": [], "shifts": []
+        }})),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    // enabled_only (the default) drops the disabled Bob, and exclude drops Cara.
+    let (status, run) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({ "staff_filter": { "exclude": [staff_ids[2]] } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{run}");
+    let forwarded = captured.lock().unwrap().take().unwrap();
+    assert_eq!(forwarded["nurses"], json!(["Alice"]));
+
+    // include forces a normally-disabled nurse back into scope.
+    let (status, run) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({ "staff_filter": { "include": [staff_ids[1]] } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{run}");
+    let forwarded = captured.lock().unwrap().take().unwrap();
+    assert_eq!(forwarded["nurses"], json!(["Alice", "Bob", "Cara"]));
+
+    // Ids from another unit are rejected before anything runs.
+    let (status, error) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/scenarios/{scenario_id}/run"),
+        Some(json!({ "staff_filter": { "exclude": [9999] } })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(
+        error["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("does not belong"),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+async fn unassigning_a_staff_opens_future_cells_only() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    // One worked day and two future days (the seeded unit has no site, so
+    // "today" is the UTC day).
+    let today = chrono::Utc::now().date_naive();
+    for offset in [-1i64, 1, 2] {
+        sqlx::query(
+            "INSERT INTO assignments (run_id, staff_id, day, shift_id) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(run_id)
+        .bind(staff_id)
+        .bind(today + chrono::Duration::days(offset))
+        .bind(shift_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/solver-runs/{run_id}/unassign-staff"),
+        Some(json!({ "staff_id": staff_id, "from_day": today - chrono::Duration::days(1) })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/solver-runs/{run_id}/unassign-staff"),
+        Some(json!({ "staff_id": staff_id, "from_day": today })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["removed"], 2);
+    let cells = body["open_cells"].as_array().unwrap();
+    assert_eq!(cells.len(), 2);
+    assert_eq!(cells[0]["shift_id"], shift_id);
+
+    // Yesterday's assignment survives; the bulk lift is in the history.
+    let (remaining,): (i64,) =
+        sqlx::query_as("SELECT count(*) FROM assignments WHERE run_id = $1")
+            .bind(run_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(remaining, 1);
+    let (_, history) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/assignment-history"),
+        None,
+    )
+    .await;
+    let entries = history.as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["action"], "assignment.staff_unassigned");
+}
+
+#[tokio::test]
+async fn bundle_is_a_self_contained_run_export() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    let (_, policy) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/policy-sets"),
+        Some(json!({ "name": "Default", "weights": { "overtime": 42 } })),
+    )
+    .await;
+    let policy_id = policy["policy_id"].as_i64().unwrap();
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": ["Alice"] } })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, policy_id, status) VALUES ($1, $2, 'succeeded')
+         RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .bind(policy_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        "INSERT INTO assignments (run_id, staff_id, day, shift_id) VALUES ($1, $2, '2025-01-06', $3)",
+    )
+    .bind(run_id)
+    .bind(staff_id)
+    .bind(shift_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO kpi (run_id, total_assignments) VALUES ($1, 1)")
+        .bind(run_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, bundle) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/bundle"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{bundle}");
+    assert_eq!(bundle["bundle_version"], 1);
+    assert_eq!(bundle["run"]["run_id"], run_id);
+    assert_eq!(bundle["scenario"]["payload"]["nurses"][0], "Alice");
+    assert_eq!(bundle["policy"]["weights"]["overtime"], 42);
+    assert_eq!(bundle["kpi"]["total_assignments"], 1);
+    let assignments = bundle["assignments"].as_array().unwrap();
+    assert_eq!(assignments.len(), 1);
+    assert_eq!(assignments[0]["staff_name"], "Alice");
+    assert_eq!(assignments[0]["shift_code"], "MORNING");
+
+    let (status, _) = req(&app, "GET", "/api/v1/solver-runs/9999/bundle", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn compare_builds_a_kpi_matrix_ordered_by_finish() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    let mut run_ids = Vec::new();
+    // Finish times deliberately out of insertion order.
+    for (hour, understaffed) in [("12", 3), ("10", 1), ("11", 2)] {
+        let (run_id,): (i64,) = sqlx::query_as(
+            "INSERT INTO solver_runs (scenario_id, status, finished_at)
+             VALUES ($1, 'succeeded', ('2025-01-06T' || $2 || ':00:00Z')::timestamptz)
+             RETURNING run_id",
+        )
+        .bind(scenario_id)
+        .bind(hour)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO kpi (run_id, understaffed_cells) VALUES ($1, $2)")
+            .bind(run_id)
+            .bind(understaffed)
+            .execute(&pool)
+            .await
+            .unwrap();
+        run_ids.push(run_id);
+    }
+
+    let (status, matrix) = req(
+        &app,
+        "POST",
+        "/api/v1/solver-runs/compare",
+        Some(json!({ "run_ids": run_ids })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{matrix}");
+    assert_eq!(matrix["unit_id"], unit_id);
+    let runs = matrix["runs"].as_array().unwrap();
+    assert_eq!(runs.len(), 3);
+    // Ordered by finished_at: 10:00, 11:00, 12:00.
+    assert_eq!(runs[0]["understaffed_cells"], 1);
+    assert_eq!(runs[1]["understaffed_cells"], 2);
+    assert_eq!(runs[2]["understaffed_cells"], 3);
+
+    // Runs of different units cannot be meaningfully compared.
+    let (_, org2) = req(
+        &app,
+        "POST",
+        "/api/v1/organizations",
+        Some(json!({ "name": "Other Hospital" })),
+    )
+    .await;
+    let org2_id = org2["organization_id"].as_i64().unwrap();
+    let (_, unit2) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org2_id}/units"),
+        Some(json!({ "name": "Ward B" })),
+    )
+    .await;
+    let unit2_id = unit2["unit_id"].as_i64().unwrap();
+    let (_, scenario2) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit2_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let (foreign_run,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario2["scenario_id"].as_i64().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let (status, body) = req(
+        &app,
+        "POST",
+        "/api/v1/solver-runs/compare",
+        Some(json!({ "run_ids": [run_ids[0], foreign_run] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+}
+
+#[tokio::test]
+async fn postfill_strategies_pick_different_staff_for_the_same_gap() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let mut staff_ids = Vec::new();
+    for code in ["S1", "S2", "S3"] {
+        let (_, staff) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/staffs"),
+            Some(json!({ "code": code, "full_name": code, "max_weekly_hours": 40 })),
+        )
+        .await;
+        staff_ids.push(staff["staff_id"].as_i64().unwrap());
+    }
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    // One open slot: nobody works the 5th yet.
+    req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-03-05", "shift_id": shift_id, "required_count": 1 }
+        ]})),
+    )
+    .await;
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+
+    // Same history in every run: S1 carries two shifts, S2 one (inserted
+    // before S1's, so S1 holds the latest assignment), S3 none.
+    let mut run_ids = Vec::new();
+    for _ in 0..3 {
+        let (run_id,): (i64,) = sqlx::query_as(
+            "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+        )
+        .bind(scenario_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        for (staff_id, day) in [
+            (staff_ids[1], "2025-03-02"),
+            (staff_ids[0], "2025-03-02"),
+            (staff_ids[0], "2025-03-03"),
+        ] {
+            sqlx::query(
+                "INSERT INTO assignments (run_id, staff_id, day, shift_id) VALUES ($1, $2, $3::date, $4)",
+            )
+            .bind(run_id)
+            .bind(staff_id)
+            .bind(day)
+            .bind(shift_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        run_ids.push(run_id);
+    }
+
+    let mut picked = Vec::new();
+    for (run_id, strategy) in run_ids
+        .iter()
+        .zip(["least_loaded", "seniority", "round_robin"])
+    {
+        let (status, result) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/solver-runs/{run_id}/postfill"),
+            Some(json!({ "strategy": strategy })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{result}");
+        assert_eq!(result["strategy"], strategy);
+        assert_eq!(result["filled"], 1, "{result}");
+        assert!(result["unfilled"].as_array().unwrap().is_empty());
+        let (staff_id,): (i64,) = sqlx::query_as(
+            "SELECT staff_id FROM assignments WHERE run_id = $1 AND source = 'POSTFILL'",
+        )
+        .bind(run_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        picked.push(staff_id);
+    }
+    // Least-loaded takes idle S3, seniority the longest-serving S1, and
+    // round robin continues past S1's latest assignment to S2.
+    assert_eq!(picked, vec![staff_ids[2], staff_ids[0], staff_ids[1]]);
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/solver-runs/{}/postfill", run_ids[0]),
+        Some(json!({ "strategy": "alphabetical" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+}
+
+#[tokio::test]
+async fn ingest_validates_and_normalizes_assignment_sources() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'running') RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    // An unknown source rejects the whole batch before anything lands.
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/solver-runs/{run_id}/ingest-result"),
+        Some(json!({
+            "assignments": [
+                { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id },
+                { "staff_id": staff_id, "day": "2025-01-07", "shift_id": shift_id, "source": "ROBOT" }
+            ],
+            "understaffed_cells": 0
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+    let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM assignments WHERE run_id = $1")
+        .bind(run_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    // Lowercase input is accepted and stored in canonical uppercase.
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/solver-runs/{run_id}/ingest-result"),
+        Some(json!({
+            "assignments": [
+                { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "source": "manual" }
+            ],
+            "understaffed_cells": 0
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (source,): (String,) =
+        sqlx::query_as("SELECT source FROM assignments WHERE run_id = $1")
+            .bind(run_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(source, "MANUAL");
+}
+
+#[tokio::test]
+async fn daily_summary_rolls_up_assigned_versus_required_per_day() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    let mut staff_ids = Vec::new();
+    for (code, name) in [("N1", "Alice"), ("N2", "Bob")] {
+        let (_, staff) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/staffs"),
+            Some(json!({ "code": code, "full_name": name })),
+        )
+        .await;
+        staff_ids.push(staff["staff_id"].as_i64().unwrap());
+    }
+    // Demand: 2 heads on the 6th, 1 on the 7th, none on the 8th.
+    req(
+        &app,
+        "PUT",
+        &format!("/api/v1/units/{unit_id}/coverage"),
+        Some(json!({ "items": [
+            { "day": "2025-01-06", "shift_id": shift_id, "required_count": 2 },
+            { "day": "2025-01-07", "shift_id": shift_id, "required_count": 1 }
+        ]})),
+    )
+    .await;
+
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {
+            "nurses": ["Alice", "Bob"],
+            "days": ["2025-01-06", "2025-01-07", "2025-01-08"],
+            "shifts": []
+        }})),
+    )
+    .await;
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario["scenario_id"].as_i64().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    // Both staff on the 6th, nobody on the 7th, Alice alone on the 8th.
+    for (staff_id, day) in [
+        (staff_ids[0], "2025-01-06"),
+        (staff_ids[1], "2025-01-06"),
+        (staff_ids[0], "2025-01-08"),
+    ] {
+        sqlx::query(
+            "INSERT INTO assignments (run_id, staff_id, day, shift_id) VALUES ($1, $2, $3::date, $4)",
+        )
+        .bind(run_id)
+        .bind(staff_id)
+        .bind(day)
+        .bind(shift_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+
+    let (status, body) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/daily-summary"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let rows = body.as_array().unwrap();
+    // Every horizon day appears, including the uncovered empty 7th.
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0]["day"], "2025-01-06");
+    assert_eq!(rows[0]["assigned"], 2);
+    assert_eq!(rows[0]["required"], 2);
+    assert_eq!(rows[0]["shortfall"], 0);
+    assert_eq!(rows[1]["assigned"], 0);
+    assert_eq!(rows[1]["required"], 1);
+    assert_eq!(rows[1]["shortfall"], 1);
+    assert_eq!(rows[2]["assigned"], 1);
+    assert_eq!(rows[2]["required"], 0);
+    assert_eq!(rows[2]["shortfall"], 0);
+
+    let (status, _) = req(&app, "GET", "/api/v1/solver-runs/999999/daily-summary", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
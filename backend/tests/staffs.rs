@@ -0,0 +1,347 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{req, seed_org_and_unit, setup};
+
+async fn seed_staff(app: &axum::Router, unit_id: i64, code: &str) -> i64 {
+    let (status, staff) = req(
+        app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": code, "full_name": code })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    staff["staff_id"].as_i64().unwrap()
+}
+
+#[tokio::test]
+async fn transfer_moves_staff_and_clears_inputs() {
+    let (app, _pool) = setup().await;
+    let (org_id, unit_a) = seed_org_and_unit(&app).await;
+    let (_, unit_b) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "Ward B" })),
+    )
+    .await;
+    let unit_b = unit_b["unit_id"].as_i64().unwrap();
+
+    let staff_id = seed_staff(&app, unit_a, "N1").await;
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_a}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/availability/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_id, "value": 0 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, result) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_b}/staffs/transfer"),
+        Some(json!({ "staff_ids": [staff_id] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{result}");
+    assert_eq!(result["transferred"], 1);
+    // The old unit's availability rows are dropped, not carried over.
+    assert_eq!(result["cleared_inputs"], 1);
+
+    let (_, staff) = req(&app, "GET", &format!("/api/v1/staffs/{staff_id}"), None).await;
+    assert_eq!(staff["unit_id"], unit_b);
+    let (_, roster) = req(&app, "GET", &format!("/api/v1/units/{unit_a}/staffs"), None).await;
+    assert_eq!(roster.as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn transfer_rejects_code_collision_atomically() {
+    let (app, _pool) = setup().await;
+    let (org_id, unit_a) = seed_org_and_unit(&app).await;
+    let (_, unit_b) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "Ward B" })),
+    )
+    .await;
+    let unit_b = unit_b["unit_id"].as_i64().unwrap();
+
+    let movable = seed_staff(&app, unit_a, "N1").await;
+    let colliding = seed_staff(&app, unit_a, "N2").await;
+    seed_staff(&app, unit_b, "N2").await;
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_b}/staffs/transfer"),
+        Some(json!({ "staff_ids": [movable, colliding] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    assert!(body["error"]["message"].as_str().unwrap().contains("N2"));
+
+    // Nothing moved: the transfer is all-or-nothing.
+    let (_, staff) = req(&app, "GET", &format!("/api/v1/staffs/{movable}"), None).await;
+    assert_eq!(staff["unit_id"], unit_a);
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_b}/staffs/transfer"),
+        Some(json!({ "staff_ids": [movable, 999999] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+}
+
+#[tokio::test]
+async fn misspelled_body_fields_are_rejected() {
+    let (app, _pool) = setup().await;
+    let (org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice", "max_weekly_hrs": 40 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+    assert!(body.as_str().unwrap().contains("max_weekly_hrs"));
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "Ward B", "site": 1 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+    assert!(body.as_str().unwrap().contains("site"));
+
+    let staff_id = seed_staff(&app, unit_id, "N1").await;
+    let (status, _) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/staffs/{staff_id}"),
+        Some(json!({ "enabled": false })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn profile_aggregates_latest_run_and_availability() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    let staff_id = seed_staff(&app, unit_id, "N1").await;
+    let (_, _) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/staffs/{staff_id}"),
+        Some(json!({ "max_weekly_hours": 16 })),
+    )
+    .await;
+
+    let mut shift_ids = Vec::new();
+    for (name, start, end, is_night) in [
+        ("Morning", "07:00:00", "15:00:00", false),
+        ("Night", "23:00:00", "07:00:00", true),
+    ] {
+        let (_, shift) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/shift-patterns"),
+            Some(json!({
+                "name": name, "start_time": start, "end_time": end, "is_night": is_night
+            })),
+        )
+        .await;
+        shift_ids.push(shift["shift_id"].as_i64().unwrap());
+    }
+
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": {} })),
+    )
+    .await;
+    let scenario_id = scenario["scenario_id"].as_i64().unwrap();
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    // Two day shifts and a night in one ISO week: 24h against a 16h contract.
+    for (day, shift_id) in [
+        ("2025-01-06", shift_ids[0]),
+        ("2025-01-07", shift_ids[0]),
+        ("2025-01-08", shift_ids[1]),
+    ] {
+        sqlx::query(
+            "INSERT INTO assignments (run_id, staff_id, day, shift_id) VALUES ($1, $2, $3::date, $4)",
+        )
+        .bind(run_id)
+        .bind(staff_id)
+        .bind(day)
+        .bind(shift_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    // 3 of the 6 (day, shift) cells in the window have availability entries.
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/availability/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_id, "day": "2025-01-06", "shift_id": shift_ids[0], "value": 1 },
+            { "staff_id": staff_id, "day": "2025-01-07", "shift_id": shift_ids[0], "value": 0 },
+            { "staff_id": staff_id, "day": "2025-01-08", "shift_id": shift_ids[1], "value": 1 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, profile) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/staffs/{staff_id}/profile?from=2025-01-06&to=2025-01-08"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{profile}");
+    assert_eq!(profile["staff_id"], staff_id);
+    assert_eq!(profile["code"], "N1");
+    assert_eq!(profile["run_id"], run_id);
+    assert_eq!(profile["total_assignments"], 3);
+    assert_eq!(profile["nights_worked"], 1);
+    assert_eq!(profile["assigned_hours"], 24.0);
+    assert_eq!(profile["overtime_hours"], 8.0);
+    assert_eq!(profile["availability_completeness_pct"], 50.0);
+
+    // A staff with no published run still gets the zeroed aggregates.
+    let lonely_id = seed_staff(&app, unit_id, "N2").await;
+    let (status, profile) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/staffs/{lonely_id}/profile?from=2025-01-06&to=2025-01-08"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{profile}");
+    assert_eq!(profile["total_assignments"], 0);
+    assert_eq!(profile["availability_completeness_pct"], 0.0);
+}
+
+#[tokio::test]
+async fn roster_reports_availability_counts_per_staff() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let mut staff_ids = Vec::new();
+    for (code, name) in [("N1", "Alice"), ("N2", "Bob")] {
+        let (_, staff) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/staffs"),
+            Some(json!({ "code": code, "full_name": name })),
+        )
+        .await;
+        staff_ids.push(staff["staff_id"].as_i64().unwrap());
+    }
+    // Disabled staff stay off the roster entirely.
+    let (_, carol) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N3", "full_name": "Carol" })),
+    )
+    .await;
+    let carol_id = carol["staff_id"].as_i64().unwrap();
+    req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/staffs/{carol_id}"),
+        Some(json!({ "is_enabled": false })),
+    )
+    .await;
+
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/availability/bulk",
+        Some(json!({ "items": [
+            { "staff_id": staff_ids[0], "day": "2025-01-06", "shift_id": shift_id, "value": 1 },
+            { "staff_id": staff_ids[0], "day": "2025-01-07", "shift_id": shift_id, "value": 1 },
+            // value 0 means "cannot work" and must not count.
+            { "staff_id": staff_ids[1], "day": "2025-01-06", "shift_id": shift_id, "value": 0 },
+            // Outside the queried window.
+            { "staff_id": staff_ids[0], "day": "2025-02-01", "shift_id": shift_id, "value": 1 }
+        ]})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, roster) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/roster?from=2025-01-06&to=2025-01-12"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{roster}");
+    let rows = roster.as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["full_name"], "Alice");
+    assert_eq!(rows[0]["available_cells"], 2);
+    assert_eq!(rows[1]["full_name"], "Bob");
+    assert_eq!(rows[1]["available_cells"], 0);
+}
+
+#[tokio::test]
+async fn validation_reports_every_bad_field_at_once() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "  ", "full_name": "Alice", "max_weekly_hours": 0 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+    assert_eq!(body["error"], "validation failed");
+    let fields = body["fields"].as_object().unwrap();
+    assert_eq!(fields.len(), 2);
+    assert!(fields.contains_key("code"));
+    assert!(fields.contains_key("max_weekly_hours"));
+}
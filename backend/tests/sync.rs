@@ -0,0 +1,83 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{req, seed_org_and_unit, setup};
+
+#[tokio::test]
+async fn changes_since_returns_only_later_edits_and_tombstones() {
+    let (app, _pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, alice) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let alice_id = alice["staff_id"].as_i64().unwrap();
+    let (_, bob) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N2", "full_name": "Bob" })),
+    )
+    .await;
+    let bob_id = bob["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    // A full sync from the epoch sees everything and yields a cursor.
+    let (status, full) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/changes?since=1970-01-01T00:00:00Z"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{full}");
+    assert_eq!(full["staffs"].as_array().unwrap().len(), 2);
+    assert_eq!(full["shift_patterns"].as_array().unwrap().len(), 1);
+    assert_eq!(full["tombstones"].as_array().unwrap().len(), 0);
+    let cursor = full["now"].as_str().unwrap().to_string();
+
+    // Only Bob changes after the cursor; the shift is deleted.
+    let (status, _) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/staffs/{bob_id}"),
+        Some(json!({ "full_name": "Robert" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = req(&app, "DELETE", &format!("/api/v1/shift-patterns/{shift_id}"), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, delta) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/changes?since={cursor}"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{delta}");
+    let staffs = delta["staffs"].as_array().unwrap();
+    assert_eq!(staffs.len(), 1);
+    assert_eq!(staffs[0]["staff_id"].as_i64().unwrap(), bob_id);
+    assert_eq!(staffs[0]["full_name"], "Robert");
+    assert_eq!(delta["shift_patterns"].as_array().unwrap().len(), 0);
+    let tombstones = delta["tombstones"].as_array().unwrap();
+    assert_eq!(tombstones.len(), 1);
+    assert_eq!(tombstones[0]["entity_type"], "shift_pattern");
+    assert_eq!(tombstones[0]["entity_id"].as_i64().unwrap(), shift_id);
+    // Alice is untouched and absent from the delta.
+    assert!(staffs.iter().all(|s| s["staff_id"].as_i64() != Some(alice_id)));
+}
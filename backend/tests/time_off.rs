@@ -0,0 +1,97 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{req, seed_org_and_unit, setup};
+
+#[tokio::test]
+async fn timeoff_violations_and_strict_ingest() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    let (_, staff) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/staffs"),
+        Some(json!({ "code": "N1", "full_name": "Alice" })),
+    )
+    .await;
+    let staff_id = staff["staff_id"].as_i64().unwrap();
+    let (_, shift) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    let shift_id = shift["shift_id"].as_i64().unwrap();
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/staffs/{staff_id}/time-off"),
+        Some(json!({ "start_day": "2025-01-06", "end_day": "2025-01-10", "reason": "vacation" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (_, scenario) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/scenarios"),
+        Some(json!({ "payload": { "nurses": ["Alice"], "days": [], "shifts": [] } })),
+    )
+    .await;
+    let (run_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO solver_runs (scenario_id, status) VALUES ($1, 'succeeded') RETURNING run_id",
+    )
+    .bind(scenario["scenario_id"].as_i64().unwrap())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    // Strict ingest refuses an assignment inside the approved range and
+    // persists nothing.
+    let body = json!({
+        "assignments": [ { "staff_id": staff_id, "day": "2025-01-07", "shift_id": shift_id } ],
+        "understaffed_cells": 0,
+        "nurse_stats": []
+    });
+    let (status, detail) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/solver-runs/{run_id}/ingest-result?strict=true"),
+        Some(body.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{detail}");
+    let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM assignments WHERE run_id = $1")
+        .bind(run_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+
+    // Default ingest accepts it but the violation report flags it.
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/solver-runs/{run_id}/ingest-result"),
+        Some(body),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, violations) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/solver-runs/{run_id}/timeoff-violations"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let violations = violations.as_array().unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0]["full_name"], "Alice");
+    assert_eq!(violations[0]["day"], "2025-01-07");
+}
@@ -0,0 +1,395 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{req, req_with_headers, seed_org_and_unit, setup};
+
+#[tokio::test]
+async fn create_unit_rejects_site_from_another_org() {
+    let (app, _pool) = setup().await;
+    let (org_a, _unit) = seed_org_and_unit(&app).await;
+    let (status, org) = req(
+        &app,
+        "POST",
+        "/api/v1/organizations",
+        Some(json!({ "name": "Other Hospital" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let org_b = org["organization_id"].as_i64().unwrap();
+    let (status, site) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_b}/sites"),
+        Some(json!({ "name": "Other Campus" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let foreign_site = site["site_id"].as_i64().unwrap();
+
+    // Org A must not adopt org B's site.
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_a}/units"),
+        Some(json!({ "name": "Ward X", "site_id": foreign_site })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("belongs to organization"));
+}
+
+#[tokio::test]
+async fn create_site_rejects_unknown_or_inactive_org() {
+    let (app, _pool) = setup().await;
+    let (status, body) = req(
+        &app,
+        "POST",
+        "/api/v1/organizations/9999/sites",
+        Some(json!({ "name": "Ghost Campus" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("does not exist"));
+
+    // A suspended org can't grow new sites either.
+    let (org_id, _unit_id) = seed_org_and_unit(&app).await;
+    let (status, _) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/organizations/{org_id}"),
+        Some(json!({ "status": "suspended" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/sites"),
+        Some(json!({ "name": "New Campus" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("suspended"));
+}
+
+#[tokio::test]
+async fn list_units_expand_site_joins_site_fields() {
+    let (app, _pool) = setup().await;
+    let (org_id, _unit_id) = seed_org_and_unit(&app).await;
+
+    let (status, site) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/sites"),
+        Some(json!({ "name": "Main Campus", "time_zone": "Asia/Bangkok" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let site_id = site["site_id"].as_i64().unwrap();
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "Ward B", "site_id": site_id })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, body) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/organizations/{org_id}/units?expand=site"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let units = body.as_array().unwrap();
+    assert_eq!(units.len(), 2);
+    // Ward A has no site: fields present but null, row not dropped.
+    assert!(units[0]["site_name"].is_null());
+    assert!(units[0]["site_time_zone"].is_null());
+    // Ward B resolves its site.
+    assert_eq!(units[1]["site_name"], "Main Campus");
+    assert_eq!(units[1]["site_time_zone"], "Asia/Bangkok");
+}
+
+#[tokio::test]
+async fn list_units_default_shape_has_no_site_fields() {
+    let (app, _pool) = setup().await;
+    let (org_id, _unit_id) = seed_org_and_unit(&app).await;
+    let (status, body) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let units = body.as_array().unwrap();
+    assert_eq!(units.len(), 1);
+    assert!(units[0].get("site_name").is_none());
+}
+
+#[tokio::test]
+async fn dry_run_unit_delete_reports_dependents_untouched() {
+    let (app, pool) = setup().await;
+    let (_org_id, unit_id) = seed_org_and_unit(&app).await;
+    for code in ["N1", "N2"] {
+        let (_, _) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/staffs"),
+            Some(json!({ "code": code, "full_name": code })),
+        )
+        .await;
+    }
+    let (_, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+
+    let (status, preview) = req(
+        &app,
+        "DELETE",
+        &format!("/api/v1/units/{unit_id}?dry_run=true"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{preview}");
+    assert_eq!(preview["dry_run"], true);
+    assert_eq!(preview["staffs"], 2);
+    assert_eq!(preview["shift_patterns"], 1);
+
+    let (staffs,): (i64,) = sqlx::query_as("SELECT count(*) FROM staffs")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(staffs, 2);
+}
+
+#[tokio::test]
+async fn suspended_org_blocks_writes_except_for_superadmin() {
+    let (app, _pool) = setup().await;
+
+    let (_, org) = req(
+        &app,
+        "POST",
+        "/api/v1/organizations",
+        Some(json!({ "name": "Closed Hospital", "status": "suspended" })),
+    )
+    .await;
+    let org_id = org["organization_id"].as_i64().unwrap();
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "Ward A" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+
+    // Reads stay open.
+    let (status, _) = req(&app, "GET", &format!("/api/v1/organizations/{org_id}/units"), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // An org-less admin (superadmin) may still write.
+    let (_, admin) = req(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "full_name": "Root", "role": "admin", "password": "s3cret-pw" })),
+    )
+    .await;
+    let admin_id = admin["user_id"].as_i64().unwrap().to_string();
+    let (status, body) = req_with_headers(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "Ward A" })),
+        &[("X-User-Id", &admin_id)],
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{body}");
+}
+
+#[tokio::test]
+async fn planning_granularity_gates_shift_definitions() {
+    let (app, _pool) = setup().await;
+    let (org_id, _unit_id) = seed_org_and_unit(&app).await;
+
+    let (status, unit) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "ICU", "planning_granularity": "hour" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED, "{unit}");
+    let unit_id = unit["unit_id"].as_i64().unwrap();
+
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Odd", "start_time": "07:30:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+    let (status, _) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Morning", "start_time": "07:00:00", "end_time": "15:00:00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // Shift-based planning refuses overlapping definitions instead.
+    let (status, _) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/units/{unit_id}"),
+        Some(json!({ "planning_granularity": "shift" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/units/{unit_id}/shift-patterns"),
+        Some(json!({ "name": "Late Morning", "start_time": "14:00:00", "end_time": "22:00:00" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+
+    let (status, body) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/units/{unit_id}"),
+        Some(json!({ "planning_granularity": "fortnight" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+}
+
+#[tokio::test]
+async fn config_issues_flag_misaligned_and_overlapping_shifts() {
+    let (app, pool) = setup().await;
+    let (org_id, _) = seed_org_and_unit(&app).await;
+    let (_, unit) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "ICU" })),
+    )
+    .await;
+    let unit_id = unit["unit_id"].as_i64().unwrap();
+
+    // Two shifts that slipped in while no granularity was enforced.
+    for (name, start, end) in [("Odd", "07:30:00", "15:00:00"), ("Day", "07:00:00", "19:00:00")] {
+        let (status, _) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/shift-patterns"),
+            Some(json!({ "name": name, "start_time": start, "end_time": end })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+
+    sqlx::query("UPDATE units SET planning_granularity = 'hour' WHERE unit_id = $1")
+        .bind(unit_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (status, report) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/config-issues"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{report}");
+    assert_eq!(report["planning_granularity"], "hour");
+    let issues = report["issues"].as_array().unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0]["kind"], "shift_misaligned");
+
+    sqlx::query("UPDATE units SET planning_granularity = 'shift' WHERE unit_id = $1")
+        .bind(unit_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let (_, report) = req(
+        &app,
+        "GET",
+        &format!("/api/v1/units/{unit_id}/config-issues"),
+        None,
+    )
+    .await;
+    let issues = report["issues"].as_array().unwrap();
+    assert_eq!(issues.len(), 1, "{report}");
+    assert_eq!(issues[0]["kind"], "shift_overlap");
+
+    let (status, _) = req(&app, "GET", "/api/v1/units/9999/config-issues", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn typed_errors_expose_machine_readable_codes() {
+    let (app, _pool) = setup().await;
+    let (org_id, unit_id) = seed_org_and_unit(&app).await;
+
+    // Missing rows surface as 404s, not 500s with "no rows returned".
+    let (status, body) = req(&app, "GET", "/api/v1/units/999999", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+    assert_eq!(body["error"]["code"], "not_found");
+    let (status, _) = req(&app, "GET", "/api/v1/organizations/999999", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Semantic mistakes are 422s with code "validation".
+    let (status, body) = req(
+        &app,
+        "POST",
+        &format!("/api/v1/organizations/{org_id}/units"),
+        Some(json!({ "name": "Ward B", "site_id": 999999 })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["error"]["code"], "validation");
+
+    // Unique violations become 409s with code "conflict".
+    for _ in 0..2 {
+        let (status, body) = req(
+            &app,
+            "POST",
+            &format!("/api/v1/units/{unit_id}/staffs"),
+            Some(json!({ "code": "N1", "full_name": "Alice" })),
+        )
+        .await;
+        if status == StatusCode::CREATED {
+            continue;
+        }
+        assert_eq!(status, StatusCode::CONFLICT, "{body}");
+        assert_eq!(body["error"]["code"], "conflict");
+        return;
+    }
+    panic!("duplicate staff code was accepted twice");
+}
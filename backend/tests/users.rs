@@ -0,0 +1,186 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use common::{req, req_with_headers, setup};
+
+#[tokio::test]
+async fn login_verification_upgrades_legacy_hashes() {
+    let (_app, pool) = setup().await;
+
+    // A pre-hashing row: the "hash" column holds the raw secret. The API
+    // hashes everything now, so seed it straight into the table.
+    let (user_id,): (i64,) = sqlx::query_as(
+        "INSERT INTO users (full_name, password_hash) VALUES ('Legacy', 's3cret')
+         RETURNING user_id",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+
+    let ok = maywin_api::routes::users::verify_and_upgrade(&pool, user_id, "s3cret")
+        .await
+        .unwrap();
+    assert!(ok);
+    let (stored,): (String,) =
+        sqlx::query_as("SELECT password_hash FROM users WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(stored.starts_with("$argon2"));
+
+    // The upgraded hash still verifies, and wrong passwords still fail.
+    assert!(maywin_api::routes::users::verify_and_upgrade(&pool, user_id, "s3cret")
+        .await
+        .unwrap());
+    assert!(!maywin_api::routes::users::verify_and_upgrade(&pool, user_id, "nope")
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn batch_rehash_reports_upgraded_rows() {
+    let (app, pool) = setup().await;
+    for name in ["One", "Two"] {
+        sqlx::query("INSERT INTO users (full_name, password_hash) VALUES ($1, 'plain')")
+            .bind(name)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    let (status, result) = req(&app, "POST", "/api/v1/admin/rehash-passwords", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(result["upgraded"], 2);
+
+    // A second pass finds nothing left to upgrade.
+    let (_, result) = req(&app, "POST", "/api/v1/admin/rehash-passwords", None).await;
+    assert_eq!(result["upgraded"], 0);
+}
+
+#[tokio::test]
+async fn passwords_are_hashed_and_never_echoed() {
+    let (app, pool) = setup().await;
+
+    let (status, user) = req(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "full_name": "Fresh", "password": "hunter2!" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert!(user.get("password_hash").is_none(), "{user}");
+    let user_id = user["user_id"].as_i64().unwrap();
+
+    // Stored as argon2, and the original password verifies against it.
+    let (stored,): (String,) =
+        sqlx::query_as("SELECT password_hash FROM users WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(stored.starts_with("$argon2"));
+    assert!(maywin_api::routes::users::verify_and_upgrade(&pool, user_id, "hunter2!")
+        .await
+        .unwrap());
+
+    // Short passwords are rejected up front.
+    let (status, _) = req(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "full_name": "Blank", "password": "short" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // Salted: the same password hashes differently for another user.
+    let (_, twin) = req(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "full_name": "Twin", "password": "hunter2!" })),
+    )
+    .await;
+    let twin_id = twin["user_id"].as_i64().unwrap();
+    let (twin_stored,): (String,) =
+        sqlx::query_as("SELECT password_hash FROM users WHERE user_id = $1")
+            .bind(twin_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_ne!(stored, twin_stored);
+
+    // Patching with a new password re-hashes rather than storing verbatim.
+    let (status, patched) = req(
+        &app,
+        "PATCH",
+        &format!("/api/v1/users/{user_id}"),
+        Some(json!({ "password": "correct horse" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(patched.get("password_hash").is_none());
+    assert!(maywin_api::routes::users::verify_and_upgrade(&pool, user_id, "correct horse")
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn my_organizations_scopes_by_membership() {
+    let (app, _pool) = setup().await;
+
+    let (_, org_a) = req(&app, "POST", "/api/v1/organizations", Some(json!({ "name": "Hospital A" }))).await;
+    let (_, _org_b) = req(&app, "POST", "/api/v1/organizations", Some(json!({ "name": "Hospital B" }))).await;
+    let org_a_id = org_a["organization_id"].as_i64().unwrap();
+
+    let (_, member) = req(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "organization_id": org_a_id, "full_name": "Member", "password": "s3cret-pw" })),
+    )
+    .await;
+    let (_, admin) = req(
+        &app,
+        "POST",
+        "/api/v1/users",
+        Some(json!({ "full_name": "Admin", "role": "admin", "password": "s3cret-pw" })),
+    )
+    .await;
+
+    // A regular user sees only their own organization.
+    let member_id = member["user_id"].as_i64().unwrap().to_string();
+    let (status, orgs) = req_with_headers(
+        &app,
+        "GET",
+        "/api/v1/me/organizations",
+        None,
+        &[("x-user-id", member_id.as_str())],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let orgs = orgs.as_array().unwrap();
+    assert_eq!(orgs.len(), 1);
+    assert_eq!(orgs[0]["organization_id"], org_a_id);
+
+    // An org-less admin spans every organization.
+    let admin_id = admin["user_id"].as_i64().unwrap().to_string();
+    let (status, orgs) = req_with_headers(
+        &app,
+        "GET",
+        "/api/v1/me/organizations",
+        None,
+        &[("x-user-id", admin_id.as_str())],
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(orgs.as_array().unwrap().len(), 2);
+
+    // No identity header at all is rejected.
+    let (status, _) = req(&app, "GET", "/api/v1/me/organizations", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}